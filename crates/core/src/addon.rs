@@ -1,9 +1,18 @@
-use crate::{config::Flavor, curse_api, tukui_api, utility::strip_non_digits};
+use crate::{
+    config::{CustomFeedId, Flavor},
+    curse_api,
+    network::DownloadProgress,
+    toc::Toc,
+    tukui_api,
+    utility::strip_non_digits,
+    wowi_api,
+};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RemotePackage {
@@ -11,6 +20,24 @@ pub struct RemotePackage {
     pub download_url: String,
     pub date_time: Option<DateTime<Utc>>,
     pub file_id: Option<i64>,
+    /// Size of the archive in bytes, if the source provides it.
+    pub file_length: Option<u64>,
+}
+
+impl From<&curse_api::File> for RemotePackage {
+    fn from(file: &curse_api::File) -> Self {
+        let date_time = DateTime::parse_from_rfc3339(&file.file_date)
+            .map(|d| d.with_timezone(&Utc))
+            .ok();
+
+        RemotePackage {
+            version: file.display_name.clone(),
+            download_url: file.download_url.clone(),
+            date_time,
+            file_id: Some(file.id),
+            file_length: file.file_length,
+        }
+    }
 }
 
 impl PartialOrd for RemotePackage {
@@ -67,6 +94,7 @@ pub enum AddonState {
     Downloading,
     Fingerprint,
     Unpacking,
+    Modified,
     Updatable,
 }
 
@@ -91,13 +119,26 @@ pub struct Addon {
     pub file_id: Option<i64>,
     pub website_url: Option<String>,
     pub path: PathBuf,
-    pub dependencies: Vec<String>,
+    /// Module folders shipped in the same package, not including the addon folder.
+    pub bundled_modules: Vec<String>,
     pub state: AddonState,
     pub wowi_id: Option<String>,
     pub tukui_id: Option<String>,
     pub curse_id: Option<u32>,
+    /// The custom feed the addon was installed from.
+    pub custom_feed_id: Option<CustomFeedId>,
     pub fingerprint: Option<u32>,
     pub game_version: Option<String>,
+    /// Module folders which differs from the remote file we matched against.
+    pub modified_modules: Vec<String>,
+    /// Code files which no `.toc` or `.xml` file references, found when verifying.
+    pub extra_files: Vec<String>,
+    /// The remote file we partially matched against. Used to restore a pristine copy.
+    pub repair_package: Option<RemotePackage>,
+    /// Everything parsed from the addon's `.toc` file.
+    pub toc: Toc,
+    /// Progress of the current download. Shared by every clone of the addon.
+    pub download_progress: Arc<DownloadProgress>,
 
     // States for GUI
     #[cfg(feature = "gui")]
@@ -105,6 +146,8 @@ pub struct Addon {
     #[cfg(feature = "gui")]
     pub update_btn_state: iced_native::button::State,
     #[cfg(feature = "gui")]
+    pub repair_btn_state: iced_native::button::State,
+    #[cfg(feature = "gui")]
     pub force_btn_state: iced_native::button::State,
     #[cfg(feature = "gui")]
     pub delete_btn_state: iced_native::button::State,
@@ -115,6 +158,8 @@ pub struct Addon {
     #[cfg(feature = "gui")]
     pub website_btn_state: iced_native::button::State,
     #[cfg(feature = "gui")]
+    pub install_dependencies_btn_state: iced_native::button::State,
+    #[cfg(feature = "gui")]
     pub pick_release_channel_state: iced_native::pick_list::State<ReleaseChannel>,
 }
 
@@ -128,7 +173,6 @@ impl Addon {
         notes: Option<String>,
         version: Option<String>,
         path: PathBuf,
-        dependencies: Vec<String>,
         wowi_id: Option<String>,
         tukui_id: Option<String>,
         curse_id: Option<u32>,
@@ -144,18 +188,26 @@ impl Addon {
             file_id: None,
             website_url: None,
             path,
-            dependencies,
+            bundled_modules: vec![],
             state: AddonState::Ajour(None),
             wowi_id,
             tukui_id,
             curse_id,
+            custom_feed_id: None,
             fingerprint: None,
             game_version: None,
+            modified_modules: vec![],
+            extra_files: vec![],
+            repair_package: None,
+            toc: Default::default(),
+            download_progress: Default::default(),
             #[cfg(feature = "gui")]
             details_btn_state: Default::default(),
             #[cfg(feature = "gui")]
             update_btn_state: Default::default(),
             #[cfg(feature = "gui")]
+            repair_btn_state: Default::default(),
+            #[cfg(feature = "gui")]
             force_btn_state: Default::default(),
             #[cfg(feature = "gui")]
             delete_btn_state: Default::default(),
@@ -166,6 +218,8 @@ impl Addon {
             #[cfg(feature = "gui")]
             website_btn_state: Default::default(),
             #[cfg(feature = "gui")]
+            install_dependencies_btn_state: Default::default(),
+            #[cfg(feature = "gui")]
             pick_release_channel_state: Default::default(),
         }
     }
@@ -182,18 +236,26 @@ impl Addon {
             file_id: None,
             website_url: None,
             path: Default::default(),
-            dependencies: Default::default(),
+            bundled_modules: Default::default(),
             state: AddonState::Ajour(None),
             wowi_id: None,
             tukui_id: None,
             curse_id: None,
+            custom_feed_id: None,
             fingerprint: None,
             game_version: None,
+            modified_modules: vec![],
+            extra_files: vec![],
+            repair_package: None,
+            toc: Default::default(),
+            download_progress: Default::default(),
             #[cfg(feature = "gui")]
             details_btn_state: Default::default(),
             #[cfg(feature = "gui")]
             update_btn_state: Default::default(),
             #[cfg(feature = "gui")]
+            repair_btn_state: Default::default(),
+            #[cfg(feature = "gui")]
             force_btn_state: Default::default(),
             #[cfg(feature = "gui")]
             delete_btn_state: Default::default(),
@@ -204,6 +266,8 @@ impl Addon {
             #[cfg(feature = "gui")]
             website_btn_state: Default::default(),
             #[cfg(feature = "gui")]
+            install_dependencies_btn_state: Default::default(),
+            #[cfg(feature = "gui")]
             pick_release_channel_state: Default::default(),
         }
     }
@@ -218,25 +282,38 @@ impl Addon {
         let version = package.version.clone();
         let download_url = package.url.clone();
 
-        let date_time = NaiveDateTime::parse_from_str(&package.lastupdate, "%Y-%m-%d")
-            .map_or(
-                NaiveDateTime::parse_from_str(&package.lastupdate, "%Y-%m-%d %H:%M:%S"),
-                Result::Ok,
-            )
-            .map(|d| Utc.from_utc_datetime(&d))
-            .ok();
+        let date_time = package.date_time();
 
         let package = RemotePackage {
             version,
             download_url,
             date_time,
             file_id: None,
+            file_length: None,
         };
 
         // Since Tukui does not support release channels, our default is 'stable'.
         self.remote_packages.insert(ReleaseChannel::Stable, package);
     }
 
+    /// Package from WoWInterface.
+    ///
+    /// This function takes a `WowIPackage` and updates self with the information.
+    pub fn apply_wowi_package(&mut self, package: &wowi_api::WowIPackage) {
+        self.website_url = Some(package.website_url());
+
+        let package = RemotePackage {
+            version: package.version.clone(),
+            download_url: package.download_url.clone(),
+            date_time: Utc.timestamp_millis_opt(package.date).single(),
+            file_id: None,
+            file_length: None,
+        };
+
+        // WoWInterface does not support release channels either.
+        self.remote_packages.insert(ReleaseChannel::Stable, package);
+    }
+
     /// Package from Curse.
     ///
     /// This function takes a `Package` and updates self with the information
@@ -250,15 +327,30 @@ impl Addon {
         info: &curse_api::AddonFingerprintInfo,
         flavor: Flavor,
     ) {
-        let dependencies: Vec<String> = info
+        let bundled_modules: Vec<String> = info
             .file
             .modules
             .iter()
+            .filter(|m| m.foldername != self.id)
             .map(|m| m.foldername.clone())
             .collect();
 
+        self.apply_curse_files(&info.latest_files, flavor);
+
+        self.bundled_modules = bundled_modules;
+        self.version = Some(info.file.display_name.clone());
+        self.curse_id = Some(info.id);
+        self.file_id = Some(info.file.id);
+        self.game_version = info.file.game_version.get(0).cloned();
+    }
+
+    /// Latest files from Curse.
+    ///
+    /// This function takes the latest files of a `Package` and stores the
+    /// file for `flavor` of each release channel.
+    pub fn apply_curse_files(&mut self, files: &[curse_api::File], flavor: Flavor) {
         let flavor = format!("wow_{}", flavor.to_string());
-        for file in info.latest_files.iter() {
+        for file in files.iter() {
             let game_version_flavor = file.game_version_flavor.as_ref();
             if !file.is_alternate && game_version_flavor == Some(&flavor) {
                 let version = file.display_name.clone();
@@ -271,6 +363,7 @@ impl Addon {
                     download_url,
                     date_time,
                     file_id: Some(file.id),
+                    file_length: file.file_length,
                 };
 
                 match file.release_type {
@@ -287,12 +380,58 @@ impl Addon {
                 };
             }
         }
+    }
 
-        self.dependencies = dependencies;
-        self.version = Some(info.file.display_name.clone());
-        self.curse_id = Some(info.id);
-        self.file_id = Some(info.file.id);
-        self.game_version = info.file.game_version.get(0).cloned();
+    /// Applies a partial fingerprint match.
+    ///
+    /// A partial match means one or more of the module folders differ from the
+    /// remote file, e.g. because a file was modified locally or a module is missing.
+    /// `local_fingerprints` maps each local folder name to its fingerprint.
+    pub fn apply_partial_fingerprint_module(
+        &mut self,
+        info: &curse_api::AddonFingerprintInfo,
+        flavor: Flavor,
+        local_fingerprints: &HashMap<String, u32>,
+    ) {
+        self.apply_fingerprint_module(info, flavor);
+
+        self.modified_modules = info
+            .file
+            .modules
+            .iter()
+            .filter(|m| local_fingerprints.get(&m.foldername) != Some(&m.fingerprint))
+            .map(|m| m.foldername.clone())
+            .collect();
+
+        self.repair_package = Some(RemotePackage::from(&info.file));
+    }
+
+    /// Returns the addon folder followed by every bundled module folder.
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders = vec![self.id.clone()];
+        folders.extend(
+            self.bundled_modules
+                .iter()
+                .filter(|m| *m != &self.id)
+                .cloned(),
+        );
+
+        folders
+    }
+
+    /// Function returns a `bool` indicating if any of the addon modules differs from
+    /// the remote file.
+    pub fn is_modified(&self) -> bool {
+        !self.modified_modules.is_empty()
+    }
+
+    /// Function returns a `bool` indicating if the game client will refuse to load the
+    /// addon, unless out of date addons are allowed, based on the `.toc` interface.
+    pub fn is_out_of_date(&self, game_interface: Option<u32>) -> bool {
+        match (self.toc.interface, game_interface) {
+            (Some(interface), Some(game_interface)) => interface < game_interface,
+            _ => false,
+        }
     }
 
     /// Function returns a `bool` indicating if the user has manually ignored the addon.
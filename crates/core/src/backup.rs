@@ -1,3 +1,4 @@
+use crate::config::{Config, Flavor};
 use crate::error::ClientError;
 use crate::fs::backup::{Backup, ZipBackup};
 use crate::Result;
@@ -28,6 +29,32 @@ pub async fn backup_folders(
     Ok(as_of)
 }
 
+/// Returns the AddOns and WTF folders of both flavors which exist in the
+/// World of Warcraft directory.
+pub fn wow_backup_folders(config: &Config) -> Vec<BackupFolder> {
+    let mut src_folders = vec![];
+
+    let wow_dir = match config.wow.directory.as_ref() {
+        Some(wow_dir) => wow_dir,
+        None => return src_folders,
+    };
+
+    for flavor in Flavor::ALL.iter() {
+        let addon_dir = config.get_addon_directory_for_flavor(flavor).unwrap();
+        let wtf_dir = config.get_wtf_directory_for_flavor(flavor).unwrap();
+
+        if addon_dir.exists() {
+            src_folders.push(BackupFolder::new(&addon_dir, wow_dir));
+        }
+
+        if wtf_dir.exists() {
+            src_folders.push(BackupFolder::new(&wtf_dir, wow_dir));
+        }
+    }
+
+    src_folders
+}
+
 /// Finds the latest archive in the supplied backup folder and returns
 /// the datetime it was saved
 pub async fn latest_backup(backup_dir: PathBuf) -> Option<NaiveDateTime> {
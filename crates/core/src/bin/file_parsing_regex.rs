@@ -1,8 +1,9 @@
-use ajour_core::parse::file_parsing_regex;
+use ajour_core::config::Endpoints;
+use ajour_core::parse::Fingerprinter;
 use async_std::task;
 
 fn main() {
     task::block_on(async move {
-        file_parsing_regex().await.unwrap();
+        Fingerprinter::load(&Endpoints::from_env()).await.unwrap();
     });
 }
@@ -1,4 +1,5 @@
-use ajour_core::parse::{file_parsing_regex, fingerprint_addon_dir, ParsingPatterns};
+use ajour_core::config::Endpoints;
+use ajour_core::parse::Fingerprinter;
 use async_std::task;
 use std::env;
 use std::path::PathBuf;
@@ -14,21 +15,9 @@ fn main() {
     let path = PathBuf::from(args.next().unwrap());
 
     task::block_on(async move {
-        let parsing_patterns = file_parsing_regex().await.unwrap();
+        let fingerprinter = Fingerprinter::load(&Endpoints::from_env()).await.unwrap();
 
-        let ParsingPatterns {
-            initial_inclusion_regex,
-            extra_inclusion_regex,
-            file_parsing_regex,
-        } = parsing_patterns;
-
-        let fingerprint = fingerprint_addon_dir(
-            &path,
-            &initial_inclusion_regex,
-            &extra_inclusion_regex,
-            &file_parsing_regex,
-        )
-        .unwrap();
+        let fingerprint = fingerprinter.fingerprint(&path).unwrap();
 
         println!("Fingerprint is {}", fingerprint);
     });
@@ -1,9 +1,10 @@
-use ajour_core::config::Flavor;
+use ajour_core::config::{Endpoints, Flavor};
 use ajour_core::parse::read_addon_directory;
 use async_std::{
     sync::{Arc, Mutex},
     task,
 };
+use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 
@@ -33,9 +34,17 @@ fn main() {
     };
 
     task::block_on(async move {
-        let addons = read_addon_directory(collection, &path, Flavor::Classic)
-            .await
-            .unwrap();
+        // Addons from custom feeds aren't looked up.
+        let addons = read_addon_directory(
+            collection,
+            &Endpoints::from_env(),
+            &[],
+            &HashMap::new(),
+            &path,
+            Flavor::Classic,
+        )
+        .await
+        .unwrap();
 
         print!("{} addons parsed", addons.len());
     });
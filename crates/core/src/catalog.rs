@@ -1,40 +1,880 @@
-use crate::config::Flavor;
+use crate::addon::{Addon, ReleaseChannel, RemotePackage};
+use crate::config::{CatalogFeeds, CustomFeed, CustomFeedId, Endpoints, Flavor};
 use crate::error::ClientError;
+use crate::fs::config_dir;
+use crate::murmur2::calculate_hash;
 use crate::network::request_async;
+use crate::utility::{html_to_text, normalize_name};
 use crate::Result;
+use crate::{curse_api, tukui_api, wowi_api};
 
-use isahc::{config::RedirectPolicy, prelude::*};
-use serde::Deserialize;
+use chrono::{DateTime, TimeZone, Utc};
 
-const CATALOG_URL: &str =
-    "https://raw.githubusercontent.com/casperstorm/ajour-catalog/master/curse.json";
+use isahc::http::{
+    header::{HeaderName, ETAG, LAST_MODIFIED},
+    StatusCode,
+};
+use isahc::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
-pub async fn get_catalog() -> Result<Catalog> {
-    let client = HttpClient::builder()
-        .redirect_policy(RedirectPolicy::Follow)
-        .max_connections_per_host(6)
-        .build()
-        .unwrap();
+/// Returns the catalog built from every enabled feed, using the cached copy
+/// of each feed which hasn't changed since it was cached.
+pub async fn get_catalog(endpoints: &Endpoints, feeds: &CatalogFeeds) -> Result<Catalog> {
+    match refresh_catalog(endpoints, feeds).await? {
+        Some(catalog) => Ok(catalog),
+        None => load_cached_catalog(endpoints, feeds)
+            .await
+            .ok_or_else(|| ClientError::Custom("Couldn't read the cached catalog".to_owned())),
+    }
+}
 
-    let mut resp = request_async(&client, CATALOG_URL, vec![], Some(30)).await?;
+/// Returns the catalog built from the feeds cached by the last refresh, if any.
+pub async fn load_cached_catalog(endpoints: &Endpoints, feeds: &CatalogFeeds) -> Option<Catalog> {
+    let addons: Vec<_> = Feed::enabled(endpoints, feeds)
+        .iter()
+        .filter_map(|feed| {
+            let body = feed.cached_body()?;
+
+            match feed.parse(&body) {
+                Ok(addons) => Some(addons),
+                Err(e) => {
+                    log::error!("couldn't parse cached catalog feed {}: {}", feed.url(), e);
+                    None
+                }
+            }
+        })
+        .flatten()
+        .collect();
 
-    if resp.status().is_success() {
-        let catalog = resp.json()?;
-        Ok(catalog)
+    if addons.is_empty() {
+        None
     } else {
-        Err(ClientError::Custom(format!(
-            "Couldn't fetch catalog: {}",
+        Some(Catalog::merge(addons))
+    }
+}
+
+/// Downloads every enabled feed, unless the cached copy is still current
+/// according to the server. Returns `None` if the cached catalog can be used.
+///
+/// A feed which can't be downloaded is left out, or its cached copy is used,
+/// so the catalog is only an error if no feed is available at all.
+pub async fn refresh_catalog(
+    endpoints: &Endpoints,
+    feeds: &CatalogFeeds,
+) -> Result<Option<Catalog>> {
+    let mut bodies = vec![];
+    let mut is_modified = false;
+    let mut last_error = None;
+
+    for feed in Feed::enabled(endpoints, feeds) {
+        let body = match feed.fetch().await {
+            Ok(Some(body)) => {
+                is_modified = true;
+                Some(body)
+            }
+            Ok(None) => feed.cached_body(),
+            Err(e) => {
+                log::error!("couldn't fetch catalog feed {}: {}", feed.url(), e);
+                last_error = Some(e);
+                feed.cached_body()
+            }
+        };
+
+        if let Some(body) = body {
+            bodies.push((feed, body));
+        }
+    }
+
+    // Nothing has changed, so the feeds are only parsed when the cached
+    // catalog is loaded.
+    if !is_modified {
+        return match last_error {
+            Some(e) if bodies.is_empty() => Err(e),
+            _ => Ok(None),
+        };
+    }
+
+    let mut addons = vec![];
+    for (feed, body) in bodies {
+        match feed.parse(&body) {
+            Ok(feed_addons) => addons.extend(feed_addons),
+            Err(e) => {
+                log::error!("couldn't parse catalog feed {}: {}", feed.url(), e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    if addons.is_empty() {
+        if let Some(e) = last_error {
+            return Err(e);
+        }
+    }
+
+    Ok(Some(Catalog::merge(addons)))
+}
+
+/// A remote list of addons the catalog is built from.
+#[derive(Debug, Clone, PartialEq)]
+enum Feed {
+    /// A list of `CatalogAddon`.
+    Curse(String),
+    /// The addon list of the Tukui API, which has separate ids for each flavor.
+    Tukui(Flavor, String),
+    /// The file list of the WoWInterface API.
+    WowI(String),
+    /// A list of `CatalogAddon` added by the user.
+    Custom(CustomFeed),
+}
+
+impl Feed {
+    fn enabled(endpoints: &Endpoints, feeds: &CatalogFeeds) -> Vec<Feed> {
+        let mut enabled = vec![];
+
+        if feeds.curse {
+            enabled.push(Feed::Curse(endpoints.catalog().to_owned()));
+        }
+
+        if feeds.tukui {
+            enabled.push(Feed::Tukui(
+                Flavor::Retail,
+                format!("{}?addons=all", endpoints.tukui()),
+            ));
+            enabled.push(Feed::Tukui(
+                Flavor::Classic,
+                format!("{}?classic-addons=all", endpoints.tukui()),
+            ));
+        }
+
+        if feeds.wowi {
+            enabled.push(Feed::WowI(format!("{}/filelist.json", endpoints.wowi())));
+        }
+
+        enabled.extend(feeds.custom.iter().cloned().map(Feed::Custom));
+
+        enabled
+    }
+
+    fn url(&self) -> &str {
+        match self {
+            Feed::Curse(url) | Feed::Tukui(_, url) | Feed::WowI(url) => url,
+            Feed::Custom(feed) => &feed.url,
+        }
+    }
+
+    /// Fetches the feed, returning `None` if the cached copy is still current.
+    ///
+    /// Local files aren't cached, so they are always read from disk instead.
+    async fn fetch(&self) -> Result<Option<String>> {
+        match self {
+            Feed::Custom(feed) if feed.path().is_some() => Ok(None),
+            _ => fetch_cached(&self.cache_name(), self.url()).await,
+        }
+    }
+
+    /// Returns the cached copy of the feed, or the content of a local file.
+    fn cached_body(&self) -> Option<String> {
+        if let Feed::Custom(feed) = self {
+            if let Some(path) = feed.path() {
+                return std::fs::read_to_string(&path)
+                    .map_err(|e| log::error!("couldn't read catalog feed {}: {}", feed.url, e))
+                    .ok();
+            }
+        }
+
+        CachedResponse::body(&self.cache_name(), self.url())
+    }
+
+    /// Name the feed is cached under. Custom feeds are keyed on their url, so
+    /// feeds whose names only differ in case or punctuation don't share a file.
+    fn cache_name(&self) -> String {
+        match self {
+            Feed::Curse(_) => "curse".to_owned(),
+            Feed::Tukui(flavor, _) => format!("tukui-{}", flavor),
+            Feed::WowI(_) => "wowi".to_owned(),
+            Feed::Custom(feed) => format!("custom-{:08x}", calculate_hash(feed.url.as_bytes(), 1)),
+        }
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<CatalogAddon>> {
+        let addons = match self {
+            Feed::Curse(_) => serde_json::from_str::<Catalog>(body)?.addons,
+            Feed::Tukui(flavor, _) => serde_json::from_str::<Vec<TukuiCatalogAddon>>(body)?
+                .into_iter()
+                .filter_map(|a| a.into_catalog_addon(*flavor))
+                .collect(),
+            Feed::WowI(_) => serde_json::from_str::<Vec<WowICatalogAddon>>(body)?
+                .into_iter()
+                .filter_map(WowICatalogAddon::into_catalog_addon)
+                .collect(),
+            Feed::Custom(feed) => serde_json::from_str::<Vec<CustomCatalogAddon>>(body)?
+                .into_iter()
+                .map(|a| a.into_catalog_addon(&feed.name))
+                .collect(),
+        };
+
+        Ok(addons)
+    }
+}
+
+/// An addon in the addon list of the Tukui API.
+#[derive(Debug, Deserialize)]
+struct TukuiCatalogAddon {
+    id: String,
+    name: String,
+    small_desc: Option<String>,
+    category: Option<String>,
+    downloads: Option<String>,
+    web_url: String,
+}
+
+impl TukuiCatalogAddon {
+    fn into_catalog_addon(self, flavor: Flavor) -> Option<CatalogAddon> {
+        Some(CatalogAddon {
+            id: self.id.parse().ok()?,
+            website_url: self.web_url,
+            name: self.name,
+            categories: self.category.into_iter().collect(),
+            summary: self.small_desc.unwrap_or_default(),
+            number_of_downloads: self
+                .downloads
+                .and_then(|d| d.parse().ok())
+                .unwrap_or_default(),
+            source: Source::Tukui,
+            flavors: vec![flavor],
+            download_url: None,
+            version: None,
+            alternatives: vec![],
+        })
+    }
+}
+
+/// An addon in the file list of the WoWInterface API.
+#[derive(Debug, Deserialize)]
+struct WowICatalogAddon {
+    #[serde(rename = "UID")]
+    id: String,
+    #[serde(rename = "UIName")]
+    name: String,
+    #[serde(rename = "UIDownloadTotal")]
+    downloads: Option<serde_json::Value>,
+    #[serde(rename = "UICompatibility")]
+    compatibility: Option<Vec<WowICompatibility>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WowICompatibility {
+    version: String,
+}
+
+impl WowICatalogAddon {
+    fn into_catalog_addon(self) -> Option<CatalogAddon> {
+        // The download count is sent as a string or a number.
+        let number_of_downloads = match self.downloads {
+            Some(serde_json::Value::String(d)) => d.parse().ok(),
+            Some(serde_json::Value::Number(d)) => d.as_u64(),
+            _ => None,
+        };
+
+        // Classic clients are 1.x. Without any compatibility info, we assume retail.
+        let mut flavors: Vec<_> = self
+            .compatibility
+            .unwrap_or_default()
+            .iter()
+            .map(|c| {
+                if c.version.starts_with("1.") {
+                    Flavor::Classic
+                } else {
+                    Flavor::Retail
+                }
+            })
+            .collect();
+        flavors.sort();
+        flavors.dedup();
+        if flavors.is_empty() {
+            flavors.push(Flavor::Retail);
+        }
+
+        Some(CatalogAddon {
+            website_url: wowi_api::website_url(&self.id),
+            id: self.id.parse().ok()?,
+            name: self.name,
+            categories: vec![],
+            summary: String::new(),
+            number_of_downloads: number_of_downloads.unwrap_or_default(),
+            source: Source::WowI,
+            flavors,
+            download_url: None,
+            version: None,
+            alternatives: vec![],
+        })
+    }
+}
+
+/// An addon in a custom feed. It has the same fields as `CatalogAddon`, along
+/// with the file to download, since there is no API to fetch it from.
+#[serde(rename_all = "camelCase")]
+#[derive(Debug, Deserialize)]
+struct CustomCatalogAddon {
+    id: u32,
+    #[serde(default)]
+    website_url: String,
+    name: String,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    number_of_downloads: u64,
+    flavors: Vec<Flavor>,
+    download_url: String,
+    version: String,
+}
+
+impl CustomCatalogAddon {
+    fn into_catalog_addon(self, feed_name: &str) -> CatalogAddon {
+        CatalogAddon {
+            id: self.id,
+            website_url: self.website_url,
+            name: self.name,
+            categories: self.categories,
+            summary: self.summary,
+            number_of_downloads: self.number_of_downloads,
+            source: Source::Custom(feed_name.to_owned()),
+            flavors: self.flavors,
+            download_url: Some(self.download_url),
+            version: Some(self.version),
+            alternatives: vec![],
+        }
+    }
+}
+
+/// Returns `addon` with the file listed for `catalog_addon` in its custom feed.
+pub async fn latest_addon_from_feed(
+    catalog_addon: CatalogAddon,
+    mut addon: Addon,
+    mut addon_path: PathBuf,
+    flavor: Flavor,
+) -> Result<(u32, Flavor, Addon)> {
+    let package = feed_package(&catalog_addon).ok_or_else(|| {
+        ClientError::Custom(format!("{} has no file to download", catalog_addon.name))
+    })?;
+
+    if let Source::Custom(feed) = &catalog_addon.source {
+        addon.custom_feed_id = Some(CustomFeedId {
+            feed: feed.clone(),
+            id: catalog_addon.id,
+        });
+    }
+
+    addon_path.push(&catalog_addon.name);
+
+    addon.title = catalog_addon.name.clone();
+    addon.id = catalog_addon.name.clone();
+    addon.path = addon_path;
+    if !catalog_addon.summary.is_empty() {
+        addon.notes = Some(catalog_addon.summary);
+    }
+    if !catalog_addon.website_url.is_empty() {
+        addon.website_url = Some(catalog_addon.website_url);
+    }
+
+    addon
+        .remote_packages
+        .insert(ReleaseChannel::Stable, package);
+
+    Ok((catalog_addon.id, flavor, addon))
+}
+
+/// Sets the file listed in its custom feed as the remote package of every
+/// addon installed from one.
+///
+/// A feed which can't be fetched is logged and skipped, so its addons keep
+/// their current remote packages.
+pub async fn apply_custom_feeds(feeds: &[CustomFeed], flavor: Flavor, addons: &mut [Addon]) {
+    for feed in feeds {
+        let is_from_feed = |addon: &Addon| {
+            addon
+                .custom_feed_id
+                .as_ref()
+                .map_or(false, |c| c.feed == feed.name)
+        };
+
+        if !addons.iter().any(is_from_feed) {
+            continue;
+        }
+
+        let feed_addons = match fetch_custom_feed(feed).await {
+            Ok(feed_addons) => feed_addons,
+            Err(e) => {
+                log::error!(
+                    "{} - couldn't fetch catalog feed {}: {}",
+                    flavor,
+                    feed.url,
+                    e
+                );
+                continue;
+            }
+        };
+
+        for addon in addons.iter_mut().filter(|a| is_from_feed(a)) {
+            let id = addon.custom_feed_id.as_ref().map(|c| c.id);
+            let package = feed_addons
+                .iter()
+                .find(|a| Some(a.id) == id && a.flavors.contains(&flavor))
+                .and_then(feed_package);
+
+            if let Some(package) = package {
+                addon.remote_packages.clear();
+                addon
+                    .remote_packages
+                    .insert(ReleaseChannel::Stable, package);
+            }
+        }
+    }
+}
+
+/// Fetches a single custom feed, using the cached copy if it's still current.
+async fn fetch_custom_feed(feed: &CustomFeed) -> Result<Vec<CatalogAddon>> {
+    let feed = Feed::Custom(feed.clone());
+
+    let body = match feed.fetch().await? {
+        Some(body) => body,
+        None => feed.cached_body().ok_or_else(|| {
+            ClientError::Custom(format!("Couldn't read catalog feed {}", feed.url()))
+        })?,
+    };
+
+    feed.parse(&body)
+}
+
+/// Returns the file listed for an addon in its custom feed.
+fn feed_package(catalog_addon: &CatalogAddon) -> Option<RemotePackage> {
+    Some(RemotePackage {
+        version: catalog_addon.version.clone().unwrap_or_default(),
+        download_url: catalog_addon.download_url.clone()?,
+        date_time: None,
+        file_id: None,
+        file_length: None,
+    })
+}
+
+/// Everything shown in the detail view of a catalog addon.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogAddonDetails {
+    pub description: String,
+    pub author: Option<String>,
+    /// Urls of the screenshots.
+    pub screenshots: Vec<String>,
+    /// Files which can be installed, newest first.
+    pub files: Vec<CatalogFile>,
+    /// Changelog of the newest file.
+    pub changelog: Option<String>,
+    pub dependencies: Vec<CatalogDependency>,
+}
+
+/// A file of a catalog addon which can be installed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogFile {
+    pub id: Option<i64>,
+    pub version: String,
+    pub release_channel: ReleaseChannel,
+    pub flavor: Flavor,
+    pub date_time: Option<DateTime<Utc>>,
+    pub game_versions: Vec<String>,
+    pub download_url: String,
+    pub file_length: Option<u64>,
+    /// Folders in the file, if known, where the first is the addon itself.
+    pub modules: Vec<String>,
+}
+
+impl CatalogFile {
+    fn from_curse_file(file: &curse_api::File) -> Option<CatalogFile> {
+        Some(CatalogFile {
+            id: Some(file.id),
+            version: file.display_name.clone(),
+            release_channel: file.release_channel()?,
+            flavor: file.flavor()?,
+            date_time: DateTime::parse_from_rfc3339(&file.file_date)
+                .map(|d| d.with_timezone(&Utc))
+                .ok(),
+            game_versions: file.game_version.clone(),
+            download_url: file.download_url.clone(),
+            file_length: file.file_length,
+            modules: file.modules.iter().map(|m| m.foldername.clone()).collect(),
+        })
+    }
+}
+
+/// Another catalog addon an addon depends on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogDependency {
+    pub source: Source,
+    pub id: u32,
+    pub required: bool,
+}
+
+/// Fetches the description, files, changelog and dependencies of `addon`
+/// from its source.
+pub async fn fetch_addon_details(
+    endpoints: &Endpoints,
+    addon: &CatalogAddon,
+) -> Result<CatalogAddonDetails> {
+    match &addon.source {
+        Source::Curse => fetch_curse_details(endpoints, addon).await,
+        Source::Tukui => fetch_tukui_details(endpoints, addon).await,
+        Source::WowI => fetch_wowi_details(endpoints, addon).await,
+        Source::Custom(_) => Ok(CatalogAddonDetails {
+            description: addon.summary.clone(),
+            files: feed_files(addon),
+            ..Default::default()
+        }),
+    }
+}
+
+async fn fetch_curse_details(
+    endpoints: &Endpoints,
+    addon: &CatalogAddon,
+) -> Result<CatalogAddonDetails> {
+    let package = curse_api::fetch_remote_package(endpoints, addon.id).await?;
+    let mut curse_files: Vec<_> = curse_api::fetch_files(endpoints, addon.id)
+        .await?
+        .into_iter()
+        .filter(|f| !f.is_alternate)
+        .collect();
+    curse_files.sort_by(|a, b| b.file_date.cmp(&a.file_date));
+
+    // The description and changelog are nice to have, so the details are
+    // still shown if they can't be fetched.
+    let description = match curse_api::fetch_description(endpoints, addon.id).await {
+        Ok(description) => html_to_text(&description),
+        Err(e) => {
+            log::error!("{}", e);
+            addon.summary.clone()
+        }
+    };
+
+    let changelog = match curse_files.first() {
+        Some(file) => curse_api::fetch_changelog(endpoints, addon.id, file.id)
+            .await
+            .map(|changelog| html_to_text(&changelog))
+            .map_err(|e| log::error!("{}", e))
+            .ok(),
+        None => None,
+    };
+
+    // Dependencies are listed for the newest stable file.
+    let mut dependencies: Vec<CatalogDependency> = vec![];
+    let dependency_file = curse_files
+        .iter()
+        .find(|f| f.release_channel() == Some(ReleaseChannel::Stable))
+        .or_else(|| curse_files.first());
+    if let Some(file) = dependency_file {
+        for dependency in &file.dependencies {
+            let is_listed = dependencies.iter().any(|d| d.id == dependency.addon_id);
+
+            if !is_listed && (dependency.is_required() || dependency.is_optional()) {
+                dependencies.push(CatalogDependency {
+                    source: Source::Curse,
+                    id: dependency.addon_id,
+                    required: dependency.is_required(),
+                });
+            }
+        }
+    }
+
+    let authors: Vec<_> = package.authors.iter().map(|a| a.name.as_str()).collect();
+
+    Ok(CatalogAddonDetails {
+        description,
+        author: if authors.is_empty() {
+            None
+        } else {
+            Some(authors.join(", "))
+        },
+        // The default attachment is the logo of the addon.
+        screenshots: package
+            .attachments
+            .iter()
+            .filter(|a| !a.is_default)
+            .map(|a| a.url.clone())
+            .collect(),
+        files: curse_files
+            .iter()
+            .filter_map(CatalogFile::from_curse_file)
+            .collect(),
+        changelog,
+        dependencies,
+    })
+}
+
+async fn fetch_tukui_details(
+    endpoints: &Endpoints,
+    addon: &CatalogAddon,
+) -> Result<CatalogAddonDetails> {
+    // Tukui lists retail and classic addons separately.
+    let flavor = addon.flavors.first().copied().unwrap_or(Flavor::Retail);
+    let package =
+        tukui_api::fetch_remote_package(endpoints, &addon.id.to_string(), &flavor).await?;
+
+    let changelog = match package.changelog {
+        Some(_) => tukui_api::fetch_changelog(&package)
+            .await
+            .map(|changelog| html_to_text(&changelog))
+            .map_err(|e| log::error!("{}", e))
+            .ok(),
+        None => None,
+    };
+
+    // Tukui only has the latest file.
+    let file = CatalogFile {
+        id: None,
+        version: package.version.clone(),
+        release_channel: ReleaseChannel::Stable,
+        flavor,
+        date_time: package.date_time(),
+        game_versions: package.patch.clone().into_iter().collect(),
+        download_url: package.url.clone(),
+        file_length: None,
+        modules: vec![],
+    };
+
+    Ok(CatalogAddonDetails {
+        description: package
+            .small_desc
+            .clone()
+            .unwrap_or_else(|| addon.summary.clone()),
+        author: package.author.clone(),
+        screenshots: package.screenshot_url.clone().into_iter().collect(),
+        files: vec![file],
+        changelog,
+        dependencies: vec![],
+    })
+}
+
+async fn fetch_wowi_details(
+    endpoints: &Endpoints,
+    addon: &CatalogAddon,
+) -> Result<CatalogAddonDetails> {
+    let package = wowi_api::fetch_remote_packages(endpoints, &[addon.id.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            ClientError::Custom(format!("Couldn't find addon {} on WoWInterface", addon.id))
+        })?;
+
+    // WoWInterface only has the latest file, which is for every flavor.
+    let files = addon
+        .flavors
+        .iter()
+        .map(|flavor| CatalogFile {
+            id: None,
+            version: package.version.clone(),
+            release_channel: ReleaseChannel::Stable,
+            flavor: *flavor,
+            date_time: Utc.timestamp_millis_opt(package.date).single(),
+            game_versions: vec![],
+            download_url: package.download_url.clone(),
+            file_length: None,
+            modules: vec![],
+        })
+        .collect();
+
+    Ok(CatalogAddonDetails {
+        description: package
+            .description
+            .clone()
+            .unwrap_or_else(|| addon.summary.clone()),
+        author: package.author.clone(),
+        screenshots: package.images.clone().unwrap_or_default(),
+        files,
+        changelog: package.changelog.clone(),
+        dependencies: vec![],
+    })
+}
+
+/// Returns the file listed for an addon from a custom feed, for each flavor.
+fn feed_files(addon: &CatalogAddon) -> Vec<CatalogFile> {
+    let download_url = match &addon.download_url {
+        Some(download_url) => download_url,
+        None => return vec![],
+    };
+
+    addon
+        .flavors
+        .iter()
+        .map(|flavor| CatalogFile {
+            id: None,
+            version: addon.version.clone().unwrap_or_default(),
+            release_channel: ReleaseChannel::Stable,
+            flavor: *flavor,
+            date_time: None,
+            game_versions: vec![],
+            download_url: download_url.clone(),
+            file_length: None,
+            modules: vec![],
+        })
+        .collect()
+}
+
+/// Returns `addon` set up to install `file` of `catalog_addon`. The addon
+/// keeps the release channel of the file, so it's updated from that channel.
+pub fn addon_from_file(
+    catalog_addon: &CatalogAddon,
+    file: &CatalogFile,
+    mut addon: Addon,
+    mut addon_path: PathBuf,
+) -> Addon {
+    let id = file
+        .modules
+        .first()
+        .cloned()
+        .unwrap_or_else(|| catalog_addon.name.clone());
+
+    addon_path.push(&id);
+
+    addon.id = id;
+    addon.title = catalog_addon.name.clone();
+    addon.path = addon_path;
+    addon.bundled_modules = file.modules.iter().skip(1).cloned().collect();
+    addon.game_version = file.game_versions.first().cloned();
+    if !catalog_addon.website_url.is_empty() {
+        addon.website_url = Some(catalog_addon.website_url.clone());
+    }
+
+    match &catalog_addon.source {
+        Source::Curse => addon.curse_id = Some(catalog_addon.id),
+        Source::Tukui => addon.tukui_id = Some(catalog_addon.id.to_string()),
+        Source::WowI => addon.wowi_id = Some(catalog_addon.id.to_string()),
+        Source::Custom(_) => {}
+    }
+
+    let package = RemotePackage {
+        version: file.version.clone(),
+        download_url: file.download_url.clone(),
+        date_time: file.date_time,
+        file_id: file.id,
+        file_length: file.file_length,
+    };
+    addon.remote_packages.insert(file.release_channel, package);
+    addon.release_channel = file.release_channel;
+
+    addon
+}
+
+/// Fetches `url` with a conditional request if a response is cached under
+/// `name`. A new response is cached, while `None` is returned if the cached
+/// response is still current.
+async fn fetch_cached(name: &str, url: &str) -> Result<Option<String>> {
+    let cached = CachedResponse::load(name)
+        .filter(|c| c.url == url && CachedResponse::body_path(name).exists());
+
+    let mut headers = vec![];
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            headers.push(("if-none-match", etag.as_str()));
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            headers.push(("if-modified-since", last_modified.as_str()));
+        }
+    }
+
+    let mut resp = request_async(url, headers, Some(30)).await?;
+
+    if resp.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+        log::debug!("{} not modified since it was cached", url);
+
+        return Ok(None);
+    }
+
+    if !resp.status().is_success() {
+        return Err(ClientError::Custom(format!(
+            "Couldn't fetch {}: {}",
+            url,
             resp.text()?
-        )))
+        )));
     }
+
+    let header = |name: HeaderName| {
+        resp.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let cached = CachedResponse {
+        url: url.to_owned(),
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    let body = resp.text()?;
+
+    // The response is still usable if it can't be cached.
+    if let Err(e) = cached.save(name, &body) {
+        log::error!("couldn't cache {}: {}", url, e);
+    }
+
+    Ok(Some(body))
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// Validators of a response which is cached on disk. The body is stored
+/// next to it, so it's only read when it's needed.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl CachedResponse {
+    fn path(name: &str) -> PathBuf {
+        config_dir().join("cache").join(format!("{}.yml", name))
+    }
+
+    fn body_path(name: &str) -> PathBuf {
+        config_dir().join("cache").join(format!("{}.json", name))
+    }
+
+    fn load(name: &str) -> Option<CachedResponse> {
+        let file = std::fs::File::open(Self::path(name)).ok()?;
+
+        serde_yaml::from_reader(file).ok()
+    }
+
+    /// Returns the cached body for `name`, if it was fetched from `url`.
+    fn body(name: &str, url: &str) -> Option<String> {
+        Self::load(name).filter(|c| c.url == url)?;
+
+        std::fs::read_to_string(Self::body_path(name)).ok()
+    }
+
+    fn save(&self, name: &str, body: &str) -> Result<()> {
+        std::fs::create_dir_all(config_dir().join("cache"))?;
+
+        // The validators are removed while the body is replaced, so they
+        // never describe another body.
+        let _ = std::fs::remove_file(Self::path(name));
+        std::fs::write(Self::body_path(name), body)?;
+        std::fs::write(Self::path(name), serde_yaml::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Source {
     #[serde(alias = "curse")]
     Curse,
     #[serde(alias = "tukui")]
     Tukui,
+    #[serde(alias = "wowi")]
+    WowI,
+    /// A custom feed, by name.
+    #[serde(skip_deserializing)]
+    Custom(String),
 }
 
 impl std::fmt::Display for Source {
@@ -42,6 +882,8 @@ impl std::fmt::Display for Source {
         let s = match self {
             Source::Curse => "Curse",
             Source::Tukui => "Tukui",
+            Source::WowI => "WoWInterface",
+            Source::Custom(name) => name,
         };
         write!(f, "{}", s)
     }
@@ -53,6 +895,52 @@ pub struct Catalog {
     pub addons: Vec<CatalogAddon>,
 }
 
+impl Catalog {
+    /// Builds a catalog from the addons of every feed.
+    ///
+    /// Entries with the same name from different sources are merged into one,
+    /// where the first source, in the order of `Source`, is shown and the
+    /// others are kept as alternatives the user can pick instead.
+    pub fn merge(addons: Vec<CatalogAddon>) -> Catalog {
+        let mut addons = addons;
+        addons.sort_by(|a, b| a.source.cmp(&b.source));
+
+        let mut merged: Vec<CatalogAddon> = vec![];
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for addon in addons {
+            let indices = by_name.entry(normalize_name(&addon.name)).or_default();
+
+            // Addons from the same source are never merged, e.g. the separate
+            // retail and classic Tukui addons.
+            let existing = indices.iter().copied().find(|idx| {
+                let entry: &CatalogAddon = &merged[*idx];
+
+                entry.source != addon.source
+                    && entry.alternatives.iter().all(|a| a.source != addon.source)
+            });
+
+            match existing {
+                Some(idx) => merged[idx].alternatives.push(addon),
+                None => {
+                    indices.push(merged.len());
+                    merged.push(addon);
+                }
+            }
+        }
+
+        Catalog { addons: merged }
+    }
+
+    /// Returns the addon `id` from `source`, also if it's an alternative.
+    pub fn find(&self, source: &Source, id: u32) -> Option<&CatalogAddon> {
+        self.addons
+            .iter()
+            .flat_map(|a| std::iter::once(a).chain(&a.alternatives))
+            .find(|a| &a.source == source && a.id == id)
+    }
+}
+
 #[serde(rename_all = "camelCase")]
 #[derive(Debug, Clone, Deserialize)]
 pub struct CatalogAddon {
@@ -64,20 +952,303 @@ pub struct CatalogAddon {
     pub number_of_downloads: u64,
     pub source: Source,
     pub flavors: Vec<Flavor>,
+    /// File to download, for addons from a custom feed.
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Version of `download_url`.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The same addon from other sources.
+    #[serde(skip)]
+    pub alternatives: Vec<CatalogAddon>,
+}
+
+impl CatalogAddon {
+    /// Returns every source the addon is available from.
+    pub fn sources(&self) -> Vec<Source> {
+        let mut sources: Vec<_> = std::iter::once(&self.source)
+            .chain(self.alternatives.iter().map(|a| &a.source))
+            .cloned()
+            .collect();
+        sources.sort();
+
+        sources
+    }
+
+    /// Returns true if `addon` is this addon, installed from any of its sources.
+    ///
+    /// Addons from a custom feed carry no id in their folder, so they are
+    /// matched on the feed and id saved when they were installed.
+    pub fn is_installed_as(&self, addon: &Addon) -> bool {
+        std::iter::once(self)
+            .chain(&self.alternatives)
+            .any(|a| match &a.source {
+                Source::Curse => addon.curse_id == Some(a.id),
+                Source::Tukui => addon.tukui_id.as_deref() == Some(&a.id.to_string()),
+                Source::WowI => addon.wowi_id.as_deref() == Some(&a.id.to_string()),
+                Source::Custom(feed) => addon
+                    .custom_feed_id
+                    .as_ref()
+                    .map_or(false, |c| &c.feed == feed && c.id == a.id),
+            })
+    }
+
+    /// Makes the alternative from `source` the one shown, keeping the
+    /// current one as an alternative.
+    pub fn prefer_source(&mut self, source: &Source) {
+        if let Some(idx) = self.alternatives.iter().position(|a| &a.source == source) {
+            let mut preferred = self.alternatives.remove(idx);
+            preferred.alternatives = std::mem::take(&mut self.alternatives);
+
+            let previous = std::mem::replace(self, preferred);
+            self.alternatives.push(previous);
+            self.alternatives.sort_by(|a, b| a.source.cmp(&b.source));
+        }
+    }
+}
+
+#[cfg(test)]
+impl CatalogAddon {
+    /// A retail addon from `source`, with every other field left empty.
+    pub(crate) fn retail(id: u32, name: &str, source: Source) -> Self {
+        CatalogAddon {
+            id,
+            website_url: String::new(),
+            name: name.to_owned(),
+            categories: vec![],
+            summary: String::new(),
+            number_of_downloads: 0,
+            source,
+            flavors: vec![Flavor::Retail],
+            download_url: None,
+            version: None,
+            alternatives: vec![],
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
 
     #[test]
     fn test_catalog_download() {
+        // Feeds are cached in the config directory, which mustn't be the user's own.
+        *crate::fs::CONFIG_DIR.lock().unwrap() = std::env::temp_dir().join("ajour-test-catalog");
+
         async_std::task::block_on(async {
-            let catalog = get_catalog().await;
+            let catalog = get_catalog(&Endpoints::default(), &CatalogFeeds::default()).await;
 
             if let Err(e) = catalog {
                 panic!("{}", e);
             }
         });
     }
+
+    #[test]
+    fn test_merge_catalog() {
+        let catalog = Catalog::merge(vec![
+            CatalogAddon::retail(5108, "Details!", Source::WowI),
+            CatalogAddon::retail(61284, "Details! Damage Meter", Source::Curse),
+            CatalogAddon::retail(12, "AddOnSkins", Source::Tukui),
+            CatalogAddon::retail(3, "AddOnSkins", Source::Tukui),
+            CatalogAddon::retail(4108, "Addon Skins", Source::WowI),
+            CatalogAddon::retail(288, "AddOnSkins", Source::Curse),
+        ]);
+
+        let entries: Vec<_> = catalog
+            .addons
+            .iter()
+            .map(|a| (a.source.clone(), a.id, a.sources()))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (Source::Curse, 61284, vec![Source::Curse]),
+                (
+                    Source::Curse,
+                    288,
+                    vec![Source::Curse, Source::Tukui, Source::WowI]
+                ),
+                (Source::Tukui, 3, vec![Source::Tukui]),
+                (Source::WowI, 5108, vec![Source::WowI]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prefer_source() {
+        let mut addon = CatalogAddon::retail(288, "AddOnSkins", Source::Curse);
+        addon.alternatives = vec![
+            CatalogAddon::retail(12, "AddOnSkins", Source::Tukui),
+            CatalogAddon::retail(4108, "AddOnSkins", Source::WowI),
+        ];
+
+        addon.prefer_source(&Source::WowI);
+
+        assert_eq!((addon.source, addon.id), (Source::WowI, 4108));
+        assert_eq!(
+            addon.sources(),
+            vec![Source::Curse, Source::Tukui, Source::WowI]
+        );
+        assert_eq!(addon.alternatives[0].source, Source::Curse);
+        assert!(addon.alternatives.iter().all(|a| a.alternatives.is_empty()));
+    }
+
+    #[test]
+    fn test_is_installed_as() {
+        let mut addon = CatalogAddon::retail(1, "Bagnon", Source::Curse);
+        addon.alternatives = vec![CatalogAddon::retail(2, "Bagnon", Source::WowI)];
+
+        let mut installed = Addon::empty("Bagnon");
+        assert!(!addon.is_installed_as(&installed));
+
+        installed.curse_id = Some(1);
+        assert!(addon.is_installed_as(&installed));
+
+        installed.curse_id = None;
+        installed.wowi_id = Some("2".to_owned());
+        assert!(addon.is_installed_as(&installed));
+
+        installed.wowi_id = Some("1".to_owned());
+        assert!(!addon.is_installed_as(&installed));
+
+        let custom = CatalogAddon::retail(3, "GuildTools", Source::Custom("Guild".to_owned()));
+        let mut installed = Addon::empty("GuildTools");
+        assert!(!custom.is_installed_as(&installed));
+
+        installed.custom_feed_id = Some(CustomFeedId {
+            feed: "Guild".to_owned(),
+            id: 3,
+        });
+        assert!(custom.is_installed_as(&installed));
+
+        installed.custom_feed_id = Some(CustomFeedId {
+            feed: "Raid".to_owned(),
+            id: 3,
+        });
+        assert!(!custom.is_installed_as(&installed));
+    }
+
+    #[test]
+    fn test_parse_custom_feed() {
+        let feed = Feed::Custom(CustomFeed {
+            name: "Guild".to_owned(),
+            url: "https://guild.example/addons.json".to_owned(),
+        });
+        let body = r#"[{
+            "id": 1,
+            "name": "GuildRoster",
+            "categories": ["Guild"],
+            "flavors": ["retail", "classic"],
+            "downloadUrl": "https://guild.example/GuildRoster-1.2.zip",
+            "version": "1.2"
+        }]"#;
+
+        let addons = feed.parse(body).unwrap();
+
+        let other = Feed::Custom(CustomFeed {
+            name: "guild!".to_owned(),
+            url: "https://other.example/addons.json".to_owned(),
+        });
+        assert_ne!(feed.cache_name(), other.cache_name());
+        assert_eq!(addons.len(), 1);
+        assert_eq!(addons[0].source, Source::Custom("Guild".to_owned()));
+        assert_eq!(addons[0].source.to_string(), "Guild");
+        assert_eq!(addons[0].version.as_deref(), Some("1.2"));
+        assert_eq!(addons[0].flavors, vec![Flavor::Retail, Flavor::Classic]);
+    }
+
+    #[test]
+    fn test_apply_custom_feeds() {
+        let feed = CustomFeed {
+            name: "Guild".to_owned(),
+            url: Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/feeds/guild.json")
+                .to_string_lossy()
+                .to_string(),
+        };
+        let installed = |folder: &str, feed: &str, id: u32| {
+            let mut addon = Addon::empty(folder);
+            addon.custom_feed_id = Some(CustomFeedId {
+                feed: feed.to_owned(),
+                id,
+            });
+            addon
+        };
+        let mut addons = vec![
+            installed("GuildRoster", "Guild", 1),
+            installed("GuildBank", "Guild", 2),
+            installed("RaidTools", "Raid", 1),
+            Addon::empty("Bagnon"),
+        ];
+
+        async_std::task::block_on(apply_custom_feeds(&[feed], Flavor::Retail, &mut addons));
+
+        let versions: Vec<_> = addons
+            .iter()
+            .map(|a| {
+                a.remote_packages
+                    .get(&ReleaseChannel::Stable)
+                    .map(|p| p.version.as_str())
+            })
+            .collect();
+
+        // GuildBank is only listed for classic, and the Raid feed isn't set up.
+        assert_eq!(versions, vec![Some("1.2"), None, None, None]);
+        assert_eq!(
+            addons[0].remote_packages[&ReleaseChannel::Stable].download_url,
+            "https://guild.example/GuildRoster-1.2.zip"
+        );
+    }
+
+    #[test]
+    fn test_addon_from_file() {
+        let catalog_addon = CatalogAddon::retail(61284, "Details! Damage Meter", Source::Curse);
+        let file = CatalogFile {
+            id: Some(3098530),
+            version: "Details.9.0.2.8001.144-beta".to_owned(),
+            release_channel: ReleaseChannel::Beta,
+            flavor: Flavor::Retail,
+            date_time: None,
+            game_versions: vec!["9.0.2".to_owned()],
+            download_url: "https://edge.forgecdn.net/files/3098/530/Details.zip".to_owned(),
+            file_length: Some(1024),
+            modules: vec!["Details".to_owned(), "Details_Compare2".to_owned()],
+        };
+
+        let addon = addon_from_file(
+            &catalog_addon,
+            &file,
+            Addon::empty("61284"),
+            PathBuf::from("AddOns"),
+        );
+
+        assert_eq!(addon.id, "Details");
+        assert_eq!(addon.path, PathBuf::from("AddOns").join("Details"));
+        assert_eq!(addon.bundled_modules, vec!["Details_Compare2".to_owned()]);
+        assert_eq!(addon.curse_id, Some(61284));
+        assert_eq!(addon.release_channel, ReleaseChannel::Beta);
+        assert_eq!(
+            addon.remote_packages[&ReleaseChannel::Beta].file_id,
+            Some(3098530)
+        );
+    }
+
+    #[test]
+    fn test_feed_files() {
+        let mut addon = CatalogAddon::retail(1, "GuildRoster", Source::Custom("Guild".to_owned()));
+        addon.flavors = vec![Flavor::Retail, Flavor::Classic];
+        addon.download_url = Some("https://guild.example/GuildRoster.zip".to_owned());
+        addon.version = Some("1.2".to_owned());
+
+        let files = feed_files(&addon);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].flavor, Flavor::Classic);
+        assert_eq!(files[1].version, "1.2");
+        assert_eq!(files[1].release_channel, ReleaseChannel::Stable);
+    }
 }
@@ -0,0 +1,46 @@
+use crate::addon::Addon;
+use crate::config::{Endpoints, Flavor};
+use crate::utility::html_to_text;
+use crate::Result;
+use crate::{curse_api, tukui_api, wowi_api};
+
+/// Fetches the changelog of the file `addon` would be updated to, as plain text.
+///
+/// Curse has a changelog for each file, while Tukui and WoWInterface only have
+/// one for the addon which covers the latest file. Returns `None` if the
+/// source has no changelog.
+pub async fn fetch_changelog(
+    endpoints: &Endpoints,
+    addon: &Addon,
+    flavor: Flavor,
+) -> Result<Option<String>> {
+    let package = match addon.relevant_release_package() {
+        Some(package) => package,
+        None => return Ok(None),
+    };
+
+    let changelog = if let (Some(curse_id), Some(file_id)) = (addon.curse_id, package.file_id) {
+        let changelog = curse_api::fetch_changelog(endpoints, curse_id, file_id).await?;
+
+        Some(html_to_text(&changelog))
+    } else if let Some(tukui_id) = &addon.tukui_id {
+        let package = tukui_api::fetch_remote_package(endpoints, tukui_id, &flavor).await?;
+
+        match package.changelog {
+            Some(_) => Some(html_to_text(&tukui_api::fetch_changelog(&package).await?)),
+            None => None,
+        }
+    } else if let Some(wowi_id) = &addon.wowi_id {
+        wowi_api::fetch_remote_packages(endpoints, &[wowi_id.clone()])
+            .await?
+            .into_iter()
+            .next()
+            .and_then(|package| package.changelog)
+    } else {
+        None
+    };
+
+    Ok(changelog
+        .map(|changelog| changelog.trim().to_owned())
+        .filter(|changelog| !changelog.is_empty()))
+}
@@ -1,4 +1,4 @@
-use super::Flavor;
+use super::{CustomFeedId, Flavor};
 use crate::addon::ReleaseChannel;
 use de::de_ignored;
 use serde::{Deserialize, Serialize};
@@ -12,6 +12,31 @@ pub struct Addons {
 
     #[serde(default)]
     pub release_channels: HashMap<Flavor, HashMap<String, ReleaseChannel>>,
+
+    /// Addons which are updated without asking.
+    #[serde(default)]
+    pub auto_update: HashMap<Flavor, Vec<String>>,
+
+    /// Release channels where every addon is updated without asking.
+    #[serde(default)]
+    pub auto_update_channels: Vec<ReleaseChannel>,
+
+    /// Addons installed from a custom feed, keyed by addon folder.
+    #[serde(default)]
+    pub custom_feeds: HashMap<Flavor, HashMap<String, CustomFeedId>>,
+}
+
+impl Addons {
+    /// Returns true if the addon `id` should be updated without asking, either
+    /// by itself or because it follows an auto update release channel.
+    pub fn is_auto_update(&self, flavor: Flavor, id: &str, channel: ReleaseChannel) -> bool {
+        self.auto_update_channels.contains(&channel)
+            || self
+                .auto_update
+                .get(&flavor)
+                .map(|ids| ids.iter().any(|i| i == id))
+                .unwrap_or_default()
+    }
 }
 
 impl Default for Addons {
@@ -19,6 +44,9 @@ impl Default for Addons {
         Addons {
             ignored: HashMap::new(),
             release_channels: HashMap::new(),
+            auto_update: HashMap::new(),
+            auto_update_channels: vec![],
+            custom_feeds: HashMap::new(),
         }
     }
 }
@@ -73,3 +101,24 @@ mod de {
         deserializer.deserialize_any(DeIgnored)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_auto_update() {
+        let mut addons = Addons::default();
+        addons
+            .auto_update
+            .insert(Flavor::Retail, vec!["DBM-Core".to_owned()]);
+
+        assert!(addons.is_auto_update(Flavor::Retail, "DBM-Core", ReleaseChannel::Stable));
+        assert!(!addons.is_auto_update(Flavor::Classic, "DBM-Core", ReleaseChannel::Stable));
+        assert!(!addons.is_auto_update(Flavor::Retail, "ElvUI", ReleaseChannel::Beta));
+
+        addons.auto_update_channels.push(ReleaseChannel::Beta);
+        assert!(addons.is_auto_update(Flavor::Retail, "ElvUI", ReleaseChannel::Beta));
+        assert!(!addons.is_auto_update(Flavor::Retail, "ElvUI", ReleaseChannel::Stable));
+    }
+}
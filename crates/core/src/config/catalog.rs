@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Feeds the catalog is built from. The url of each feed is set in `Endpoints`.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CatalogFeeds {
+    /// The Curse catalog.
+    #[serde(default = "enabled")]
+    pub curse: bool,
+
+    /// The addon lists of the Tukui API.
+    #[serde(default = "enabled")]
+    pub tukui: bool,
+
+    /// The file list of the WoWInterface API.
+    #[serde(default = "enabled")]
+    pub wowi: bool,
+
+    /// Feeds added by the user, e.g. the addons of a guild.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomFeed>,
+}
+
+/// A user defined list of addons, in the same format as the catalog.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CustomFeed {
+    /// Name shown as the source of its addons.
+    pub name: String,
+
+    /// Url of the feed, or the path of a local file.
+    pub url: String,
+}
+
+/// An addon installed from a custom feed, which has no id of its own in the
+/// addon folder.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CustomFeedId {
+    /// Name of the feed the addon was installed from.
+    pub feed: String,
+
+    /// Id of the addon in the feed.
+    pub id: u32,
+}
+
+impl CustomFeed {
+    /// Returns the path of the feed, if it's a local file.
+    pub fn path(&self) -> Option<PathBuf> {
+        if self.url.starts_with("http://") || self.url.starts_with("https://") {
+            None
+        } else {
+            let path = self.url.strip_prefix("file://").unwrap_or(&self.url);
+            Some(PathBuf::from(path))
+        }
+    }
+}
+
+impl Default for CatalogFeeds {
+    fn default() -> Self {
+        CatalogFeeds {
+            curse: true,
+            tukui: true,
+            wowi: true,
+            custom: vec![],
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_feed_path() {
+        let feed = |url: &str| CustomFeed {
+            name: "Guild".to_owned(),
+            url: url.to_owned(),
+        };
+
+        assert_eq!(feed("https://guild.example/addons.json").path(), None);
+        assert_eq!(
+            feed("/home/ajour/addons.json").path(),
+            Some(PathBuf::from("/home/ajour/addons.json"))
+        );
+        assert_eq!(
+            feed("file:///home/ajour/addons.json").path(),
+            Some(PathBuf::from("/home/ajour/addons.json"))
+        );
+    }
+}
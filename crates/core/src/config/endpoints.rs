@@ -0,0 +1,123 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::Mutex;
+
+const CURSE_ENDPOINT: &str = "https://addons-ecs.forgesvc.net/api/v2";
+const TUKUI_ENDPOINT: &str = "https://www.tukui.org/api.php";
+const CATALOG_ENDPOINT: &str =
+    "https://raw.githubusercontent.com/casperstorm/ajour-catalog/master/curse.json";
+const GITHUB_ENDPOINT: &str = "https://api.github.com/repos/casperstorm/ajour";
+const WOWI_ENDPOINT: &str = "https://api.mmoui.com/v3/game/WOW";
+
+lazy_static! {
+    /// Endpoints passed on the command line. These take precedence over both
+    /// environment variables and the config file.
+    pub static ref ENDPOINT_OVERRIDES: Mutex<Endpoints> = Mutex::new(Endpoints::default());
+}
+
+/// Urls of the remote APIs used by Ajour.
+///
+/// Each url is optional and falls back to the official API when not set. This
+/// makes it possible to point Ajour at a local mock server or a caching mirror.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct Endpoints {
+    /// Base url of the Curse API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curse: Option<String>,
+
+    /// Url of the Tukui `api.php`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tukui: Option<String>,
+
+    /// Url of the addon catalog.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catalog: Option<String>,
+
+    /// Base url of the GitHub repository API, used to check for new releases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<String>,
+
+    /// Base url of the WoWInterface API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wowi: Option<String>,
+}
+
+impl Endpoints {
+    /// Reads endpoints from the `AJOUR_CURSE_ENDPOINT`, `AJOUR_TUKUI_ENDPOINT`,
+    /// `AJOUR_CATALOG_ENDPOINT`, `AJOUR_GITHUB_ENDPOINT` and `AJOUR_WOWI_ENDPOINT`
+    /// environment variables.
+    pub fn from_env() -> Endpoints {
+        let var = |key: &str| env::var(key).ok().filter(|v| !v.is_empty());
+
+        Endpoints {
+            curse: var("AJOUR_CURSE_ENDPOINT"),
+            tukui: var("AJOUR_TUKUI_ENDPOINT"),
+            catalog: var("AJOUR_CATALOG_ENDPOINT"),
+            github: var("AJOUR_GITHUB_ENDPOINT"),
+            wowi: var("AJOUR_WOWI_ENDPOINT"),
+        }
+    }
+
+    /// Returns `self` with every url set in `other` replaced.
+    pub fn merge(self, other: Endpoints) -> Endpoints {
+        Endpoints {
+            curse: other.curse.or(self.curse),
+            tukui: other.tukui.or(self.tukui),
+            catalog: other.catalog.or(self.catalog),
+            github: other.github.or(self.github),
+            wowi: other.wowi.or(self.wowi),
+        }
+    }
+
+    pub fn curse(&self) -> &str {
+        trim_url(self.curse.as_deref().unwrap_or(CURSE_ENDPOINT))
+    }
+
+    pub fn tukui(&self) -> &str {
+        trim_url(self.tukui.as_deref().unwrap_or(TUKUI_ENDPOINT))
+    }
+
+    pub fn catalog(&self) -> &str {
+        trim_url(self.catalog.as_deref().unwrap_or(CATALOG_ENDPOINT))
+    }
+
+    pub fn github(&self) -> &str {
+        trim_url(self.github.as_deref().unwrap_or(GITHUB_ENDPOINT))
+    }
+
+    pub fn wowi(&self) -> &str {
+        trim_url(self.wowi.as_deref().unwrap_or(WOWI_ENDPOINT))
+    }
+}
+
+/// Removes any trailing `/`, since paths are appended to the urls.
+fn trim_url(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_endpoints() {
+        let config = Endpoints {
+            curse: Some("http://mirror.lan/curse/".to_owned()),
+            tukui: Some("http://mirror.lan/tukui".to_owned()),
+            ..Default::default()
+        };
+        let cli = Endpoints {
+            tukui: Some("http://localhost:8080/tukui".to_owned()),
+            ..Default::default()
+        };
+
+        let endpoints = config.merge(cli);
+
+        assert_eq!(endpoints.curse(), "http://mirror.lan/curse");
+        assert_eq!(endpoints.tukui(), "http://localhost:8080/tukui");
+        assert_eq!(endpoints.catalog(), CATALOG_ENDPOINT);
+        assert_eq!(endpoints.github(), GITHUB_ENDPOINT);
+        assert_eq!(endpoints.wowi(), WOWI_ENDPOINT);
+    }
+}
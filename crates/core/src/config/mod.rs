@@ -1,16 +1,27 @@
 use glob::MatchOptions;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 mod addons;
+mod catalog;
+mod endpoints;
+mod network;
 mod wow;
 
 use crate::fs::PersistentData;
 use crate::Result;
 
 pub use crate::config::addons::Addons;
+pub use crate::config::catalog::{CatalogFeeds, CustomFeed, CustomFeedId};
+pub use crate::config::endpoints::{Endpoints, ENDPOINT_OVERRIDES};
+pub use crate::config::network::{Network, NETWORK_OVERRIDES};
 pub use crate::config::wow::{Flavor, Wow};
 
+/// Minutes between background update checks, unless set in the config.
+pub const DEFAULT_UPDATE_CHECK_INTERVAL: u64 = 60;
+
 /// Config struct.
 #[derive(Deserialize, Serialize, Debug, PartialEq, Default, Clone)]
 pub struct Config {
@@ -30,9 +41,63 @@ pub struct Config {
     pub scale: Option<f64>,
 
     pub backup_directory: Option<PathBuf>,
+
+    #[serde(default)]
+    pub endpoints: Endpoints,
+
+    #[serde(default)]
+    pub network: Network,
+
+    #[serde(default)]
+    pub catalog_feeds: CatalogFeeds,
+
+    /// Minutes between checking for addon updates in the background, where
+    /// `0` turns the checks off. Defaults to an hour.
+    pub update_check_interval: Option<u64>,
 }
 
 impl Config {
+    /// Returns the API endpoints to use. Environment variables take precedence
+    /// over the config file, and endpoints passed on the command line over both.
+    pub fn resolved_endpoints(&self) -> Endpoints {
+        let overrides = ENDPOINT_OVERRIDES.lock().unwrap().clone();
+
+        self.endpoints
+            .clone()
+            .merge(Endpoints::from_env())
+            .merge(overrides)
+    }
+
+    /// Returns the addons installed from a custom feed for `flavor`, keyed by
+    /// addon folder.
+    pub fn custom_feed_ids(&self, flavor: Flavor) -> HashMap<String, CustomFeedId> {
+        self.addons
+            .custom_feeds
+            .get(&flavor)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the proxy and certificate settings to use. Settings passed on
+    /// the command line take precedence over the config file.
+    pub fn resolved_network(&self) -> Network {
+        let overrides = NETWORK_OVERRIDES.lock().unwrap().clone();
+
+        self.network.clone().merge(overrides)
+    }
+
+    /// Returns how often to check for addon updates in the background, or `None`
+    /// if the checks are turned off.
+    pub fn update_check_interval(&self) -> Option<Duration> {
+        match self
+            .update_check_interval
+            .unwrap_or(DEFAULT_UPDATE_CHECK_INTERVAL)
+        {
+            0 => None,
+            minutes => Some(Duration::from_secs(minutes * 60)),
+        }
+    }
+
     /// Returns a `Option<PathBuf>` to the directory containing the addons.
     /// This will return `None` if no `wow_directory` is set in the config.
     pub fn get_addon_directory_for_flavor(&self, flavor: &Flavor) -> Option<PathBuf> {
@@ -0,0 +1,106 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Network settings passed on the command line. These take precedence
+    /// over the config file.
+    pub static ref NETWORK_OVERRIDES: Mutex<Network> = Mutex::new(Network::default());
+}
+
+/// Proxy and certificate settings applied to every request.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
+pub struct Network {
+    /// Proxy url, e.g. `http://proxy.lan:3128` or `socks5://proxy.lan:1080`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_username: Option<String>,
+
+    /// Saved as plain text in `ajour.yml`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_password: Option<String>,
+
+    /// Hosts which are reached without the proxy, e.g. `localhost` or `.lan`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+
+    /// PEM file with every certificate authority trusted for TLS. It replaces
+    /// the system certificate authorities rather than adding to them, so
+    /// besides e.g. the certificate of a proxy with TLS interception, it must
+    /// hold the public ones for any host which isn't intercepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_roots: Option<PathBuf>,
+}
+
+impl Network {
+    /// Returns `self` with every setting set in `other` replaced.
+    pub fn merge(self, other: Network) -> Network {
+        Network {
+            proxy: other.proxy.or(self.proxy),
+            proxy_username: other.proxy_username.or(self.proxy_username),
+            proxy_password: other.proxy_password.or(self.proxy_password),
+            no_proxy: if other.no_proxy.is_empty() {
+                self.no_proxy
+            } else {
+                other.no_proxy
+            },
+            ca_roots: other.ca_roots.or(self.ca_roots),
+        }
+    }
+}
+
+// The password is kept out of the logs.
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Network")
+            .field("proxy", &self.proxy)
+            .field("proxy_username", &self.proxy_username)
+            .field(
+                "proxy_password",
+                &self.proxy_password.as_ref().map(|_| "<hidden>"),
+            )
+            .field("no_proxy", &self.no_proxy)
+            .field("ca_roots", &self.ca_roots)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_network() {
+        let config = Network {
+            proxy: Some("http://proxy.lan:3128".to_owned()),
+            no_proxy: vec!["localhost".to_owned()],
+            ..Default::default()
+        };
+        let cli = Network {
+            proxy: Some("socks5://localhost:1080".to_owned()),
+            proxy_username: Some("ajour".to_owned()),
+            ..Default::default()
+        };
+
+        let network = config.merge(cli);
+
+        assert_eq!(network.proxy.as_deref(), Some("socks5://localhost:1080"));
+        assert_eq!(network.proxy_username.as_deref(), Some("ajour"));
+        assert_eq!(network.no_proxy, vec!["localhost".to_owned()]);
+        assert_eq!(network.ca_roots, None);
+    }
+
+    #[test]
+    fn test_debug_hides_password() {
+        let network = Network {
+            proxy_password: Some("hunter2".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(!format!("{:?}", network).contains("hunter2"));
+    }
+}
@@ -1,17 +1,16 @@
 use crate::{
     addon::{Addon, ReleaseChannel, RemotePackage},
-    config::Flavor,
+    config::{Endpoints, Flavor},
     error::ClientError,
     network::{post_json_async, request_async},
     Result,
 };
+use chrono::{DateTime, Utc};
 use isahc::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-const API_ENDPOINT: &str = "https://addons-ecs.forgesvc.net/api/v2";
-
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 /// Struct for applying curse details to an `Addon`.
@@ -20,6 +19,26 @@ pub struct Package {
     pub name: String,
     pub website_url: String,
     pub latest_files: Vec<File>,
+    #[serde(default)]
+    pub authors: Vec<Author>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    pub name: String,
+}
+
+/// An image shown on the addon page, e.g. a screenshot.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub url: String,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -30,12 +49,58 @@ pub struct File {
     pub file_name: String,
     pub file_date: String,
     pub download_url: String,
+    #[serde(default)]
+    pub file_length: Option<u64>,
     pub release_type: u32,
     pub game_version_flavor: Option<String>,
     pub modules: Vec<Module>,
     pub is_alternate: bool,
     pub game_version_date_released: String,
     pub game_version: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+impl File {
+    /// Returns the release channel of the file, if it's a known release type.
+    pub fn release_channel(&self) -> Option<ReleaseChannel> {
+        match self.release_type {
+            1 => Some(ReleaseChannel::Stable),
+            2 => Some(ReleaseChannel::Beta),
+            3 => Some(ReleaseChannel::Alpha),
+            _ => None,
+        }
+    }
+
+    /// Returns the flavor the file is for, if any.
+    pub fn flavor(&self) -> Option<Flavor> {
+        match self.game_version_flavor.as_deref() {
+            Some("wow_retail") => Some(Flavor::Retail),
+            Some("wow_classic") => Some(Flavor::Classic),
+            _ => None,
+        }
+    }
+}
+
+/// Another addon a file depends on.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    pub addon_id: u32,
+    /// 1 is an embedded library, 2 an optional dependency, 3 a required
+    /// dependency, 4 a tool, 5 incompatible and 6 included.
+    #[serde(rename = "type")]
+    pub type_field: u32,
+}
+
+impl Dependency {
+    pub fn is_required(&self) -> bool {
+        self.type_field == 3
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.type_field == 2
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -101,8 +166,11 @@ pub struct AddonFingerprintInfo {
     pub latest_files: Vec<File>,
 }
 
-pub async fn fetch_remote_packages_by_fingerprint(fingerprints: &[u32]) -> Result<FingerprintInfo> {
-    let url = format!("{}/fingerprint", API_ENDPOINT);
+pub async fn fetch_remote_packages_by_fingerprint(
+    endpoints: &Endpoints,
+    fingerprints: &[u32],
+) -> Result<FingerprintInfo> {
+    let url = format!("{}/fingerprint", endpoints.curse());
     let mut resp = post_json_async(url, fingerprints, vec![], None).await?;
     if resp.status().is_success() {
         let fingerprint_info = resp.json()?;
@@ -115,8 +183,11 @@ pub async fn fetch_remote_packages_by_fingerprint(fingerprints: &[u32]) -> Resul
     }
 }
 
-pub async fn fetch_remote_packages_by_ids(curse_ids: &[u32]) -> Result<Vec<Package>> {
-    let url = format!("{}/addon", API_ENDPOINT);
+pub async fn fetch_remote_packages_by_ids(
+    endpoints: &Endpoints,
+    curse_ids: &[u32],
+) -> Result<Vec<Package>> {
+    let url = format!("{}/addon", endpoints.curse());
     let mut resp = post_json_async(url, curse_ids, vec![], None).await?;
     if resp.status().is_success() {
         let packages = resp.json()?;
@@ -129,10 +200,95 @@ pub async fn fetch_remote_packages_by_ids(curse_ids: &[u32]) -> Result<Vec<Packa
     }
 }
 
-pub async fn fetch_game_info() -> Result<GameInfo> {
-    let url = format!("{}/game/1", API_ENDPOINT);
-    let client = HttpClient::builder().build().unwrap();
-    let mut resp = request_async(&client, url, vec![], None).await?;
+/// Function to fetch a specific file for a curse addon.
+/// This includes the module fingerprints of the file.
+pub async fn fetch_remote_file(endpoints: &Endpoints, curse_id: u32, file_id: i64) -> Result<File> {
+    let url = format!("{}/addon/{}/file/{}", endpoints.curse(), curse_id, file_id);
+    let mut resp = request_async(url, vec![], None).await?;
+    if resp.status().is_success() {
+        let file = resp.json()?;
+        Ok(file)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch file {} for addon {}. Server returned: {}",
+            file_id,
+            curse_id,
+            resp.text()?
+        )))
+    }
+}
+
+/// Function to fetch a single addon, including its authors and screenshots.
+pub async fn fetch_remote_package(endpoints: &Endpoints, curse_id: u32) -> Result<Package> {
+    let url = format!("{}/addon/{}", endpoints.curse(), curse_id);
+    let mut resp = request_async(url, vec![], None).await?;
+    if resp.status().is_success() {
+        let package = resp.json()?;
+        Ok(package)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch details for addon {}. Server returned: {}",
+            curse_id,
+            resp.text()?
+        )))
+    }
+}
+
+/// Function to fetch the full description of an addon, which is HTML.
+pub async fn fetch_description(endpoints: &Endpoints, curse_id: u32) -> Result<String> {
+    let url = format!("{}/addon/{}/description", endpoints.curse(), curse_id);
+    let mut resp = request_async(url, vec![], None).await?;
+    if resp.status().is_success() {
+        Ok(resp.text()?)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch description for addon {}. Server returned: {}",
+            curse_id,
+            resp.text()?
+        )))
+    }
+}
+
+/// Function to fetch every file of an addon, not only the latest ones.
+pub async fn fetch_files(endpoints: &Endpoints, curse_id: u32) -> Result<Vec<File>> {
+    let url = format!("{}/addon/{}/files", endpoints.curse(), curse_id);
+    let mut resp = request_async(url, vec![], None).await?;
+    if resp.status().is_success() {
+        let files = resp.json()?;
+        Ok(files)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch files for addon {}. Server returned: {}",
+            curse_id,
+            resp.text()?
+        )))
+    }
+}
+
+/// Function to fetch the changelog of a file, which is HTML.
+pub async fn fetch_changelog(endpoints: &Endpoints, curse_id: u32, file_id: i64) -> Result<String> {
+    let url = format!(
+        "{}/addon/{}/file/{}/changelog",
+        endpoints.curse(),
+        curse_id,
+        file_id
+    );
+    let mut resp = request_async(url, vec![], None).await?;
+    if resp.status().is_success() {
+        Ok(resp.text()?)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch changelog of file {} for addon {}. Server returned: {}",
+            file_id,
+            curse_id,
+            resp.text()?
+        )))
+    }
+}
+
+pub async fn fetch_game_info(endpoints: &Endpoints) -> Result<GameInfo> {
+    let url = format!("{}/game/1", endpoints.curse());
+    let mut resp = request_async(url, vec![], None).await?;
     if resp.status().is_success() {
         let game_info = resp.json()?;
         Ok(game_info)
@@ -144,31 +300,57 @@ pub async fn fetch_game_info() -> Result<GameInfo> {
     }
 }
 
-pub async fn latest_stable_addon_from_id(
+/// Returns the newest file for `flavor` which `release_channel` would install.
+///
+/// A channel also accepts files from more stable channels, so a stable
+/// release newer than the latest beta is picked for the beta channel.
+fn latest_file_for_channel(
+    files: &[File],
+    flavor: Flavor,
+    release_channel: ReleaseChannel,
+) -> Option<&File> {
+    let accepted = match release_channel {
+        ReleaseChannel::Stable => 1,
+        ReleaseChannel::Beta => 2,
+        ReleaseChannel::Alpha => 3,
+    };
+
+    files
+        .iter()
+        .filter(|f| {
+            !f.is_alternate
+                && f.flavor() == Some(flavor)
+                && (1..=accepted).contains(&f.release_type)
+        })
+        .max_by_key(|f| f.id)
+}
+
+pub async fn latest_addon_from_id(
+    endpoints: &Endpoints,
     curse_id: u32,
     mut addon: Addon,
     mut addon_path: PathBuf,
     flavor: Flavor,
+    release_channel: ReleaseChannel,
 ) -> Result<(u32, Flavor, Addon)> {
-    let packages: Vec<Package> = fetch_remote_packages_by_ids(&[curse_id]).await?;
+    let packages: Vec<Package> = fetch_remote_packages_by_ids(endpoints, &[curse_id]).await?;
 
     let package = packages.into_iter().next().ok_or_else(|| {
         ClientError::Custom(format!("No package found for curse id {}", curse_id))
     })?;
 
-    let stable_file = package
-        .latest_files
-        .iter()
-        .find(|f| {
-            f.release_type == 1
-                && f.game_version_flavor.as_ref() == Some(&format!("wow_{}", flavor))
-        })
-        .ok_or_else(|| {
-            ClientError::Custom(format!("No stable file found for curse id {}", curse_id))
-        })?;
+    let file = latest_file_for_channel(&package.latest_files, flavor, release_channel).ok_or_else(
+        || {
+            ClientError::Custom(format!(
+                "No {} file found for curse id {}",
+                release_channel.to_string().to_lowercase(),
+                curse_id
+            ))
+        },
+    )?;
 
     // Use first module
-    let id = stable_file
+    let id = file
         .modules
         .get(0)
         .cloned()
@@ -179,7 +361,7 @@ pub async fn latest_stable_addon_from_id(
     addon_path.push(&id);
 
     // Use rest of the modules
-    let dependencies = stable_file
+    let bundled_modules = file
         .modules
         .iter()
         .enumerate()
@@ -187,27 +369,73 @@ pub async fn latest_stable_addon_from_id(
         .map(|(_, m)| m.foldername.clone())
         .collect();
 
-    let version = Some(stable_file.display_name.clone());
+    let version = Some(file.display_name.clone());
 
     addon.id = id;
     addon.title = title;
     addon.version = version;
     addon.path = addon_path;
     addon.curse_id = Some(curse_id);
-    addon.dependencies = dependencies;
+    addon.bundled_modules = bundled_modules;
 
+    // Keep the newest file of each channel, so the addon can later switch channel.
     let mut remote_packages = HashMap::new();
-    let package = RemotePackage {
-        version: stable_file.display_name.clone(),
-        download_url: stable_file.download_url.clone(),
-        date_time: None,
-        file_id: None,
-    };
+    for channel in ReleaseChannel::ALL.iter() {
+        let channel_file = package
+            .latest_files
+            .iter()
+            .filter(|f| !f.is_alternate && f.flavor() == Some(flavor))
+            .filter(|f| f.release_channel() == Some(*channel))
+            .max_by_key(|f| f.id);
 
-    remote_packages.insert(ReleaseChannel::Stable, package);
+        if let Some(channel_file) = channel_file {
+            let package = RemotePackage {
+                version: channel_file.display_name.clone(),
+                download_url: channel_file.download_url.clone(),
+                date_time: DateTime::parse_from_rfc3339(&channel_file.file_date)
+                    .map(|d| d.with_timezone(&Utc))
+                    .ok(),
+                file_id: Some(channel_file.id),
+                file_length: channel_file.file_length,
+            };
+
+            remote_packages.insert(*channel, package);
+        }
+    }
 
     addon.remote_packages = remote_packages;
-    addon.release_channel = ReleaseChannel::Stable;
+    addon.release_channel = release_channel;
 
     Ok((curse_id, flavor, addon))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(id: i64, release_type: u32, game_version_flavor: &str) -> File {
+        File {
+            id,
+            release_type,
+            game_version_flavor: Some(game_version_flavor.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_latest_file_for_channel() {
+        let files = vec![
+            file(1, 1, "wow_retail"),
+            file(2, 3, "wow_retail"),
+            file(3, 2, "wow_retail"),
+            file(4, 1, "wow_classic"),
+        ];
+
+        let id = |flavor, channel| latest_file_for_channel(&files, flavor, channel).map(|f| f.id);
+
+        assert_eq!(id(Flavor::Retail, ReleaseChannel::Stable), Some(1));
+        assert_eq!(id(Flavor::Retail, ReleaseChannel::Beta), Some(3));
+        assert_eq!(id(Flavor::Retail, ReleaseChannel::Alpha), Some(3));
+        assert_eq!(id(Flavor::Classic, ReleaseChannel::Beta), Some(4));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    addon::Addon,
+    catalog::{Catalog, CatalogAddon},
+    config::Flavor,
+    toc::Toc,
+    utility::normalize_name,
+    Result,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Dependencies of a single `Addon`, resolved against the installed addons.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddonDependencies {
+    /// Module folders shipped in the same package as the addon.
+    pub bundled: Vec<String>,
+    /// External addons which must be installed for the addon to load.
+    pub required: Vec<String>,
+    /// External addons which are loaded first, if they are installed.
+    pub optional: Vec<String>,
+    /// Required dependencies which aren't installed.
+    pub missing: Vec<String>,
+    /// Installed addons which require one of the addon folders.
+    pub dependents: Vec<String>,
+}
+
+/// Folders affected by deleting a single `Addon`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeletePlan {
+    /// Folders which are only owned by the addon, and will be removed.
+    pub remove: Vec<String>,
+    /// Folders which are also shipped with other installed addons, and are kept.
+    pub keep: Vec<String>,
+}
+
+/// Dependency graph of every addon folder in an AddOns directory.
+///
+/// The game treats folder names case-insensitively, so all lookups do as well.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// Parsed `.toc` for each folder, keyed by lowercased folder name.
+    tocs: HashMap<String, (String, Toc)>,
+    /// Id of the addon owning each folder, keyed by lowercased folder name.
+    owners: HashMap<String, String>,
+}
+
+impl DependencyGraph {
+    /// Creates a graph from the `.toc` of each folder, keyed by folder name.
+    pub fn new(tocs: HashMap<String, Toc>, addons: &[Addon]) -> Self {
+        let tocs = tocs
+            .into_iter()
+            .map(|(folder, toc)| (folder.to_lowercase(), (folder, toc)))
+            .collect();
+
+        let owners = addons
+            .iter()
+            .flat_map(|addon| {
+                addon
+                    .folders()
+                    .into_iter()
+                    .map(move |folder| (folder.to_lowercase(), addon.id.clone()))
+            })
+            .collect();
+
+        DependencyGraph { tocs, owners }
+    }
+
+    /// Creates a graph by reading the `.toc` of every folder in `addon_directory`.
+    ///
+    /// Folders without a readable `.toc` file are not addons, and are skipped.
+    pub fn from_directory(addon_directory: impl AsRef<Path>, addons: &[Addon]) -> Result<Self> {
+        let mut tocs = HashMap::new();
+
+        for entry in addon_directory.as_ref().read_dir()? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let folder = entry.file_name().to_string_lossy().to_string();
+            let toc_path = entry.path().join(format!("{}.toc", folder));
+
+            if let Ok(toc) = Toc::from_path(&toc_path) {
+                tocs.insert(folder, toc);
+            }
+        }
+
+        Ok(DependencyGraph::new(tocs, addons))
+    }
+
+    /// Function returns a `bool` indicating if `folder` is installed.
+    pub fn is_installed(&self, folder: &str) -> bool {
+        self.tocs.contains_key(&folder.to_lowercase())
+    }
+
+    /// Resolves the dependencies of `addon`.
+    ///
+    /// Required and optional dependencies are collected from the `.toc` of every
+    /// folder in the package, leaving out the folders shipped with the addon itself.
+    pub fn resolve(&self, addon: &Addon) -> AddonDependencies {
+        let folders: Vec<_> = addon.folders().iter().map(|f| f.to_lowercase()).collect();
+        let is_own_folder = |name: &str| folders.contains(&name.to_lowercase());
+
+        let mut required: Vec<String> = vec![];
+        let mut optional: Vec<String> = vec![];
+        for folder in folders.iter() {
+            let toc = match self.tocs.get(folder) {
+                Some((_, toc)) => toc,
+                // Fallback to the toc parsed with the addon if the folder is gone.
+                None if folder == &addon.id.to_lowercase() => &addon.toc,
+                None => continue,
+            };
+
+            for dependency in toc.dependencies.iter() {
+                if !is_own_folder(dependency) && !contains(&required, dependency) {
+                    required.push(dependency.clone());
+                }
+            }
+
+            for dependency in toc.optional_dependencies.iter() {
+                if !is_own_folder(dependency) && !contains(&optional, dependency) {
+                    optional.push(dependency.clone());
+                }
+            }
+        }
+
+        // A dependency listed as both required and optional, is required.
+        optional.retain(|d| !contains(&required, d));
+
+        let missing = required
+            .iter()
+            .filter(|d| !self.is_installed(d))
+            .cloned()
+            .collect();
+
+        let mut dependents: Vec<String> = vec![];
+        for (key, (folder, toc)) in self.tocs.iter() {
+            if folders.contains(key) {
+                continue;
+            }
+
+            if toc.dependencies.iter().any(|d| is_own_folder(d)) {
+                // Report the addon owning the folder, or the folder itself if unknown.
+                let dependent = self.owners.get(key).unwrap_or(folder);
+
+                if dependent != &addon.id && !dependents.contains(dependent) {
+                    dependents.push(dependent.clone());
+                }
+            }
+        }
+        dependents.sort();
+
+        AddonDependencies {
+            bundled: addon.bundled_modules.clone(),
+            required,
+            optional,
+            missing,
+            dependents,
+        }
+    }
+}
+
+/// Computes which folders to remove when deleting `addon`.
+///
+/// Several packages can ship the same folder, e.g. a common library. A folder is
+/// only removed if no other addon in `addons` ships it as well.
+pub fn delete_plan(addon: &Addon, addons: &[Addon]) -> DeletePlan {
+    let shared: Vec<String> = addons
+        .iter()
+        .filter(|a| a.id != addon.id)
+        .flat_map(Addon::folders)
+        .collect();
+
+    let (keep, remove): (Vec<String>, Vec<String>) = addon
+        .folders()
+        .into_iter()
+        .partition(|folder| contains(&shared, folder));
+
+    DeletePlan { remove, keep }
+}
+
+/// Finds the catalog addon providing the addon folder `folder` for `flavor`.
+///
+/// The catalog doesn't know the folders of each addon, so we match on the name
+/// ignoring case and punctuation. If several addons match, the most downloaded wins.
+pub fn find_catalog_addon<'a>(
+    catalog: &'a Catalog,
+    folder: &str,
+    flavor: Flavor,
+) -> Option<&'a CatalogAddon> {
+    let folder = normalize_name(folder);
+
+    catalog
+        .addons
+        .iter()
+        .filter(|a| a.flavors.contains(&flavor) && normalize_name(&a.name) == folder)
+        .max_by_key(|a| a.number_of_downloads)
+}
+
+fn contains(names: &[String], name: &str) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toc(dependencies: &[&str], optional_dependencies: &[&str]) -> Toc {
+        Toc {
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            optional_dependencies: optional_dependencies
+                .iter()
+                .map(|d| d.to_string())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn addon(id: &str, bundled_modules: &[&str]) -> Addon {
+        let mut addon = Addon::empty(id);
+        addon.bundled_modules = bundled_modules.iter().map(|m| m.to_string()).collect();
+        addon
+    }
+
+    fn graph(addons: &[Addon]) -> DependencyGraph {
+        let mut tocs = HashMap::new();
+        tocs.insert("Foo".to_owned(), toc(&["Ace3"], &["LibStub"]));
+        tocs.insert("Foo_Options".to_owned(), toc(&["Foo", "Missing"], &[]));
+        tocs.insert("Ace3".to_owned(), toc(&[], &[]));
+        tocs.insert("Bar".to_owned(), toc(&["ace3"], &["Foo"]));
+        tocs.insert("Baz".to_owned(), toc(&["Foo_Options"], &[]));
+
+        DependencyGraph::new(tocs, addons)
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let foo = addon("Foo", &["Foo_Options"]);
+        let addons = vec![foo.clone(), addon("Ace3", &[]), addon("Bar", &[])];
+
+        let dependencies = graph(&addons).resolve(&foo);
+
+        assert_eq!(dependencies.bundled, vec!["Foo_Options"]);
+        assert_eq!(dependencies.required, vec!["Ace3", "Missing"]);
+        assert_eq!(dependencies.optional, vec!["LibStub"]);
+        assert_eq!(dependencies.missing, vec!["Missing"]);
+        // `Bar` only depends optionally on `Foo`.
+        assert_eq!(dependencies.dependents, vec!["Baz"]);
+    }
+
+    #[test]
+    fn test_resolve_dependents() {
+        let ace = addon("Ace3", &[]);
+        let addons = vec![
+            addon("Foo", &["Foo_Options"]),
+            ace.clone(),
+            addon("Bar", &[]),
+        ];
+
+        let dependencies = graph(&addons).resolve(&ace);
+
+        assert!(dependencies.required.is_empty());
+        assert!(dependencies.missing.is_empty());
+        assert_eq!(dependencies.dependents, vec!["Bar", "Foo"]);
+    }
+
+    #[test]
+    fn test_delete_plan_keeps_shared_folders() {
+        let foo = addon("Foo", &["Foo_Options", "LibShared"]);
+        let addons = vec![foo.clone(), addon("Bar", &["libshared"]), addon("Baz", &[])];
+
+        let plan = delete_plan(&foo, &addons);
+
+        assert_eq!(plan.remove, vec!["Foo", "Foo_Options"]);
+        assert_eq!(plan.keep, vec!["LibShared"]);
+    }
+}
@@ -2,10 +2,10 @@ use crate::{addon::Addon, Result};
 use std::fs::remove_dir_all;
 use std::path::PathBuf;
 
-/// Deletes an Addon and all dependencies from disk.
-pub fn delete_addons(path: &PathBuf, dependencies: &[String]) -> Result<()> {
-    for dependency in dependencies {
-        let path = path.join(dependency);
+/// Deletes the given addon folders from disk.
+pub fn delete_addons(path: &PathBuf, folders: &[String]) -> Result<()> {
+    for folder in folders {
+        let path = path.join(folder);
         if path.exists() {
             remove_dir_all(path)?;
         }
@@ -17,18 +17,31 @@ pub fn delete_addons(path: &PathBuf, dependencies: &[String]) -> Result<()> {
 /// Unzips an `Addon` archive, and once that is done, it moves the content
 /// to the `to_directory`.
 /// At the end it will cleanup and remove the archive.
+///
+/// Returns the sorted names of the top-level folders in the archive.
 pub async fn install_addon(
     addon: &Addon,
     from_directory: &PathBuf,
     to_directory: &PathBuf,
-) -> Result<()> {
+) -> Result<Vec<String>> {
     let zip_path = from_directory.join(addon.id.clone());
     let mut zip_file = std::fs::File::open(&zip_path)?;
     let mut archive = zip::ZipArchive::new(&mut zip_file)?;
+    let mut folders = vec![];
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
-        let path = to_directory.join(file.sanitized_name());
+        let name = file.sanitized_name();
+        let path = to_directory.join(&name);
+
+        // Archives don't always have an entry for each folder, so the folder
+        // is taken from the path of every entry within it.
+        let mut components = name.components();
+        if let Some(folder) = components.next() {
+            if file.is_dir() || components.next().is_some() {
+                folders.push(folder.as_os_str().to_string_lossy().to_string());
+            }
+        }
 
         // If top-level destination folder for addon, delete that folder to remove
         // the previous version so we guarantee a clean copy
@@ -54,5 +67,8 @@ pub async fn install_addon(
     // Cleanup
     std::fs::remove_file(&zip_path)?;
 
-    Ok(())
+    folders.sort();
+    folders.dedup();
+
+    Ok(folders)
 }
@@ -1,17 +1,23 @@
 pub mod addon;
 pub mod backup;
 pub mod catalog;
+pub mod changelog;
 pub mod config;
 pub mod curse_api;
+pub mod dependency;
 pub mod error;
 pub mod fs;
 pub mod murmur2;
 pub mod network;
 pub mod parse;
+pub mod search;
 #[cfg(feature = "gui")]
 pub mod theme;
+pub mod toc;
 pub mod tukui_api;
 pub mod utility;
+pub mod verify;
+pub mod wowi_api;
 
 use crate::error::ClientError;
 
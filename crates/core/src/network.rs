@@ -1,33 +1,137 @@
-use crate::{addon::Addon, Result};
+use crate::{
+    addon::{Addon, RemotePackage},
+    config::Network,
+    error::ClientError,
+    murmur2::calculate_hash,
+    Result,
+};
 use async_std::{
-    fs::{create_dir_all, File},
+    fs::{create_dir_all, metadata, remove_file, rename, File, OpenOptions},
+    prelude::*,
+    sync::Mutex,
+    task,
+};
+use chrono::{DateTime, Utc};
+use isahc::{
+    auth::Credentials,
+    config::{CaCertificate, RedirectPolicy},
+    http::{
+        header::{HeaderMap, HeaderName, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RETRY_AFTER},
+        StatusCode, Uri,
+    },
     prelude::*,
+    Body,
 };
-use isahc::prelude::*;
+use lazy_static::lazy_static;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Max attempts for a single request, including the first one.
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry. The delay is doubled for every following retry.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Longest delay between two attempts, also when the server asks for more.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Max concurrent connections to a single host.
+const MAX_CONNECTIONS_PER_HOST: usize = 6;
+/// Minimum time between the start of two requests to the same host.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(50);
+/// Max times an interrupted download is resumed before giving up.
+const MAX_DOWNLOAD_RESUMES: u32 = 3;
+/// Every zip archive starts with a local file header.
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+lazy_static! {
+    /// Client shared by every request, so connections are reused and the
+    /// per-host connection limit applies across the whole application.
+    static ref HTTP_CLIENT: RwLock<Arc<HttpClient>> = RwLock::new(Arc::new(
+        build_client(&Network::default()).expect("building http client")
+    ));
+
+    /// Earliest time the next request to each host is allowed to start.
+    static ref NEXT_REQUEST_AT: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+
+    static ref USER_AGENT: String = format!(
+        "ajour/{} (+https://github.com/casperstorm/ajour)",
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+/// Rebuilds the shared client with the proxy and certificate `settings`.
+/// Requests already in flight finish with the previous client, which is also
+/// kept if `settings` can't be applied.
+pub fn configure_client(settings: &Network) -> Result<()> {
+    let client = build_client(settings)?;
+
+    *HTTP_CLIENT.write().unwrap() = Arc::new(client);
+
+    Ok(())
+}
+
+fn build_client(settings: &Network) -> Result<HttpClient> {
+    let mut builder = HttpClient::builder()
+        .redirect_policy(RedirectPolicy::Follow)
+        .max_connections_per_host(MAX_CONNECTIONS_PER_HOST);
+
+    if let Some(proxy) = &settings.proxy {
+        let uri = proxy
+            .parse::<Uri>()
+            .map_err(|e| ClientError::Custom(format!("Invalid proxy url {}: {}", proxy, e)))?;
+
+        builder = builder.proxy(Some(uri));
+
+        if let Some(username) = &settings.proxy_username {
+            let password = settings.proxy_password.clone().unwrap_or_default();
+
+            builder = builder.proxy_credentials(Credentials::new(username.as_str(), password));
+        }
+
+        if !settings.no_proxy.is_empty() {
+            builder = builder.proxy_blacklist(settings.no_proxy.iter().cloned());
+        }
+    }
+
+    if let Some(ca_roots) = &settings.ca_roots {
+        if !ca_roots.is_file() {
+            return Err(ClientError::LoadFileDoesntExist(ca_roots.clone()));
+        }
+
+        // curl can't add to the system certificate authorities, the file replaces them.
+        builder = builder.ssl_ca_certificate(CaCertificate::file(ca_roots));
+    }
+
+    Ok(builder.build()?)
+}
 
 /// Generic request function.
 pub async fn request_async<T: ToString>(
-    shared_client: &HttpClient,
     url: T,
     headers: Vec<(&str, &str)>,
     timeout: Option<u64>,
-) -> Result<Response<isahc::Body>> {
+) -> Result<Response<Body>> {
     // Sometimes a download url has a space.
     let url = url.to_string().replace(" ", "%20");
 
-    let mut request = Request::builder().uri(url);
+    send_with_retries(&url, || {
+        let mut request = Request::builder()
+            .uri(&url)
+            .header("user-agent", USER_AGENT.as_str());
 
-    for (name, value) in headers {
-        request = request.header(name, value);
-    }
+        for (name, value) in headers.iter() {
+            request = request.header(*name, *value);
+        }
 
-    if let Some(timeout) = timeout {
-        request = request.timeout(std::time::Duration::from_secs(timeout));
-    }
+        if let Some(timeout) = timeout {
+            request = request.timeout(Duration::from_secs(timeout));
+        }
 
-    Ok(shared_client.send_async(request.body(())?).await?)
+        Ok(request.body(Body::empty())?)
+    })
+    .await
 }
 
 // Generic function for posting Json data
@@ -36,68 +140,491 @@ pub async fn post_json_async<T: ToString, D: Serialize>(
     data: D,
     headers: Vec<(&str, &str)>,
     timeout: Option<u64>,
-) -> Result<Response<isahc::Body>> {
-    let mut request = Request::builder()
-        .method("POST")
-        .uri(url.to_string())
-        .header("content-type", "application/json");
+) -> Result<Response<Body>> {
+    let url = url.to_string();
+    let data = serde_json::to_vec(&data)?;
+
+    send_with_retries(&url, || {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(&url)
+            .header("content-type", "application/json")
+            .header("user-agent", USER_AGENT.as_str());
+
+        for (name, value) in headers.iter() {
+            request = request.header(*name, *value);
+        }
+
+        if let Some(timeout) = timeout {
+            request = request.timeout(Duration::from_secs(timeout));
+        }
+
+        Ok(request.body(Body::from(data.clone()))?)
+    })
+    .await
+}
+
+/// Sends the request created by `build_request` with the shared client.
+///
+/// Server errors, `429 Too Many Requests` and timeouts are retried with an
+/// exponential backoff, honouring any `Retry-After` header from the server.
+async fn send_with_retries<F>(url: &str, build_request: F) -> Result<Response<Body>>
+where
+    F: Fn() -> Result<Request<Body>>,
+{
+    let host = url
+        .parse::<Uri>()
+        .ok()
+        .and_then(|uri| uri.host().map(str::to_owned))
+        .unwrap_or_default();
 
-    for (name, value) in headers {
-        request = request.header(name, value);
+    let mut attempt = 1;
+    loop {
+        wait_for_host(&host).await;
+
+        // The client is cloned, so the lock isn't held while waiting for the response.
+        let client = HTTP_CLIENT.read().unwrap().clone();
+
+        let retry_after = match client.send_async(build_request()?).await {
+            Ok(resp) if attempt < MAX_ATTEMPTS && is_retryable(resp.status()) => {
+                log::debug!("{} returned {}", url, resp.status());
+
+                resp.headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| parse_retry_after(value, Utc::now()))
+            }
+            Err(isahc::Error::Timeout) if attempt < MAX_ATTEMPTS => {
+                log::debug!("{} timed out", url);
+
+                None
+            }
+            result => return Ok(result?),
+        };
+
+        let delay = retry_delay(attempt, retry_after);
+        attempt += 1;
+
+        log::debug!(
+            "retrying {} in {:?} (attempt {} of {})",
+            url,
+            delay,
+            attempt,
+            MAX_ATTEMPTS
+        );
+        task::sleep(delay).await;
+    }
+}
+
+/// Waits until a new request to `host` is allowed to start.
+async fn wait_for_host(host: &str) {
+    let start_at = {
+        let mut next_request_at = NEXT_REQUEST_AT.lock().await;
+
+        let now = Instant::now();
+        let start_at = next_request_at
+            .get(host)
+            .copied()
+            .filter(|at| *at > now)
+            .unwrap_or(now);
+
+        next_request_at.insert(host.to_owned(), start_at + MIN_REQUEST_INTERVAL);
+
+        start_at
+    };
+
+    let now = Instant::now();
+    if start_at > now {
+        task::sleep(start_at - now).await;
+    }
+}
+
+/// Function returns a `bool` indicating if a response with `status` should be retried.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Returns the delay before retrying after `attempt` failed attempts.
+fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| INITIAL_BACKOFF * 2u32.pow(attempt - 1))
+        .min(MAX_BACKOFF)
+}
+
+/// Parses a `Retry-After` header, which is either a number of seconds or a http-date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+
+    // A date in the past means we can retry right away.
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Progress of a download, shared between the download and whoever displays it.
+#[derive(Debug, Default)]
+pub struct DownloadProgress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+}
+
+impl DownloadProgress {
+    /// Bytes downloaded so far, including bytes of a resumed partial download.
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Size of the whole download, if the server told us.
+    pub fn total(&self) -> Option<u64> {
+        Some(self.total.load(Ordering::Relaxed)).filter(|total| *total > 0)
+    }
+
+    /// Returns how much has been downloaded as a value between 0 and 1,
+    /// if the size of the download is known.
+    pub fn fraction(&self) -> Option<f32> {
+        self.total()
+            .map(|total| (self.downloaded() as f64 / total as f64).min(1.0) as f32)
     }
 
-    if let Some(timeout) = timeout {
-        request = request.timeout(std::time::Duration::from_secs(timeout));
+    fn start(&self, downloaded: u64, total: Option<u64>) {
+        self.downloaded.store(downloaded, Ordering::Relaxed);
+        self.total
+            .store(total.unwrap_or_default(), Ordering::Relaxed);
     }
 
-    Ok(request
-        .body(serde_json::to_vec(&data)?)?
-        .send_async()
-        .await?)
+    fn advance(&self, bytes: u64) {
+        self.downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Outcome of a single request for an archive.
+enum Transfer {
+    /// The body was read to the end. Holds the size of the whole archive, if known.
+    Complete(Option<u64>),
+    /// The connection was lost before the whole archive was read.
+    Interrupted(std::io::Error),
 }
 
 /// Function to download a zip archive for a `Addon`.
 /// Note: Addon needs to have a `remote_url` to the file.
-pub async fn download_addon(
-    shared_client: &HttpClient,
+pub async fn download_addon(addon: &Addon, to_directory: &PathBuf) -> Result<()> {
+    if let Some(package) = addon.relevant_release_package() {
+        download_addon_package(addon, package, to_directory).await?;
+    }
+
+    Ok(())
+}
+
+/// Function to download a specific `RemotePackage` for a `Addon`.
+/// The archive is saved in `to_directory` named after the addon id.
+///
+/// The archive is written to a partial file first, which is resumed with a
+/// `Range` request if the transfer is interrupted, also by a later download of
+/// the same package. It is only moved in place once it has been verified.
+/// Progress is reported on `addon.download_progress`.
+pub async fn download_addon_package(
     addon: &Addon,
+    package: &RemotePackage,
     to_directory: &PathBuf,
 ) -> Result<()> {
-    if let Some(package) = addon.relevant_release_package() {
-        log::debug!(
-            "downloading remote version {} for {}",
-            package.version,
-            &addon.id
-        );
-        let mut resp =
-            request_async(shared_client, package.download_url.clone(), vec![], None).await?;
-        let body = resp.body_mut();
+    log::debug!(
+        "downloading remote version {} for {}",
+        package.version,
+        &addon.id
+    );
+
+    if !to_directory.exists() {
+        create_dir_all(to_directory).await?;
+    }
+
+    // The partial file is named after the url, so we never resume a partial
+    // download of another version.
+    let url_hash = calculate_hash(package.download_url.as_bytes(), 1);
+    let partial_path = to_directory.join(format!("{}.{:08x}.part", addon.id, url_hash));
+    let zip_path = to_directory.join(&addon.id);
+
+    let mut resumes = 0;
+    let total = loop {
+        match transfer(
+            &package.download_url,
+            &partial_path,
+            &addon.download_progress,
+        )
+        .await?
+        {
+            Transfer::Complete(total) => break total,
+            Transfer::Interrupted(error) if resumes < MAX_DOWNLOAD_RESUMES => {
+                resumes += 1;
+                log::debug!(
+                    "download of {} interrupted, resuming (attempt {} of {}): {}",
+                    &addon.id,
+                    resumes,
+                    MAX_DOWNLOAD_RESUMES,
+                    error
+                );
+            }
+            Transfer::Interrupted(error) => return Err(error.into()),
+        }
+    };
+
+    let verified = verify_archive(&partial_path, total, package.file_length);
+    if verified.is_err() {
+        // Start over next time, rather than resuming a broken file.
+        let _ = remove_file(&partial_path).await;
+    }
+    verified?;
+
+    rename(&partial_path, &zip_path).await?;
+
+    Ok(())
+}
 
-        if !to_directory.exists() {
-            create_dir_all(to_directory).await?;
+/// Requests `url` and writes the body to `partial_path`, continuing from the
+/// end of the file if it already exists and the server supports it.
+async fn transfer(url: &str, partial_path: &Path, progress: &DownloadProgress) -> Result<Transfer> {
+    let offset = metadata(partial_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or_default();
+
+    let range = format!("bytes={}-", offset);
+    let headers = if offset > 0 {
+        vec![("range", range.as_str())]
+    } else {
+        vec![]
+    };
+
+    let mut resp = request_async(url, headers, None).await?;
+    let status = resp.status();
+
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        let total = header_value(resp.headers(), CONTENT_RANGE).and_then(content_range_total);
+
+        // The partial file already holds the whole archive.
+        if total == Some(offset) {
+            progress.start(offset, total);
+            return Ok(Transfer::Complete(total));
+        }
+
+        remove_file(partial_path).await?;
+        return Ok(Transfer::Interrupted(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "partial download doesn't match the remote file",
+        )));
+    }
+
+    if !status.is_success() {
+        return Err(ClientError::Custom(format!(
+            "Couldn't download {}. Server returned: {}",
+            url, status
+        )));
+    }
+
+    if let Some(content_type) = header_value(resp.headers(), CONTENT_TYPE) {
+        if !is_archive_content_type(content_type) {
+            return Err(ClientError::Custom(format!(
+                "Couldn't download {}. Expected an archive, but the server returned {}",
+                url, content_type
+            )));
         }
+    }
+
+    // A server which ignores the range sends the whole archive again.
+    let resumed = status == StatusCode::PARTIAL_CONTENT;
+    let start = if resumed { offset } else { 0 };
+    let total = if resumed {
+        header_value(resp.headers(), CONTENT_RANGE).and_then(content_range_total)
+    } else {
+        header_value(resp.headers(), CONTENT_LENGTH).and_then(|v| v.parse().ok())
+    };
+
+    let mut file = if resumed {
+        log::debug!("resuming download of {} at {} bytes", url, offset);
+        OpenOptions::new().append(true).open(partial_path).await?
+    } else {
+        File::create(partial_path).await?
+    };
+
+    progress.start(start, total);
+
+    let body = resp.body_mut();
+    let mut buffer = [0; 8000]; // 8KB
 
-        let zip_path = to_directory.join(&addon.id);
-        let mut buffer = [0; 8000]; // 8KB
-        let mut file = File::create(&zip_path).await?;
-
-        loop {
-            match body.read(&mut buffer).await {
-                Ok(0) => {
-                    break;
-                }
-                Ok(x) => {
-                    file.write_all(&buffer[0..x])
-                        .await
-                        .expect("TODO: error handling");
-                }
-                Err(e) => {
-                    println!("error: {:?}", e);
-                    break;
-                }
+    loop {
+        match body.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(x) => {
+                file.write_all(&buffer[0..x]).await?;
+                progress.advance(x as u64);
             }
+            Err(error) => {
+                file.flush().await?;
+                return Ok(Transfer::Interrupted(error));
+            }
+        }
+    }
+
+    file.flush().await?;
+
+    if let Some(total) = total {
+        if progress.downloaded() < total {
+            return Ok(Transfer::Interrupted(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "connection closed before the whole archive was received",
+            )));
         }
     }
 
+    Ok(Transfer::Complete(total))
+}
+
+/// Checks the downloaded archive at `path` against the size announced by the
+/// server and the size provided by the source, and that it is a zip archive.
+fn verify_archive(path: &Path, total: Option<u64>, file_length: Option<u64>) -> Result<()> {
+    let mut header = [0; 4];
+    let mut file = std::fs::File::open(path)?;
+    let length = file.metadata()?.len();
+    let read = std::io::Read::read(&mut file, &mut header)?;
+
+    check_archive(&header[..read], length, &[total, file_length])
+}
+
+/// Returns an error if an archive of `length` bytes starting with `header`
+/// isn't a zip archive or doesn't match any of the `expected_lengths`.
+fn check_archive(header: &[u8], length: u64, expected_lengths: &[Option<u64>]) -> Result<()> {
+    if let Some(expected) = expected_lengths
+        .iter()
+        .flatten()
+        .find(|expected| **expected != length)
+    {
+        return Err(ClientError::Custom(format!(
+            "Downloaded archive is {} bytes, expected {} bytes",
+            length, expected
+        )));
+    }
+
+    if !header.starts_with(ZIP_SIGNATURE) {
+        return Err(ClientError::Custom(
+            "Downloaded file is not a zip archive".to_owned(),
+        ));
+    }
+
     Ok(())
 }
+
+/// Returns `false` for content types of error pages and API responses.
+/// Archives are served under many types, so anything else is accepted.
+fn is_archive_content_type(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    !(mime.starts_with("text/") || mime.ends_with("json") || mime.ends_with("xml"))
+}
+
+/// Parses the size of the whole file from a `Content-Range` header,
+/// e.g. `bytes 200-999/1000` or `bytes */1000`.
+fn content_range_total(value: &str) -> Option<u64> {
+    value.rsplit('/').next()?.trim().parse().ok()
+}
+
+fn header_value(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1, None), Duration::from_millis(500));
+        assert_eq!(retry_delay(2, None), Duration::from_secs(1));
+        assert_eq!(retry_delay(3, None), Duration::from_secs(2));
+        assert_eq!(retry_delay(10, None), MAX_BACKOFF);
+        assert_eq!(
+            retry_delay(1, Some(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+        assert_eq!(retry_delay(1, Some(Duration::from_secs(600))), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.ymd(2020, 10, 21).and_hms(7, 28, 0);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2020 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2020 07:00:00 GMT", now),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_check_archive() {
+        let zip = b"PK\x03\x04";
+
+        assert!(check_archive(zip, 100, &[Some(100), None]).is_ok());
+        assert!(check_archive(zip, 100, &[None, None]).is_ok());
+        assert!(check_archive(zip, 100, &[Some(100), Some(120)]).is_err());
+        assert!(check_archive(b"<!DOCTYPE html>", 100, &[Some(100)]).is_err());
+        assert!(check_archive(b"", 0, &[None]).is_err());
+    }
+
+    #[test]
+    fn test_is_archive_content_type() {
+        assert!(is_archive_content_type("application/zip"));
+        assert!(is_archive_content_type("application/x-zip-compressed"));
+        assert!(is_archive_content_type("application/octet-stream"));
+        assert!(!is_archive_content_type("text/html; charset=utf-8"));
+        assert!(!is_archive_content_type("application/json"));
+        assert!(!is_archive_content_type("application/problem+json"));
+    }
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 200-999/1000"), Some(1000));
+        assert_eq!(content_range_total("bytes */1000"), Some(1000));
+        assert_eq!(content_range_total("bytes 200-999/*"), None);
+    }
+
+    #[test]
+    fn test_download_progress() {
+        let progress = DownloadProgress::default();
+        assert_eq!(progress.fraction(), None);
+
+        progress.start(250, Some(1000));
+        progress.advance(250);
+        assert_eq!(progress.downloaded(), 500);
+        assert_eq!(progress.fraction(), Some(0.5));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::OK));
+    }
+}
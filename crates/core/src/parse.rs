@@ -1,26 +1,27 @@
 use crate::{
     addon::Addon,
-    config::Flavor,
+    catalog::apply_custom_feeds,
+    config::{CustomFeed, CustomFeedId, Endpoints, Flavor},
     curse_api::{
         fetch_game_info, fetch_remote_packages_by_fingerprint, fetch_remote_packages_by_ids,
-        GameInfo,
+        FileParsingRule, GameInfo,
     },
     error::ClientError,
     fs::PersistentData,
     murmur2::calculate_hash,
+    toc::Toc,
     tukui_api::fetch_remote_package,
-    Result,
+    wowi_api, Result,
 };
 use async_std::sync::{Arc, Mutex};
 use fancy_regex::Regex;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::SystemTime;
+use walkdir::WalkDir;
 
 lazy_static::lazy_static! {
     static ref CACHED_GAME_INFO: Mutex<Option<GameInfo>> = Mutex::new(None);
@@ -31,6 +32,21 @@ pub struct Fingerprint {
     pub title: String,
     pub hash: Option<u32>,
     pub modified: SystemTime,
+    /// Files which made up `hash`, used to avoid rehashing unchanged files.
+    #[serde(default)]
+    pub files: Vec<FileFingerprint>,
+}
+
+/// Hash of a single file used when fingerprinting an addon folder.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct FileFingerprint {
+    /// Path relative to the AddOns directory.
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub hash: u32,
+    /// Paths included by this file, if it has been parsed.
+    pub includes: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -52,59 +68,296 @@ async fn load_fingerprint_collection() -> Result<FingerprintCollection> {
     Ok(FingerprintCollection::load_or_default()?)
 }
 
-pub struct ParsingPatterns {
-    pub initial_inclusion_regex: Regex,
-    pub extra_inclusion_regex: Regex,
-    pub file_parsing_regex: HashMap<String, (regex::Regex, Regex)>,
+/// Removes every stored fingerprint, forcing all addons to be fingerprinted
+/// from scratch the next time the addon directory is read.
+pub async fn clear_fingerprint_collection(
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+) -> Result<()> {
+    log::debug!("clearing fingerprint collection");
+
+    let mut collection_guard = fingerprint_collection.lock().await;
+
+    let collection = FingerprintCollection::default();
+    collection.save()?;
+
+    *collection_guard = Some(collection);
+
+    Ok(())
 }
 
-/// File parsing regexes used for parsing the addon files.
-pub async fn file_parsing_regex() -> Result<ParsingPatterns> {
-    // Fetches game_info from memory or API if not in memory
-    // Used to get regexs for various operations.
-    let game_info = {
-        let cached_info = { CACHED_GAME_INFO.lock().await.clone() };
+/// Returns the latest modified time of the directory and everything inside it.
+///
+/// The modified time of a directory only changes when entries are added or removed
+/// directly in it, so editing a nested file would otherwise go unnoticed.
+fn last_modified(dir: &Path) -> SystemTime {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max()
+        .unwrap_or_else(SystemTime::now)
+}
 
-        if let Some(info) = cached_info {
-            info
-        } else {
-            let info = fetch_game_info().await?;
-            *CACHED_GAME_INFO.lock().await = Some(info.clone());
+/// Rule describing how to find the files included by a file with a given extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsingRule {
+    /// File extension the rule applies to, including the leading dot, e.g. `.toc`.
+    pub file_extension: String,
+    /// Matches everything which should be removed before looking for includes.
+    pub comment_strip_pattern: String,
+    /// Matches an include, where the first capture group is the included path.
+    pub inclusion_pattern: String,
+}
 
-            info
+impl From<&FileParsingRule> for ParsingRule {
+    fn from(rule: &FileParsingRule) -> Self {
+        ParsingRule {
+            file_extension: rule.file_extension.clone(),
+            comment_strip_pattern: rule.comment_strip_pattern.clone(),
+            inclusion_pattern: rule.inclusion_pattern.clone(),
         }
-    };
+    }
+}
 
-    // Compile regexes
-    let addon_cat = &game_info.category_sections[0];
-    let initial_inclusion_regex =
-        Regex::new(&addon_cat.initial_inclusion_pattern).expect("Error compiling inclusion regex");
-    let extra_inclusion_regex = Regex::new(&addon_cat.extra_include_pattern)
-        .expect("Error compiling extra inclusion regex");
-    let file_parsing_regex = game_info
-        .file_parsing_rules
-        .iter()
-        .map(|data| {
-            let comment_strip_regex = regex::Regex::new(&data.comment_strip_pattern)
-                .expect("Error compiling comment strip regex");
+/// Calculates the fingerprint of an addon folder, the same way Curse does.
+///
+/// Files matching the initial inclusion pattern (the `.toc` file) are parsed for
+/// included files using the `ParsingRule` for their extension, which are parsed in
+/// turn. Files matching the extra inclusion pattern (`Bindings.xml`) are hashed but
+/// not parsed. Every file found this way is hashed with murmur2 after removing
+/// whitespace, and the sorted file hashes are hashed together into the fingerprint.
+///
+/// Paths are matched against the patterns relative to the AddOns directory, lowercased
+/// and with Windows separators. Included paths are resolved case-insensitively.
+pub struct Fingerprinter {
+    initial_inclusion_regex: Regex,
+    extra_inclusion_regex: Regex,
+    file_parsing_regex: HashMap<String, (regex::Regex, Regex)>,
+}
+
+impl Fingerprinter {
+    /// Creates a `Fingerprinter` from the given patterns and parsing rules.
+    pub fn new(
+        initial_inclusion_pattern: &str,
+        extra_inclusion_pattern: &str,
+        parsing_rules: &[ParsingRule],
+    ) -> Result<Self> {
+        let initial_inclusion_regex =
+            Regex::new(initial_inclusion_pattern).map_err(ClientError::fingerprint)?;
+        let extra_inclusion_regex =
+            Regex::new(extra_inclusion_pattern).map_err(ClientError::fingerprint)?;
+
+        let mut file_parsing_regex = HashMap::new();
+        for rule in parsing_rules {
+            let comment_strip_regex =
+                regex::Regex::new(&rule.comment_strip_pattern).map_err(ClientError::fingerprint)?;
             let inclusion_regex =
-                Regex::new(&data.inclusion_pattern).expect("Error compiling inclusion pattern");
-            (
-                data.file_extension.clone(),
+                Regex::new(&rule.inclusion_pattern).map_err(ClientError::fingerprint)?;
+
+            file_parsing_regex.insert(
+                rule.file_extension.clone(),
                 (comment_strip_regex, inclusion_regex),
-            )
+            );
+        }
+
+        Ok(Fingerprinter {
+            initial_inclusion_regex,
+            extra_inclusion_regex,
+            file_parsing_regex,
         })
-        .collect();
+    }
 
-    Ok(ParsingPatterns {
-        initial_inclusion_regex,
-        extra_inclusion_regex,
-        file_parsing_regex,
-    })
+    /// Creates a `Fingerprinter` using the patterns and rules from the Curse `GameInfo`.
+    pub fn from_game_info(game_info: &GameInfo) -> Result<Self> {
+        let addon_cat = game_info.category_sections.get(0).ok_or_else(|| {
+            ClientError::FingerprintError("No category section in game info".to_owned())
+        })?;
+        let parsing_rules: Vec<_> = game_info
+            .file_parsing_rules
+            .iter()
+            .map(ParsingRule::from)
+            .collect();
+
+        Fingerprinter::new(
+            &addon_cat.initial_inclusion_pattern,
+            &addon_cat.extra_include_pattern,
+            &parsing_rules,
+        )
+    }
+
+    /// Creates a `Fingerprinter` using the `GameInfo` from memory, or the Curse API
+    /// if not in memory.
+    pub async fn load(endpoints: &Endpoints) -> Result<Self> {
+        let game_info = {
+            let cached_info = { CACHED_GAME_INFO.lock().await.clone() };
+
+            if let Some(info) = cached_info {
+                info
+            } else {
+                let info = fetch_game_info(endpoints).await?;
+                *CACHED_GAME_INFO.lock().await = Some(info.clone());
+
+                info
+            }
+        };
+
+        Fingerprinter::from_game_info(&game_info)
+    }
+
+    /// Calculates the fingerprint of `addon_dir`.
+    pub fn fingerprint(&self, addon_dir: &Path) -> Result<u32> {
+        self.fingerprint_cached(addon_dir, &[])
+            .map(|(hash, _)| hash)
+    }
+
+    /// Same as `fingerprint`, but files in `cached_files` with an unchanged size and
+    /// modified time are not read again.
+    ///
+    /// Returns the fingerprint together with the `FileFingerprint` of every file used,
+    /// which should be passed as `cached_files` the next time the folder is fingerprinted.
+    pub fn fingerprint_cached(
+        &self,
+        addon_dir: &Path,
+        cached_files: &[FileFingerprint],
+    ) -> Result<(u32, Vec<FileFingerprint>)> {
+        let mut to_fingerprint = HashSet::new();
+        let mut to_parse = VecDeque::new();
+        let root_dir = addon_dir.parent().ok_or_else(|| {
+            ClientError::FingerprintError(format!("No parent directory for {:?}", addon_dir))
+        })?;
+
+        let cached_files: HashMap<&Path, &FileFingerprint> = cached_files
+            .iter()
+            .map(|file| (file.path.as_path(), file))
+            .collect();
+        let mut files: HashMap<PathBuf, FileFingerprint> = HashMap::new();
+
+        // Add initial files
+        let glob_pattern = format!(
+            "{}/**/*.*",
+            addon_dir
+                .to_str()
+                .ok_or_else(|| ClientError::FingerprintError(format!(
+                    "Invalid UTF8 path: {:?}",
+                    addon_dir
+                )))?
+        );
+        for path in glob::glob(&glob_pattern).map_err(ClientError::fingerprint)? {
+            let path = path.map_err(ClientError::fingerprint)?;
+            if !path.is_file() {
+                continue;
+            }
+
+            // Test relative path matches regexes
+            let relative_path = path
+                .strip_prefix(root_dir)
+                .map_err(ClientError::fingerprint)?
+                .to_str()
+                .ok_or_else(|| {
+                    ClientError::FingerprintError(format!("Invalid UTF8 path: {:?}", path))
+                })?
+                .to_ascii_lowercase()
+                .replace("/", "\\"); // Convert to windows seperator
+            if self
+                .initial_inclusion_regex
+                .is_match(&relative_path)
+                .map_err(ClientError::fingerprint)?
+            {
+                to_parse.push_back(path);
+            } else if self
+                .extra_inclusion_regex
+                .is_match(&relative_path)
+                .map_err(ClientError::fingerprint)?
+            {
+                to_fingerprint.insert(path);
+            }
+        }
+
+        // Parse additional files
+        while let Some(path) = to_parse.pop_front() {
+            if !path.exists() || !path.is_file() {
+                return Err(ClientError::FingerprintError(format!(
+                    "Invalid file given to parse: {:?}",
+                    path.display()
+                )));
+            }
+
+            to_fingerprint.insert(path.clone());
+
+            // Skip if no rules for extension
+            let ext = format!(
+                ".{}",
+                path.extension()
+                    .ok_or_else(|| ClientError::FingerprintError(format!(
+                        "Invalid extension for path: {:?}",
+                        path
+                    )))?
+                    .to_str()
+                    .ok_or_else(|| ClientError::FingerprintError(format!(
+                        "Invalid UTF8 path: {:?}",
+                        path
+                    )))?
+            );
+            if !self.file_parsing_regex.contains_key(&ext) {
+                continue;
+            }
+
+            // Parse file for matches, unless we already know what it includes
+            let rules = self.file_parsing_regex.get(&ext).ok_or_else(|| {
+                ClientError::FingerprintError(format!("ext not in file parsing regex: {:?}", ext))
+            })?;
+            let file = fingerprint_file(root_dir, &path, Some(rules), &cached_files)?;
+            let includes = file.includes.clone().unwrap_or_default();
+            files.insert(path.clone(), file);
+
+            for path_match in includes {
+                // Path might be case insensitive and have windows separators. Find it
+                let path_match = path_match.replace("\\", "/");
+                if let Some(parent) = path.parent() {
+                    let file_to_find = parent.join(Path::new(&path_match));
+
+                    if let Some(real_path) = find_file(&file_to_find) {
+                        to_parse.push_back(real_path);
+                    }
+                }
+            }
+        }
+
+        // Calculate fingerprints
+        let mut fingerprints = vec![];
+        for path in to_fingerprint.iter() {
+            if !files.contains_key(path) {
+                let file = fingerprint_file(root_dir, path, None, &cached_files)?;
+                files.insert(path.clone(), file);
+            }
+
+            fingerprints.push(files[path].hash);
+        }
+
+        // Calculate overall fingerprint
+        fingerprints.sort_unstable();
+        let to_hash = fingerprints
+            .iter()
+            .map(|val| val.to_string())
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok((
+            calculate_hash(to_hash.as_bytes(), 1),
+            files.values().cloned().collect(),
+        ))
+    }
 }
 
+/// Parses every addon folder in `root_dir`, and matches them against Curse,
+/// Tukui, WoWInterface and the custom feeds.
+///
+/// Addons from a custom feed have no id in their folder, so they are found by
+/// the folder they were installed in, as saved in `custom_feed_ids`.
 pub async fn read_addon_directory<P: AsRef<Path>>(
     fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    endpoints: &Endpoints,
+    custom_feeds: &[CustomFeed],
+    custom_feed_ids: &HashMap<String, CustomFeedId>,
     root_dir: P,
     flavor: Flavor,
 ) -> Result<Vec<Addon>> {
@@ -140,11 +393,7 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
         all_dirs.len()
     );
 
-    let ParsingPatterns {
-        initial_inclusion_regex,
-        extra_inclusion_regex,
-        file_parsing_regex,
-    } = file_parsing_regex().await?;
+    let fingerprinter = Fingerprinter::load(endpoints).await?;
 
     // Load fingerprint collection from memory else disk.
     let mut collection_guard = fingerprint_collection.lock().await;
@@ -162,32 +411,25 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
         .par_iter() // Easy parallelization
         .map(|dir_name| {
             let addon_dir = root_dir.join(dir_name);
-            let modified = if let Ok(metadata) = addon_dir.metadata() {
-                metadata.modified().unwrap_or_else(|_| SystemTime::now())
-            } else {
-                SystemTime::now()
-            };
+            let modified = last_modified(&addon_dir);
+
+            let stored_fingerprint = fingerprints.iter().find(|f| &f.title == dir_name);
 
             // If we have a stored fingerprint on disk, we use that.
-            if let Some(fingerprint) = fingerprints
-                .iter()
-                .find(|f| &f.title == dir_name && f.modified == modified)
-            {
+            if let Some(fingerprint) = stored_fingerprint.filter(|f| f.modified == modified) {
                 let _ = num_cached.fetch_add(1, Ordering::SeqCst);
                 fingerprint.to_owned()
             } else {
-                let hash_result = fingerprint_addon_dir(
-                    &addon_dir,
-                    &initial_inclusion_regex,
-                    &extra_inclusion_regex,
-                    &file_parsing_regex,
-                );
-
-                let hash = match hash_result {
-                    Ok(hash) => Some(hash),
+                // Files which hasn't changed since last time are not rehashed.
+                let cached_files = stored_fingerprint.map_or(&[][..], |f| &f.files[..]);
+
+                let hash_result = fingerprinter.fingerprint_cached(&addon_dir, cached_files);
+
+                let (hash, files) = match hash_result {
+                    Ok((hash, files)) => (Some(hash), files),
                     Err(e) => {
                         log::error!("fingerprinting failed for {:?}: {}", addon_dir, e);
-                        None
+                        (None, vec![])
                     }
                 };
 
@@ -195,6 +437,7 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
                     title: dir_name.to_owned(),
                     hash,
                     modified,
+                    files,
                 }
             }
         })
@@ -270,7 +513,7 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
     let mut tukui_addons = vec![];
     // Loops each tukui_id and fetch a remote package from their api.
     for id in tukui_ids {
-        let package = fetch_remote_package(&id, &flavor).await;
+        let package = fetch_remote_package(endpoints, &id, &flavor).await;
         // Find the corresponding addon.
         if let Some(mut addon) = unfiltred_addons
             .iter()
@@ -291,9 +534,9 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
         tukui_addons.len()
     );
 
-    // Links dependencies. This is needed for tukui addons since they don't
-    // tell which dependencies a addon has, so we use the information from toc.
-    link_dependencies_bidirectional(&mut tukui_addons, &unfiltred_addons);
+    // Links bundled modules. This is needed for tukui addons since they don't
+    // tell which folders a package has, so we use the information from toc.
+    link_bundled_modules(&mut tukui_addons, &unfiltred_addons);
 
     // Filter out addons with fingerprints.
     let mut fingerprint_hashes: Vec<_> = unfiltred_addons
@@ -318,7 +561,8 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
     );
 
     // Fetches fingerprint package from curse_api
-    let mut fingerprint_package = fetch_remote_packages_by_fingerprint(&fingerprint_hashes).await?;
+    let mut fingerprint_package =
+        fetch_remote_packages_by_fingerprint(endpoints, &fingerprint_hashes).await?;
 
     // We had a case where a addon hash returned a minecraft addon.
     // So we filter out all matches which does not have a valid flavor.
@@ -415,6 +659,54 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
         fingerprint_addons.len()
     );
 
+    // Local fingerprint for each folder, used to find which modules differs
+    // in a partial match.
+    let local_fingerprints: HashMap<String, u32> = unfiltred_addons
+        .iter()
+        .filter_map(|a| a.fingerprint.map(|hash| (a.id.clone(), hash)))
+        .collect();
+
+    // Converts the partial matches into our `Addon` struct, flagged as modified.
+    let partial_addons: Vec<_> = fingerprint_package
+        .partial_matches
+        .iter()
+        .filter(|info| {
+            !fingerprint_addons
+                .iter()
+                .any(|a| a.curse_id == Some(info.id))
+        })
+        .filter_map(|info| {
+            // We try to find the addon matching the curse_id, else we use the
+            // first module which exists locally.
+            let addon = unfiltred_addons
+                .iter()
+                .find(|a| a.curse_id == Some(info.id))
+                .or_else(|| {
+                    info.file
+                        .modules
+                        .iter()
+                        .find_map(|m| unfiltred_addons.iter().find(|a| a.id == m.foldername))
+                })
+                .filter(|a| !fingerprint_addons.iter().any(|f| f.id == a.id))
+                .cloned();
+
+            if let Some(mut addon) = addon {
+                addon.apply_partial_fingerprint_module(info, flavor, &local_fingerprints);
+                return Some(addon);
+            }
+
+            None
+        })
+        .collect();
+
+    log::debug!(
+        "{} - {} addons applied with partial fingerprint metadata",
+        flavor,
+        partial_addons.len()
+    );
+
+    fingerprint_addons.extend(partial_addons);
+
     // Creates a `Vec` of curse_ids.
     let curse_ids: Vec<_> = fingerprint_addons
         .iter()
@@ -434,7 +726,7 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
     );
 
     // Fetches the curse packages based on the ids.
-    let curse_id_packages_result = fetch_remote_packages_by_ids(&curse_ids).await;
+    let curse_id_packages_result = fetch_remote_packages_by_ids(endpoints, &curse_ids).await;
     if let Ok(curse_id_packages) = curse_id_packages_result {
         let mut updated = 0;
 
@@ -456,8 +748,94 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
         );
     }
 
+    // Addons from WoWInterface, which weren't matched by any of the above.
+    let matched = |a: &Addon| {
+        fingerprint_addons
+            .iter()
+            .chain(tukui_addons.iter())
+            .any(|m| m.id == a.id || m.bundled_modules.contains(&a.id))
+    };
+    let mut wowi_ids: Vec<_> = unfiltred_addons
+        .iter()
+        .filter(|a| a.tukui_id.is_none() && !matched(a))
+        .filter_map(|a| a.wowi_id.clone())
+        .collect();
+    wowi_ids.sort();
+    wowi_ids.dedup();
+
+    log::debug!("{} - {} addons with wowi id", flavor, wowi_ids.len());
+
+    let mut wowi_addons = vec![];
+    if !wowi_ids.is_empty() {
+        match wowi_api::fetch_remote_packages(endpoints, &wowi_ids).await {
+            Ok(packages) => {
+                for package in packages {
+                    // Every folder of a package usually has the wowi id, so the
+                    // shortest name is taken as the addon and the rest are bundled.
+                    let mut folders: Vec<_> = unfiltred_addons
+                        .iter()
+                        .filter(|a| {
+                            a.wowi_id.as_deref() == Some(package.id.as_str()) && !matched(a)
+                        })
+                        .collect();
+                    folders.sort_by_key(|a| a.id.len());
+
+                    if let Some((addon, bundled)) = folders.split_first() {
+                        let mut addon = (*addon).clone();
+                        addon.apply_wowi_package(&package);
+                        addon.bundled_modules = bundled.iter().map(|a| a.id.clone()).collect();
+                        wowi_addons.push(addon);
+                    }
+                }
+            }
+            Err(e) => log::error!("{} - couldn't fetch wowi packages: {}", flavor, e),
+        }
+    }
+
+    log::debug!(
+        "{} - {} addons applied with wowi id package metadata",
+        flavor,
+        wowi_addons.len()
+    );
+
+    link_bundled_modules(&mut wowi_addons, &unfiltred_addons);
+
+    // Addons installed from a custom feed, which weren't matched by any of the above.
+    let mut custom_feed_addons: Vec<_> = unfiltred_addons
+        .iter()
+        .filter(|a| {
+            !matched(a)
+                && !wowi_addons
+                    .iter()
+                    .any(|m| m.id == a.id || m.bundled_modules.contains(&a.id))
+        })
+        .filter_map(|a| {
+            let custom_feed_id = custom_feed_ids.get(&a.id)?;
+
+            let mut addon = a.clone();
+            addon.custom_feed_id = Some(custom_feed_id.clone());
+            Some(addon)
+        })
+        .collect();
+
+    apply_custom_feeds(custom_feeds, flavor, &mut custom_feed_addons).await;
+
+    log::debug!(
+        "{} - {} addons installed from a custom feed",
+        flavor,
+        custom_feed_addons.len()
+    );
+
+    link_bundled_modules(&mut custom_feed_addons, &unfiltred_addons);
+
     // Concats the different repo addons, and returns.
-    let concatenated = [&fingerprint_addons[..], &tukui_addons[..]].concat();
+    let concatenated = [
+        &fingerprint_addons[..],
+        &tukui_addons[..],
+        &wowi_addons[..],
+        &custom_feed_addons[..],
+    ]
+    .concat();
 
     log::debug!(
         "{} - {} addons successfully parsed",
@@ -468,31 +846,120 @@ pub async fn read_addon_directory<P: AsRef<Path>>(
     Ok(concatenated)
 }
 
+/// Fetches the latest remote packages of already parsed addons.
+///
+/// Addons keep the source ids they were matched with, so unlike
+/// `read_addon_directory` nothing is fingerprinted. Addons which couldn't be
+/// looked up keep their current remote packages. A source which can't be
+/// reached is logged and skipped, so the other sources are still refreshed.
+pub async fn refresh_remote_packages(
+    endpoints: &Endpoints,
+    custom_feeds: &[CustomFeed],
+    flavor: Flavor,
+    mut addons: Vec<Addon>,
+) -> Result<Vec<Addon>> {
+    let curse_ids: Vec<_> = addons
+        .iter()
+        .filter(|a| a.tukui_id.is_none())
+        .filter_map(|a| a.curse_id)
+        .collect();
+
+    if !curse_ids.is_empty() {
+        match fetch_remote_packages_by_ids(endpoints, &curse_ids).await {
+            Ok(packages) => {
+                for package in packages {
+                    for addon in addons
+                        .iter_mut()
+                        .filter(|a| a.tukui_id.is_none() && a.curse_id == Some(package.id))
+                    {
+                        addon.remote_packages.clear();
+                        addon.apply_curse_files(&package.latest_files, flavor);
+                    }
+                }
+            }
+            Err(e) => log::error!("{} - couldn't fetch curse packages: {}", flavor, e),
+        }
+    }
+
+    for addon in addons.iter_mut() {
+        if let Some(tukui_id) = addon.tukui_id.clone() {
+            match fetch_remote_package(endpoints, &tukui_id, &flavor).await {
+                Ok(package) => addon.apply_tukui_package(&package),
+                Err(e) => log::error!("{} - couldn't fetch tukui package: {}", flavor, e),
+            }
+        }
+    }
+
+    let mut wowi_ids: Vec<_> = addons
+        .iter()
+        .filter(|a| a.tukui_id.is_none() && a.curse_id.is_none())
+        .filter_map(|a| a.wowi_id.clone())
+        .collect();
+    wowi_ids.sort();
+    wowi_ids.dedup();
+
+    if !wowi_ids.is_empty() {
+        match wowi_api::fetch_remote_packages(endpoints, &wowi_ids).await {
+            Ok(packages) => {
+                for package in packages {
+                    for addon in addons.iter_mut().filter(|a| {
+                        a.tukui_id.is_none()
+                            && a.curse_id.is_none()
+                            && a.wowi_id.as_deref() == Some(package.id.as_str())
+                    }) {
+                        addon.apply_wowi_package(&package);
+                    }
+                }
+            }
+            Err(e) => log::error!("{} - couldn't fetch wowi packages: {}", flavor, e),
+        }
+    }
+
+    apply_custom_feeds(custom_feeds, flavor, &mut addons).await;
+
+    log::debug!(
+        "{} - refreshed remote packages of {} addons",
+        flavor,
+        addons.len()
+    );
+
+    Ok(addons)
+}
+
 pub async fn update_addon_fingerprint(
     fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    endpoints: &Endpoints,
     flavor: Flavor,
     addon_dir: impl AsRef<Path>,
     addon_id: String,
 ) -> Result<()> {
     log::debug!("{} - updating fingerprint for {}", flavor, &addon_id);
 
-    // Regexes
-    let ParsingPatterns {
-        initial_inclusion_regex,
-        extra_inclusion_regex,
-        file_parsing_regex,
-    } = file_parsing_regex().await?;
+    let fingerprinter = Fingerprinter::load(endpoints).await?;
 
     let addon_path = addon_dir.as_ref().join(&addon_id);
 
+    // Files from the previous fingerprint, so unchanged files are not rehashed.
+    let cached_files = {
+        let mut collection_guard = fingerprint_collection.lock().await;
+
+        if collection_guard.is_none() {
+            *collection_guard = Some(load_fingerprint_collection().await?);
+        }
+
+        collection_guard
+            .as_mut()
+            .unwrap()
+            .get_mut_for_flavor(flavor)
+            .iter()
+            .find(|f| f.title == addon_id)
+            .map(|f| f.files.clone())
+            .unwrap_or_default()
+    };
+
     // Generate new hash, and update collection.
-    match fingerprint_addon_dir(
-        &addon_path,
-        &initial_inclusion_regex,
-        &extra_inclusion_regex,
-        &file_parsing_regex,
-    ) {
-        Ok(hash) => {
+    match fingerprinter.fingerprint_cached(&addon_path, &cached_files) {
+        Ok((hash, files)) => {
             // Lock Mutex ensuring this is the only operation that can update the collection.
             // This is needed since during `Update All` we can have concurrent operations updating
             // this collection and we need to ensure they don't overwrite eachother.
@@ -504,16 +971,13 @@ pub async fn update_addon_fingerprint(
 
             let fingerprint_collection = collection_guard.as_mut().unwrap();
             let fingerprints = fingerprint_collection.get_mut_for_flavor(flavor);
-            let modified = if let Ok(metadata) = addon_path.metadata() {
-                metadata.modified().unwrap_or_else(|_| SystemTime::now())
-            } else {
-                SystemTime::now()
-            };
+            let modified = last_modified(&addon_path);
 
             fingerprints.iter_mut().for_each(|fingerprint| {
                 if fingerprint.title == addon_id {
                     fingerprint.hash = Some(hash);
                     fingerprint.modified = modified;
+                    fingerprint.files = files.clone();
                 }
             });
 
@@ -530,158 +994,98 @@ pub async fn update_addon_fingerprint(
     Ok(())
 }
 
-pub fn fingerprint_addon_dir(
-    addon_dir: &PathBuf,
-    initial_inclusion_regex: &Regex,
-    extra_inclusion_regex: &Regex,
-    file_parsing_regex: &HashMap<String, (regex::Regex, Regex)>,
-) -> Result<u32> {
-    let mut to_fingerprint = HashSet::new();
-    let mut to_parse = VecDeque::new();
-    let root_dir = addon_dir.parent().ok_or_else(|| {
-        ClientError::FingerprintError(format!("No parent directory for {:?}", addon_dir))
-    })?;
-
-    // Add initial files
-    let glob_pattern = format!(
-        "{}/**/*.*",
-        addon_dir
-            .to_str()
-            .ok_or_else(|| ClientError::FingerprintError(format!(
-                "Invalid UTF8 path: {:?}",
-                addon_dir
-            )))?
-    );
-    for path in glob::glob(&glob_pattern).map_err(ClientError::fingerprint)? {
-        let path = path.map_err(ClientError::fingerprint)?;
-        if !path.is_file() {
-            continue;
-        }
-
-        // Test relative path matches regexes
-        let relative_path = path
-            .strip_prefix(root_dir)
-            .map_err(ClientError::fingerprint)?
-            .to_str()
-            .ok_or_else(|| ClientError::FingerprintError(format!("Invalid UTF8 path: {:?}", path)))?
-            .to_ascii_lowercase()
-            .replace("/", "\\"); // Convert to windows seperator
-        if initial_inclusion_regex
-            .is_match(&relative_path)
-            .map_err(ClientError::fingerprint)?
-        {
-            to_parse.push_back(path);
-        } else if extra_inclusion_regex
-            .is_match(&relative_path)
-            .map_err(ClientError::fingerprint)?
+/// Returns the `FileFingerprint` for the file at `path`, either from `cached_files`
+/// if size and modified time still matches, or by reading the file.
+///
+/// If `rules` is given, the returned `FileFingerprint` will contain the files it includes.
+fn fingerprint_file(
+    root_dir: &Path,
+    path: &Path,
+    rules: Option<&(regex::Regex, Regex)>,
+    cached_files: &HashMap<&Path, &FileFingerprint>,
+) -> Result<FileFingerprint> {
+    let relative_path = path
+        .strip_prefix(root_dir)
+        .map_err(ClientError::fingerprint)?;
+    let metadata = path.metadata().map_err(ClientError::fingerprint)?;
+    let size = metadata.len();
+    let modified = metadata.modified().map_err(ClientError::fingerprint)?;
+
+    if let Some(cached) = cached_files.get(relative_path) {
+        if cached.size == size
+            && cached.modified == modified
+            && (rules.is_none() || cached.includes.is_some())
         {
-            to_fingerprint.insert(path);
+            return Ok((*cached).clone());
         }
     }
 
-    // Parse additional files
-    while let Some(path) = to_parse.pop_front() {
-        if !path.exists() || !path.is_file() {
-            return Err(ClientError::FingerprintError(format!(
-                "Invalid file given to parse: {:?}",
-                path.display()
-            )));
-        }
-
-        to_fingerprint.insert(path.clone());
+    let buf = std::fs::read(path).map_err(ClientError::fingerprint)?;
 
-        // Skip if no rules for extension
-        let ext = format!(
-            ".{}",
-            path.extension()
-                .ok_or_else(|| ClientError::FingerprintError(format!(
-                    "Invalid extension for path: {:?}",
-                    path
-                )))?
-                .to_str()
-                .ok_or_else(|| ClientError::FingerprintError(format!(
-                    "Invalid UTF8 path: {:?}",
-                    path
-                )))?
-        );
-        if !file_parsing_regex.contains_key(&ext) {
-            continue;
-        }
+    let includes = match rules {
+        Some((comment_strip_regex, inclusion_regex)) => Some(parse_file_includes(
+            &buf,
+            comment_strip_regex,
+            inclusion_regex,
+        )?),
+        None => None,
+    };
 
-        // Parse file for matches
-        let (comment_strip_regex, inclusion_regex) =
-            file_parsing_regex.get(&ext).ok_or_else(|| {
-                ClientError::FingerprintError(format!("ext not in file parsing regex: {:?}", ext))
-            })?;
-        let mut file = File::open(&path).map_err(ClientError::fingerprint)?;
-
-        let mut buf = vec![];
-        file.read_to_end(&mut buf)
-            .map_err(ClientError::fingerprint)?;
-
-        let text = String::from_utf8_lossy(&buf);
-        let text = comment_strip_regex.replace_all(&text, "");
-        for line in text.split(&['\n', '\r'][..]) {
-            let mut last_offset = 0;
-            while let Some(inc_match) = inclusion_regex
-                .captures_from_pos(line, last_offset)
-                .map_err(ClientError::fingerprint)?
-            {
-                let prev_last_offset = last_offset;
-                last_offset = inc_match
-                    .get(0)
-                    .ok_or_else(|| {
-                        ClientError::FingerprintError(format!(
-                            "Inclusion regex error for group 0 on pos {}, line: {:?}",
-                            prev_last_offset, line
-                        ))
-                    })?
-                    .end();
-                let path_match = inc_match
-                    .get(1)
-                    .ok_or_else(|| {
-                        ClientError::FingerprintError(format!(
-                            "Inclusion regex error for group 1 on pos {}, line: {:?}",
-                            prev_last_offset, line
-                        ))
-                    })?
-                    .as_str();
-                // Path might be case insensitive and have windows separators. Find it
-                let path_match = path_match.replace("\\", "/");
-                if let Some(parent) = path.parent() {
-                    let file_to_find = parent.join(Path::new(&path_match));
+    let data: Vec<_> = buf
+        .into_iter()
+        .filter(|&b| b != b' ' && b != b'\n' && b != b'\r' && b != b'\t')
+        .collect();
 
-                    if let Some(real_path) = find_file(&file_to_find) {
-                        to_parse.push_back(real_path);
-                    }
-                }
-            }
-        }
-    }
+    Ok(FileFingerprint {
+        path: relative_path.to_path_buf(),
+        size,
+        modified,
+        hash: calculate_hash(&data, 1),
+        includes,
+    })
+}
 
-    // Calculate fingerprints
-    let mut fingerprints = vec![];
-    for path in to_fingerprint.iter() {
-        let data: Vec<_> = std::fs::read(path)
+/// Returns every path included by a file, as written in the file.
+fn parse_file_includes(
+    buf: &[u8],
+    comment_strip_regex: &regex::Regex,
+    inclusion_regex: &Regex,
+) -> Result<Vec<String>> {
+    let mut includes = vec![];
+
+    let text = String::from_utf8_lossy(buf);
+    let text = comment_strip_regex.replace_all(&text, "");
+    for line in text.split(&['\n', '\r'][..]) {
+        let mut last_offset = 0;
+        while let Some(inc_match) = inclusion_regex
+            .captures_from_pos(line, last_offset)
             .map_err(ClientError::fingerprint)?
-            .into_iter()
-            .filter(|&b| b != b' ' && b != b'\n' && b != b'\r' && b != b'\t')
-            .collect();
-
-        let hash = calculate_hash(&data, 1);
-
-        fingerprints.push(hash);
+        {
+            let prev_last_offset = last_offset;
+            last_offset = inc_match
+                .get(0)
+                .ok_or_else(|| {
+                    ClientError::FingerprintError(format!(
+                        "Inclusion regex error for group 0 on pos {}, line: {:?}",
+                        prev_last_offset, line
+                    ))
+                })?
+                .end();
+            let path_match = inc_match
+                .get(1)
+                .ok_or_else(|| {
+                    ClientError::FingerprintError(format!(
+                        "Inclusion regex error for group 1 on pos {}, line: {:?}",
+                        prev_last_offset, line
+                    ))
+                })?
+                .as_str();
+
+            includes.push(path_match.to_owned());
+        }
     }
 
-    // Calculate overall fingerprint
-    fingerprints.sort_unstable();
-    let to_hash = fingerprints
-        .iter()
-        .map(|val| val.to_string())
-        .collect::<Vec<_>>()
-        .join("");
-
-    Ok(calculate_hash(to_hash.as_bytes(), 1))
+    Ok(includes)
 }
 
 /// Finds a case sensitive path from an insensitive path
@@ -722,37 +1126,38 @@ where
     Some(current)
 }
 
-/// Helper function to run through all addons and
-/// link all dependencies bidirectional.
+/// Helper function to find the bundled modules of each addon.
+///
+/// This is needed for tukui addons, since their api doesn't tell which folders
+/// are shipped in the package, so we use the information from the toc files.
 ///
 /// Example: We download a Addon which upon unzipping has
-/// three folders (addons): `Foo`, `Bar`, `Baz`.
-/// `Foo` is the parent and `Bar` and `Baz` are two helper addons.
-/// `Bar` and `Baz` are both dependent on `Foo`.
-/// This we know because of their .toc file.
-/// However we don't know anything about `Bar` and `Baz` when
-/// only looking at `Foo`.
+/// three folders (addons): `Foo`, `Foo_Config`, `Foo_Extras`.
+/// `Foo` is the parent and `Foo_Config` and `Foo_Extras` are two helper addons
+/// which both depend on `Foo`, and are named after it.
 ///
-/// After reading TOC files:
-/// `Foo` - dependencies: []
-/// `Bar` - dependencies: [`Foo`]
-/// `Baz` - dependencies: [`Foo`]
+/// After linking:
+/// `Foo` - bundled modules: [`Foo_Config`, `Foo_Extras`]
 ///
-/// After bidirectional dependencies link:
-/// `Foo` - dependencies: [`Bar`, `Baz`]
-/// `Bar` - dependencies: [`Foo`]
-/// `Baz` - dependencies: [`Foo`]
-fn link_dependencies_bidirectional(sliced_addons: &mut [Addon], all_addons: &[Addon]) {
+/// Folders with a project id of their own are separate addons, e.g. plugins,
+/// and are never bundled even if they depend on the addon.
+fn link_bundled_modules(sliced_addons: &mut [Addon], all_addons: &[Addon]) {
     for addon in sliced_addons {
+        let prefix = format!("{}_", addon.id);
+
         for unsorted_addon in all_addons {
-            for dependency in &unsorted_addon.dependencies {
-                if dependency == &addon.id {
-                    addon.dependencies.push(unsorted_addon.id.clone());
-                }
+            let has_project_id = unsorted_addon.tukui_id.is_some()
+                || unsorted_addon.curse_id.is_some()
+                || unsorted_addon.wowi_id.is_some();
+
+            if unsorted_addon.id.starts_with(&prefix)
+                && !has_project_id
+                && unsorted_addon.toc.dependencies.contains(&addon.id)
+                && !addon.bundled_modules.contains(&unsorted_addon.id)
+            {
+                addon.bundled_modules.push(unsorted_addon.id.clone());
             }
         }
-
-        addon.dependencies.dedup();
     }
 }
 
@@ -762,87 +1167,169 @@ fn link_dependencies_bidirectional(sliced_addons: &mut [Addon], all_addons: &[Ad
 /// TOC format summary:
 /// https://wowwiki.fandom.com/wiki/TOC_format
 pub fn parse_toc_path(toc_path: &PathBuf) -> Option<Addon> {
-    //direntry
-    let file = if let Ok(file) = File::open(toc_path) {
-        file
-    } else {
-        return None;
-    };
-    let reader = BufReader::new(file);
+    let toc = Toc::from_path(toc_path).ok()?;
 
     let path = toc_path.parent()?.to_path_buf();
     let id = path.file_name()?.to_str()?.to_string();
-    let mut title: Option<String> = None;
-    let mut author: Option<String> = None;
-    let mut notes: Option<String> = None;
-    let mut version: Option<String> = None;
-    let mut dependencies: Vec<String> = Vec::new();
-    let mut wowi_id: Option<String> = None;
-    let mut tukui_id: Option<String> = None;
-    let mut curse_id: Option<u32> = None;
-
-    // TODO: We should save these somewere so we don't keep creating them.
-    let re_toc = regex::Regex::new(r"^##\s*(?P<key>.*?)\s*:\s?(?P<value>.*)").unwrap();
-    let re_title = regex::Regex::new(r"\|[a-fA-F\d]{9}([^|]+)\|r?").unwrap();
-
-    for line in reader.lines().filter_map(|l| l.ok()) {
-        for cap in re_toc.captures_iter(line.as_str()) {
-            match &cap["key"] {
-                // Note: Coloring is possible via UI escape sequences.
-                // Since we don't want any color modifications, we will trim it away.
-                "Title" => {
-                    title = Some(re_title.replace_all(&cap["value"], "$1").trim().to_string())
-                }
-                "Author" => author = Some(cap["value"].trim().to_string()),
-                "Notes" => {
-                    notes = Some(re_title.replace_all(&cap["value"], "$1").trim().to_string())
-                }
-                "Version" => version = Some(cap["value"].trim().to_owned()),
-                // Names that must be loaded before this addon can be loaded.
-                "Dependencies" | "RequiredDeps" => {
-                    dependencies.append(&mut split_dependencies_into_vec(&cap["value"]));
-                }
-                "X-Tukui-ProjectID" => tukui_id = Some(cap["value"].to_string()),
-                "X-WoWI-ID" => wowi_id = Some(cap["value"].to_string()),
-                "X-Curse-Project-ID" => {
-                    if let Ok(id) = cap["value"].to_string().parse::<u32>() {
-                        curse_id = Some(id)
-                    }
-                }
-                _ => (),
-            }
-        }
-    }
 
     // If we don't find title, we will fallback to id (foldername).
-    let title = if let Some(title) = title {
-        title
-    } else {
-        id.clone()
-    };
+    let title = toc.title.clone().unwrap_or_else(|| id.clone());
 
-    Some(Addon::new(
+    let mut addon = Addon::new(
         id,
         title,
-        author,
-        notes,
-        version,
+        toc.author.clone(),
+        toc.notes.clone(),
+        toc.version.clone(),
         path,
-        dependencies,
-        wowi_id,
-        tukui_id,
-        curse_id,
-    ))
+        toc.wowi_id.clone(),
+        toc.tukui_id.clone(),
+        toc.curse_id,
+    );
+    addon.toc = toc;
+
+    Some(addon)
 }
 
-/// Helper function to split a comma separated string into `Vec<String>`.
-fn split_dependencies_into_vec(value: &str) -> Vec<String> {
-    if value == "" {
-        return vec![];
+#[cfg(test)]
+impl Fingerprinter {
+    /// Patterns and rules as served by the Curse API for World of Warcraft.
+    pub(crate) fn curse_defaults() -> Self {
+        let parsing_rules = vec![
+            ParsingRule {
+                file_extension: ".xml".to_owned(),
+                comment_strip_pattern: r"(?s)<!--.*?-->".to_owned(),
+                inclusion_pattern:
+                    r#"(?i)<(?:Include|Script)\s+file=[""']((?:(?<!\.\.).)+)[""']\s*/>"#.to_owned(),
+            },
+            ParsingRule {
+                file_extension: ".toc".to_owned(),
+                comment_strip_pattern: r"(?m)\s*#.*$".to_owned(),
+                inclusion_pattern: r"(?mi)^\s*((?:(?<!\.\.).)+\.(?:xml|lua))\s*$".to_owned(),
+            },
+        ];
+
+        Fingerprinter::new(
+            r"(?i)^([^/]+)[\\/]\1\.toc$",
+            r"(?i)^[^/\\]+[/\\]Bindings\.xml$",
+            &parsing_rules,
+        )
+        .unwrap()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // The expected fingerprints below are not taken from the Curse API. They
+    // were computed separately from this code: the files each fixture should
+    // include were listed by hand following the rules in
+    // `Fingerprinter::curse_defaults`, and hashed with
+    // an independent murmur2 implementation, checked against the known Curse
+    // hash in `murmur2::tests::test_hash`. Each file hash is taken without
+    // whitespace, and the sorted hashes are joined as decimal strings and
+    // hashed again.
+    fn fingerprint_fixture(name: &str) -> u32 {
+        let addon_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/fingerprint")
+            .join(name);
+
+        Fingerprinter::curse_defaults()
+            .fingerprint(&addon_dir)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_toc_only() {
+        // Files not referenced by the toc are ignored.
+        assert_eq!(fingerprint_fixture("Simple"), 1_067_348_738);
+    }
+
+    #[test]
+    fn test_fingerprint_include_chain() {
+        // toc -> xml -> xml -> lua, Bindings.xml and commented out includes.
+        assert_eq!(fingerprint_fixture("Chain"), 173_529_029);
+    }
+
+    #[test]
+    fn test_fingerprint_case_insensitive_paths() {
+        // Includes differs in case from the files on disk, and paths
+        // containing `..` are not followed.
+        assert_eq!(fingerprint_fixture("CaseMismatch"), 3_053_873_665);
+    }
+
+    #[test]
+    fn test_fingerprint_whitespace_normalization() {
+        // Same content with different whitespace and line endings.
+        assert_eq!(fingerprint_fixture("Whitespace"), 3_503_224_926);
+        assert_eq!(fingerprint_fixture("Compact"), 3_503_224_926);
+    }
+
+    #[test]
+    fn test_fingerprint_cached_matches_uncached() {
+        let fingerprinter = Fingerprinter::curse_defaults();
+        let addon_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fingerprint/Chain");
 
-    value
-        .split([','].as_ref())
-        .map(|s| s.trim().to_string())
-        .collect()
+        let (hash, files) = fingerprinter.fingerprint_cached(&addon_dir, &[]).unwrap();
+        assert_eq!(files.len(), 7);
+
+        let (cached_hash, cached_files) = fingerprinter
+            .fingerprint_cached(&addon_dir, &files)
+            .unwrap();
+        assert_eq!(hash, cached_hash);
+        assert_eq!(files.len(), cached_files.len());
+    }
+
+    #[test]
+    fn test_fingerprint_file_cache() {
+        let fingerprinter = Fingerprinter::curse_defaults();
+        let addon_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fingerprint/Simple");
+        let lua_path = Path::new("Simple/Simple.lua");
+
+        let (hash, files) = fingerprinter.fingerprint_cached(&addon_dir, &[]).unwrap();
+        let lua = files.iter().find(|f| f.path == lua_path).unwrap().clone();
+
+        // Returns the hash of Simple.lua, after fingerprinting with `cached`
+        // in place of its real entry.
+        let lua_hash_with = |cached: FileFingerprint| {
+            let mut cached_files = files.clone();
+            cached_files.retain(|f| f.path != lua_path);
+            cached_files.push(cached);
+
+            let (_, files) = fingerprinter
+                .fingerprint_cached(&addon_dir, &cached_files)
+                .unwrap();
+            files.into_iter().find(|f| f.path == lua_path).unwrap().hash
+        };
+
+        // An entry with the same size and modification time is reused as is,
+        // even if its hash no longer matches the file.
+        let stale = FileFingerprint {
+            hash: lua.hash.wrapping_add(1),
+            ..lua.clone()
+        };
+        assert_eq!(lua_hash_with(stale.clone()), stale.hash);
+
+        // A changed size or modification time forces the file to be rehashed.
+        let resized = FileFingerprint {
+            size: lua.size + 1,
+            ..stale.clone()
+        };
+        assert_eq!(lua_hash_with(resized), lua.hash);
+
+        let touched = FileFingerprint {
+            modified: lua.modified - Duration::from_secs(1),
+            ..stale
+        };
+        assert_eq!(lua_hash_with(touched), lua.hash);
+
+        let (cached_hash, _) = fingerprinter
+            .fingerprint_cached(&addon_dir, &files)
+            .unwrap();
+        assert_eq!(hash, cached_hash);
+    }
 }
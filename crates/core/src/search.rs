@@ -0,0 +1,589 @@
+use crate::catalog::{CatalogAddon, Source};
+use crate::utility::normalize_name;
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How much a match in each field counts towards the relevance of an addon.
+const NAME_WEIGHT: f64 = 1.0;
+const CATEGORY_WEIGHT: f64 = 0.6;
+const SUMMARY_WEIGHT: f64 = 0.5;
+
+/// How much match quality and popularity count towards the score.
+const RELEVANCE_WEIGHT: f64 = 0.75;
+const POPULARITY_WEIGHT: f64 = 0.25;
+
+/// Number of downloads at which an addon is considered as popular as it gets.
+const MAX_POPULARITY_DOWNLOADS: f64 = 1e9;
+
+/// A parsed catalog search.
+///
+/// Words are matched fuzzily against the name, summary and categories of an
+/// addon, and every word has to match. The query also supports:
+///
+/// - `"quoted phrases"`, which has to match exactly
+/// - `category:`, `source:` and `flavor:` filters, where values with spaces
+///   can be quoted, e.g. `category:"Bags & Inventory"`
+/// - `-word`, `-"phrase"` and `-source:curse` to exclude addons
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    terms: Vec<String>,
+    phrases: Vec<String>,
+    excluded: Vec<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    field: FilterField,
+    value: String,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FilterField {
+    Category,
+    Source,
+    Flavor,
+}
+
+impl FilterField {
+    fn from_key(key: &str) -> Option<FilterField> {
+        match key.to_lowercase().as_str() {
+            "category" => Some(FilterField::Category),
+            "source" => Some(FilterField::Source),
+            "flavor" => Some(FilterField::Flavor),
+            _ => None,
+        }
+    }
+}
+
+impl Filter {
+    fn matches(&self, addon: &CatalogAddon) -> bool {
+        match self.field {
+            FilterField::Category => addon
+                .categories
+                .iter()
+                .any(|c| normalize_name(c).contains(&self.value)),
+            FilterField::Source => addon.sources().iter().any(|s| self.matches_source(s)),
+            FilterField::Flavor => addon
+                .flavors
+                .iter()
+                .any(|f| normalize_name(&f.to_string()).starts_with(&self.value)),
+        }
+    }
+
+    /// Sources match by prefix, so `source:wowi` finds `WoWInterface`.
+    fn matches_source(&self, source: &Source) -> bool {
+        normalize_name(&source.to_string()).starts_with(&self.value)
+    }
+}
+
+impl Query {
+    /// Parses a search query. Anything which isn't valid syntax is searched
+    /// for as words, so parsing never fails.
+    pub fn parse(input: &str) -> Query {
+        let mut query = Query::default();
+        let mut chars = input.chars().peekable();
+
+        loop {
+            match chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    chars.next();
+                    continue;
+                }
+                Some(_) => {}
+                None => break,
+            }
+
+            let negated = chars.peek() == Some(&'-');
+            if negated {
+                chars.next();
+            }
+
+            let (token, quoted) = read_token(&mut chars);
+
+            if quoted {
+                let phrase = token.trim().to_lowercase();
+                if phrase.is_empty() {
+                    continue;
+                } else if negated {
+                    query.excluded.push(phrase);
+                } else {
+                    query.phrases.push(phrase);
+                }
+                continue;
+            }
+
+            if let Some(idx) = token.find(':') {
+                let field = FilterField::from_key(&token[..idx]);
+                let value = normalize_name(&token[idx + 1..]);
+
+                if let (Some(field), false) = (field, value.is_empty()) {
+                    query.filters.push(Filter {
+                        field,
+                        value,
+                        negated,
+                    });
+                    continue;
+                }
+            }
+
+            if negated {
+                let word = token.to_lowercase();
+                if !word.is_empty() {
+                    query.excluded.push(word);
+                }
+            } else {
+                query.terms.extend(words(&token));
+            }
+        }
+
+        query
+    }
+
+    /// Returns true if the query doesn't filter anything.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+            && self.phrases.is_empty()
+            && self.excluded.is_empty()
+            && self.filters.is_empty()
+    }
+
+    /// Returns true if the query has words or phrases to rank by.
+    pub fn has_text(&self) -> bool {
+        !self.terms.is_empty() || !self.phrases.is_empty()
+    }
+
+    /// Returns the score of `addon`, or `None` if it doesn't match the query.
+    ///
+    /// The score blends how well the addon matches with how popular it is,
+    /// so a popular addon ranks above an obscure one matching equally well.
+    pub fn score(&self, addon: &CatalogAddon) -> Option<f64> {
+        if !self.matches_filters(addon) || self.is_excluded(addon) {
+            return None;
+        }
+
+        let relevance = if self.has_text() {
+            self.relevance(addon)?
+        } else {
+            1.0
+        };
+
+        Some(RELEVANCE_WEIGHT * relevance + POPULARITY_WEIGHT * popularity(addon))
+    }
+
+    /// Returns the first source of `addon` matching a `source:` filter, which
+    /// the addon should be shown from.
+    pub fn preferred_source(&self, addon: &CatalogAddon) -> Option<Source> {
+        let sources = addon.sources();
+
+        self.filters
+            .iter()
+            .filter(|f| f.field == FilterField::Source && !f.negated)
+            .find_map(|f| sources.iter().find(|s| f.matches_source(s)).cloned())
+    }
+
+    /// Every field is matched if any of its filters match, while a negated
+    /// filter excludes the addon.
+    fn matches_filters(&self, addon: &CatalogAddon) -> bool {
+        let fields = [
+            FilterField::Category,
+            FilterField::Source,
+            FilterField::Flavor,
+        ];
+
+        let matches_fields = fields.iter().all(|field| {
+            let mut filters = self
+                .filters
+                .iter()
+                .filter(|f| f.field == *field && !f.negated)
+                .peekable();
+
+            filters.peek().is_none() || filters.any(|f| f.matches(addon))
+        });
+
+        matches_fields
+            && self
+                .filters
+                .iter()
+                .filter(|f| f.negated)
+                .all(|f| !f.matches(addon))
+    }
+
+    fn is_excluded(&self, addon: &CatalogAddon) -> bool {
+        if self.excluded.is_empty() {
+            return false;
+        }
+
+        let text = format!(
+            "{} {} {}",
+            addon.name,
+            addon.summary,
+            addon.categories.join(" ")
+        )
+        .to_lowercase();
+
+        self.excluded.iter().any(|e| text.contains(e.as_str()))
+    }
+
+    /// Returns how well `addon` matches the words and phrases, where every
+    /// one of them has to match.
+    fn relevance(&self, addon: &CatalogAddon) -> Option<f64> {
+        let name = words(&addon.name);
+        let summary = words(&addon.summary);
+        let categories: Vec<_> = addon.categories.iter().flat_map(|c| words(c)).collect();
+
+        let mut total = 0.0;
+
+        for term in &self.terms {
+            let score = [
+                (&name, NAME_WEIGHT),
+                (&categories, CATEGORY_WEIGHT),
+                (&summary, SUMMARY_WEIGHT),
+            ]
+            .iter()
+            .map(|(words, weight)| {
+                weight
+                    * words
+                        .iter()
+                        .map(|w| match_quality(term, w))
+                        .fold(0.0, f64::max)
+            })
+            .fold(0.0, f64::max);
+
+            if score <= 0.0 {
+                return None;
+            }
+            total += score;
+        }
+
+        for phrase in &self.phrases {
+            let score = if addon.name.to_lowercase().contains(phrase.as_str()) {
+                NAME_WEIGHT
+            } else if addon
+                .categories
+                .iter()
+                .any(|c| c.to_lowercase().contains(phrase.as_str()))
+            {
+                CATEGORY_WEIGHT
+            } else if addon.summary.to_lowercase().contains(phrase.as_str()) {
+                SUMMARY_WEIGHT
+            } else {
+                return None;
+            };
+
+            total += score;
+        }
+
+        let mut relevance = total / (self.terms.len() + self.phrases.len()) as f64;
+
+        // Searching for the name of an addon should find that addon first.
+        if !self.terms.is_empty() {
+            let query = self.terms.concat();
+            let name = name.concat();
+
+            if name == query {
+                relevance += 0.5;
+            } else if name.starts_with(&query) {
+                relevance += 0.25;
+            }
+        }
+
+        Some(relevance)
+    }
+}
+
+/// Returns the addons matching `query`, best match first.
+pub fn search<'a, I>(addons: I, query: &Query) -> Vec<&'a CatalogAddon>
+where
+    I: IntoIterator<Item = &'a CatalogAddon>,
+{
+    let mut results: Vec<_> = addons
+        .into_iter()
+        .filter_map(|a| query.score(a).map(|score| (a, score)))
+        .collect();
+
+    results.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.number_of_downloads.cmp(&a.number_of_downloads))
+    });
+
+    results.into_iter().map(|(a, _)| a).collect()
+}
+
+/// Reads a word up to the next whitespace, or a phrase in quotes. The value
+/// of a filter can also be quoted, e.g. `category:"Bags & Inventory"`.
+///
+/// Returns the token and whether it was a phrase.
+fn read_token(chars: &mut Peekable<Chars>) -> (String, bool) {
+    if chars.peek() == Some(&'"') {
+        chars.next();
+        let phrase = chars.by_ref().take_while(|c| *c != '"').collect();
+
+        return (phrase, true);
+    }
+
+    let mut token = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            break;
+        }
+
+        chars.next();
+        token.push(c);
+
+        if c == ':' && chars.peek() == Some(&'"') {
+            let (value, _) = read_token(chars);
+            token.push_str(&value);
+            break;
+        }
+    }
+
+    (token, false)
+}
+
+/// Splits `text` into lowercase words.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Returns how well the search `term` matches `word`, from 0.0 for no match
+/// to 1.0 for the same word.
+fn match_quality(term: &str, word: &str) -> f64 {
+    if term == word {
+        return 1.0;
+    }
+
+    // The last word is often only partially typed.
+    if word.starts_with(term) {
+        return 0.9;
+    }
+
+    let term_len = term.chars().count();
+    if term_len >= 3 && word.contains(term) {
+        return 0.7;
+    }
+
+    let max_typos = match term_len {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    };
+    if max_typos == 0 {
+        return 0.0;
+    }
+
+    let distance = edit_distance(term, word);
+    if distance <= max_typos {
+        return 0.8 - 0.2 * distance as f64;
+    }
+
+    // A typo in a partially typed word.
+    let prefix: String = word.chars().take(term_len).collect();
+    let distance = edit_distance(term, &prefix);
+    if distance <= max_typos {
+        return 0.7 - 0.2 * distance as f64;
+    }
+
+    0.0
+}
+
+/// Returns the number of insertions, deletions, substitutions and swaps of
+/// adjacent characters needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Returns the popularity of `addon` from 0.0 to 1.0, on a log scale since
+/// downloads range from a handful to hundreds of millions.
+fn popularity(addon: &CatalogAddon) -> f64 {
+    let downloads = (addon.number_of_downloads as f64 + 1.0).log10();
+
+    (downloads / MAX_POPULARITY_DOWNLOADS.log10()).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Flavor;
+
+    fn catalog_addon(
+        name: &str,
+        summary: &str,
+        categories: &[&str],
+        downloads: u64,
+    ) -> CatalogAddon {
+        CatalogAddon {
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            summary: summary.to_owned(),
+            number_of_downloads: downloads,
+            ..CatalogAddon::retail(0, name, Source::Curse)
+        }
+    }
+
+    fn names(addons: Vec<&CatalogAddon>) -> Vec<&str> {
+        addons.iter().map(|a| a.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = Query::parse(
+            r#"deadly  "boss mods" category:"Boss Encounters" -source:tukui -beta flavor:classic foo:bar"#,
+        );
+
+        assert_eq!(query.terms, vec!["deadly", "foo", "bar"]);
+        assert_eq!(query.phrases, vec!["boss mods"]);
+        assert_eq!(query.excluded, vec!["beta"]);
+        assert_eq!(
+            query.filters,
+            vec![
+                Filter {
+                    field: FilterField::Category,
+                    value: "bossencounters".to_owned(),
+                    negated: false,
+                },
+                Filter {
+                    field: FilterField::Source,
+                    value: "tukui".to_owned(),
+                    negated: true,
+                },
+                Filter {
+                    field: FilterField::Flavor,
+                    value: "classic".to_owned(),
+                    negated: false,
+                },
+            ]
+        );
+
+        assert!(Query::parse("  - \"\" ").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("details", "details"), 0);
+        assert_eq!(edit_distance("detials", "details"), 1);
+        assert_eq!(edit_distance("detail", "details"), 1);
+        assert_eq!(edit_distance("bagnon", "baganator"), 4);
+    }
+
+    #[test]
+    fn test_typo_tolerance() {
+        let addons = vec![
+            catalog_addon("Details! Damage Meter", "", &["Combat"], 100),
+            catalog_addon(
+                "Bagnon",
+                "Single window inventory",
+                &["Bags & Inventory"],
+                100,
+            ),
+        ];
+
+        assert_eq!(
+            names(search(&addons, &Query::parse("detials"))),
+            vec!["Details! Damage Meter"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("damge meter"))),
+            vec!["Details! Damage Meter"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("inventroy"))),
+            vec!["Bagnon"]
+        );
+        assert!(search(&addons, &Query::parse("xy")).is_empty());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let addons = vec![
+            catalog_addon("Bagnon Void Storage", "", &["Bags & Inventory"], 1_000_000),
+            catalog_addon("Bagnon", "", &["Bags & Inventory"], 50_000_000),
+            catalog_addon("AdiBags", "Like Bagnon, but with filters", &[], 10_000_000),
+            catalog_addon("Bagnon Guild Bank", "", &[], 10),
+        ];
+
+        // An exact name match comes first, while popularity ranks the rest.
+        assert_eq!(
+            names(search(&addons, &Query::parse("bagnon"))),
+            vec![
+                "Bagnon",
+                "Bagnon Void Storage",
+                "Bagnon Guild Bank",
+                "AdiBags"
+            ]
+        );
+
+        // Without words, the most downloaded addons come first.
+        assert_eq!(
+            names(search(&addons, &Query::parse("category:bags"))),
+            vec!["Bagnon", "Bagnon Void Storage"]
+        );
+    }
+
+    #[test]
+    fn test_filters_and_exclusions() {
+        let mut classic = catalog_addon("Questie", "Quest helper", &["Quests & Leveling"], 10);
+        classic.flavors = vec![Flavor::Classic];
+        let mut wowi = catalog_addon("Quest Completist", "", &["Quests & Leveling"], 10);
+        wowi.source = Source::WowI;
+        let addons = vec![classic, wowi];
+
+        assert_eq!(
+            names(search(&addons, &Query::parse("quest flavor:classic"))),
+            vec!["Questie"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("quest source:wowi"))),
+            vec!["Quest Completist"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("quest -source:wowi"))),
+            vec!["Questie"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("quest -helper"))),
+            vec!["Quest Completist"]
+        );
+        assert_eq!(
+            names(search(&addons, &Query::parse("\"quest helper\""))),
+            vec!["Questie"]
+        );
+        assert_eq!(
+            Query::parse("source:wowi").preferred_source(&addons[1]),
+            Some(Source::WowI)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+lazy_static::lazy_static! {
+    static ref RE_TOC: Regex = Regex::new(r"^##\s*(?P<key>.*?)\s*:\s?(?P<value>.*)").unwrap();
+    static ref RE_TITLE: Regex = Regex::new(r"\|[a-fA-F\d]{9}([^|]+)\|r?").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultState {
+    Enabled,
+    Disabled,
+}
+
+impl Default for DefaultState {
+    fn default() -> DefaultState {
+        DefaultState::Enabled
+    }
+}
+
+/// Struct which stores everything parsed from a `.toc` file.
+///
+/// TOC format summary:
+/// https://wowwiki.fandom.com/wiki/TOC_format
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Toc {
+    /// The game build the addon targets, e.g. `90001`.
+    pub interface: Option<u32>,
+    pub title: Option<String>,
+    /// Titles keyed by locale, e.g. `deDE`.
+    pub localized_titles: HashMap<String, String>,
+    pub notes: Option<String>,
+    /// Notes keyed by locale, e.g. `deDE`.
+    pub localized_notes: HashMap<String, String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    /// Addons which must be loaded before this addon can be loaded.
+    pub dependencies: Vec<String>,
+    /// Addons which should be loaded before this addon, if they are installed.
+    pub optional_dependencies: Vec<String>,
+    pub load_on_demand: bool,
+    pub saved_variables: Vec<String>,
+    pub saved_variables_per_character: Vec<String>,
+    pub default_state: DefaultState,
+    pub website: Option<String>,
+    pub category: Option<String>,
+    pub wowi_id: Option<String>,
+    pub tukui_id: Option<String>,
+    pub curse_id: Option<u32>,
+    /// Files loaded by the addon, as written in the `.toc` file.
+    pub files: Vec<String>,
+}
+
+impl Toc {
+    /// Reads and parses the `.toc` file at `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Toc> {
+        let bytes = std::fs::read(path.as_ref())?;
+
+        Ok(Toc::parse(&decode(&bytes)))
+    }
+
+    /// Parses the content of a `.toc` file.
+    pub fn parse(content: &str) -> Toc {
+        let mut toc = Toc::default();
+
+        for line in content.lines() {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let cap = match RE_TOC.captures(line) {
+                Some(cap) => cap,
+                None => {
+                    // Lines starting with a single `#` are comments, everything else is a file.
+                    if !line.starts_with('#') {
+                        toc.files.push(line.to_owned());
+                    }
+
+                    continue;
+                }
+            };
+
+            let value = cap["value"].trim();
+
+            match &cap["key"] {
+                "Interface" => toc.interface = value.parse().ok(),
+                // Note: Coloring is possible via UI escape sequences.
+                // Since we don't want any color modifications, we will trim it away.
+                "Title" => toc.title = Some(strip_color(value)),
+                "Notes" => toc.notes = Some(strip_color(value)),
+                key if key.starts_with("Title-") => {
+                    toc.localized_titles
+                        .insert(key["Title-".len()..].to_owned(), strip_color(value));
+                }
+                key if key.starts_with("Notes-") => {
+                    toc.localized_notes
+                        .insert(key["Notes-".len()..].to_owned(), strip_color(value));
+                }
+                "Author" => toc.author = Some(value.to_owned()),
+                "Version" => toc.version = Some(value.to_owned()),
+                "OptionalDeps" | "OptDeps" => {
+                    toc.optional_dependencies.append(&mut split_list(value));
+                }
+                // `Dependencies`, `RequiredDeps` or anything else starting with `Dep`.
+                key if key == "RequiredDeps" || key.starts_with("Dep") => {
+                    toc.dependencies.append(&mut split_list(value));
+                }
+                "LoadOnDemand" => toc.load_on_demand = value == "1",
+                "SavedVariables" => toc.saved_variables.append(&mut split_list(value)),
+                "SavedVariablesPerCharacter" => {
+                    toc.saved_variables_per_character
+                        .append(&mut split_list(value));
+                }
+                "DefaultState" => {
+                    toc.default_state = if value.eq_ignore_ascii_case("disabled") {
+                        DefaultState::Disabled
+                    } else {
+                        DefaultState::Enabled
+                    }
+                }
+                "X-Website" => toc.website = Some(value.to_owned()),
+                "X-Category" => toc.category = Some(value.to_owned()),
+                "X-Tukui-ProjectID" => toc.tukui_id = Some(value.to_owned()),
+                "X-WoWI-ID" => toc.wowi_id = Some(value.to_owned()),
+                "X-Curse-Project-ID" => toc.curse_id = value.parse().ok(),
+                _ => (),
+            }
+        }
+
+        toc
+    }
+
+    /// Returns the title for `locale`, falling back to the default title.
+    pub fn localized_title(&self, locale: &str) -> Option<&str> {
+        self.localized_titles
+            .get(locale)
+            .or_else(|| self.title.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Returns the notes for `locale`, falling back to the default notes.
+    pub fn localized_notes(&self, locale: &str) -> Option<&str> {
+        self.localized_notes
+            .get(locale)
+            .or_else(|| self.notes.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Decodes a `.toc` file, which can be saved as UTF-8 or UTF-16 with or without a BOM.
+fn decode(bytes: &[u8]) -> String {
+    let decode_utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+
+        String::from_utf16_lossy(&units)
+    };
+
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Removes UI escape sequences used for coloring.
+fn strip_color(value: &str) -> String {
+    RE_TITLE.replace_all(value, "$1").trim().to_string()
+}
+
+/// Helper function to split a comma separated string into `Vec<String>`.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOC: &str = "## Interface: 90001
+## Title: |cff1784d1Example|r Addon
+## Title-deDE: Beispiel
+## Notes: Does things
+## Notes-deDE: Macht Dinge
+## Author: Someone
+## Version: 1.2.3
+## Dependencies: Foo, Bar
+## RequiredDeps: Baz
+## OptionalDeps: Ace3, LibStub
+## LoadOnDemand: 1
+## SavedVariables: ExampleDB, ExampleGlobal
+## SavedVariablesPerCharacter: ExampleCharDB
+## DefaultState: disabled
+## X-Website: https://example.com
+## X-Category: Bags
+## X-Curse-Project-ID: 1234
+
+# A comment
+embeds.xml
+Core.lua
+";
+
+    #[test]
+    fn test_parse_toc() {
+        let toc = Toc::parse(TOC);
+
+        assert_eq!(toc.interface, Some(90001));
+        assert_eq!(toc.title.as_deref(), Some("Example Addon"));
+        assert_eq!(toc.localized_title("deDE"), Some("Beispiel"));
+        assert_eq!(toc.localized_title("frFR"), Some("Example Addon"));
+        assert_eq!(toc.localized_notes("deDE"), Some("Macht Dinge"));
+        assert_eq!(toc.author.as_deref(), Some("Someone"));
+        assert_eq!(toc.version.as_deref(), Some("1.2.3"));
+        assert_eq!(toc.dependencies, vec!["Foo", "Bar", "Baz"]);
+        assert_eq!(toc.optional_dependencies, vec!["Ace3", "LibStub"]);
+        assert!(toc.load_on_demand);
+        assert_eq!(toc.saved_variables, vec!["ExampleDB", "ExampleGlobal"]);
+        assert_eq!(toc.saved_variables_per_character, vec!["ExampleCharDB"]);
+        assert_eq!(toc.default_state, DefaultState::Disabled);
+        assert_eq!(toc.website.as_deref(), Some("https://example.com"));
+        assert_eq!(toc.category.as_deref(), Some("Bags"));
+        assert_eq!(toc.curse_id, Some(1234));
+        assert_eq!(toc.files, vec!["embeds.xml", "Core.lua"]);
+    }
+
+    #[test]
+    fn test_decode_bom() {
+        let content = "## Interface: 11305\r\n## Title: Classic\r\n";
+
+        let mut utf8 = vec![0xEF, 0xBB, 0xBF];
+        utf8.extend(content.as_bytes());
+
+        let mut utf16_le = vec![0xFF, 0xFE];
+        let mut utf16_be = vec![0xFE, 0xFF];
+        for unit in content.encode_utf16() {
+            utf16_le.extend(&unit.to_le_bytes());
+            utf16_be.extend(&unit.to_be_bytes());
+        }
+
+        for bytes in [utf8, utf16_le, utf16_be].iter() {
+            let toc = Toc::parse(&decode(bytes));
+
+            assert_eq!(toc.interface, Some(11305));
+            assert_eq!(toc.title.as_deref(), Some("Classic"));
+        }
+    }
+}
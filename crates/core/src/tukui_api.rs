@@ -1,5 +1,11 @@
-use crate::{addon::Addon, config::Flavor, error::ClientError, network::request_async, Result};
-use isahc::config::RedirectPolicy;
+use crate::{
+    addon::Addon,
+    config::{Endpoints, Flavor},
+    error::ClientError,
+    network::request_async,
+    Result,
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use isahc::prelude::*;
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -15,31 +21,48 @@ pub struct TukuiPackage {
     pub patch: Option<String>,
     pub author: Option<String>,
     pub small_desc: Option<String>,
+    pub screenshot_url: Option<String>,
+    /// Url of the changelog page.
+    pub changelog: Option<String>,
+}
+
+impl TukuiPackage {
+    /// Returns the time of the last update, which is sent with or without a time.
+    pub fn date_time(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.lastupdate, "%Y-%m-%d")
+            .map_or(
+                NaiveDateTime::parse_from_str(&self.lastupdate, "%Y-%m-%d %H:%M:%S"),
+                Result::Ok,
+            )
+            .map(|d| Utc.from_utc_datetime(&d))
+            .ok()
+    }
 }
 
 /// Return the tukui API endpoint.
-fn api_endpoint(id: &str, flavor: &Flavor) -> String {
+fn api_endpoint(endpoints: &Endpoints, id: &str, flavor: &Flavor) -> String {
+    let api = endpoints.tukui();
+
     match flavor {
         Flavor::Retail => match id {
-            "-1" => "https://www.tukui.org/api.php?ui=tukui".to_owned(),
-            "-2" => "https://www.tukui.org/api.php?ui=elvui".to_owned(),
-            _ => format!("https://www.tukui.org/api.php?addon={}", id),
+            "-1" => format!("{}?ui=tukui", api),
+            "-2" => format!("{}?ui=elvui", api),
+            _ => format!("{}?addon={}", api, id),
         },
-        Flavor::Classic => format!("https://www.tukui.org/api.php?classic-addon={}", id),
+        Flavor::Classic => format!("{}?classic-addon={}", api, id),
     }
 }
 
 /// Function to fetch a remote addon package which contains
 /// information about the addon on the repository.
-pub async fn fetch_remote_package(id: &str, flavor: &Flavor) -> Result<TukuiPackage> {
-    let client = HttpClient::builder()
-        .redirect_policy(RedirectPolicy::Follow)
-        .max_connections_per_host(6)
-        .build()
-        .unwrap();
-    let url = api_endpoint(id, flavor);
+pub async fn fetch_remote_package(
+    endpoints: &Endpoints,
+    id: &str,
+    flavor: &Flavor,
+) -> Result<TukuiPackage> {
+    let url = api_endpoint(endpoints, id, flavor);
     let timeout = Some(30);
-    let mut resp = request_async(&client, &url, vec![], timeout).await?;
+    let mut resp = request_async(&url, vec![], timeout).await?;
 
     if resp.status().is_success() {
         let package = resp.json()?;
@@ -52,7 +75,26 @@ pub async fn fetch_remote_package(id: &str, flavor: &Flavor) -> Result<TukuiPack
     }
 }
 
+/// Function to fetch the changelog page of an addon, which is HTML.
+pub async fn fetch_changelog(package: &TukuiPackage) -> Result<String> {
+    let url = package
+        .changelog
+        .as_deref()
+        .ok_or_else(|| ClientError::Custom(format!("{} has no changelog", package.name)))?;
+    let mut resp = request_async(url, vec![], None).await?;
+
+    if resp.status().is_success() {
+        Ok(resp.text()?)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch changelog for addon. Server returned: {}",
+            resp.text()?
+        )))
+    }
+}
+
 pub async fn latest_stable_addon_from_id(
+    endpoints: &Endpoints,
     tukui_id: u32,
     mut addon: Addon,
     mut addon_path: PathBuf,
@@ -60,7 +102,7 @@ pub async fn latest_stable_addon_from_id(
 ) -> Result<(u32, Flavor, Addon)> {
     let tukui_id_string = tukui_id.to_string();
 
-    let package = fetch_remote_package(&tukui_id_string, &flavor).await?;
+    let package = fetch_remote_package(endpoints, &tukui_id_string, &flavor).await?;
 
     addon_path.push(&package.name);
 
@@ -1,9 +1,22 @@
-use crate::{network::request_async, Result};
+use crate::{
+    config::{Endpoints, Flavor},
+    network::request_async,
+    Result,
+};
 use isahc::prelude::*;
 use regex::Regex;
 use serde::Deserialize;
 use std::ffi::OsStr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+lazy_static::lazy_static! {
+    static ref RE_WHITESPACE: Regex = Regex::new(r"\s+").unwrap();
+    static ref RE_BLOCK_END: Regex = Regex::new(r"(?i)</(p|div|h[1-6]|ul|ol|pre|table)>").unwrap();
+    static ref RE_LINE_BREAK: Regex = Regex::new(r"(?i)<br\s*/?>|</tr>").unwrap();
+    static ref RE_LIST_ITEM: Regex = Regex::new(r"(?i)<li(\s[^>]*)?>").unwrap();
+    static ref RE_TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+    static ref RE_ENTITY: Regex = Regex::new(r"&(#x?[0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+}
 
 /// Takes a `&str` and strips any non-digit.
 /// This is used to unify and compare addon versions:
@@ -21,18 +34,11 @@ struct Release {
     tag_name: String,
 }
 
-pub async fn needs_update(current_version: &str) -> Result<Option<String>> {
+pub async fn needs_update(endpoints: &Endpoints, current_version: &str) -> Result<Option<String>> {
     log::debug!("checking for application update");
 
-    let client = HttpClient::new()?;
-
-    let mut resp = request_async(
-        &client,
-        "https://api.github.com/repos/casperstorm/ajour/releases/latest",
-        vec![],
-        None,
-    )
-    .await?;
+    let url = format!("{}/releases/latest", endpoints.github());
+    let mut resp = request_async(url, vec![], None).await?;
 
     let release: Release = resp.json()?;
 
@@ -71,10 +77,123 @@ pub fn wow_path_resolution(path: Option<PathBuf>) -> Option<PathBuf> {
     None
 }
 
+/// Returns the interface version of the installed game client for `flavor`,
+/// e.g. `90002` for 9.0.2. Addons with a lower `## Interface` are out of date.
+///
+/// The version is read from the `.build.info` file in the World of Warcraft directory.
+pub fn game_interface(wow_directory: &Path, flavor: Flavor) -> Option<u32> {
+    let build_info = std::fs::read_to_string(wow_directory.join(".build.info")).ok()?;
+    let product = match flavor {
+        Flavor::Retail => "wow",
+        Flavor::Classic => "wow_classic",
+    };
+
+    let version = build_info_version(&build_info, product)?;
+
+    interface_from_version(&version)
+}
+
+/// Finds the client version for `product` in the content of a `.build.info` file.
+///
+/// The file is a `|` separated table, where the header names are suffixed
+/// with their type, e.g. `Version!STRING:0`.
+fn build_info_version(build_info: &str, product: &str) -> Option<String> {
+    let mut lines = build_info.lines();
+    let header: Vec<_> = lines
+        .next()?
+        .split('|')
+        .map(|column| column.split('!').next().unwrap_or_default())
+        .collect();
+
+    let version_idx = header.iter().position(|c| *c == "Version")?;
+    let product_idx = header.iter().position(|c| *c == "Product")?;
+    let active_idx = header.iter().position(|c| *c == "Active");
+
+    lines
+        .map(|line| line.split('|').collect::<Vec<_>>())
+        .filter(|row| row.get(product_idx) == Some(&product))
+        .filter(|row| active_idx.map_or(true, |idx| row.get(idx) != Some(&"0")))
+        .find_map(|row| row.get(version_idx).map(|v| v.to_string()))
+}
+
+/// Converts a client version, e.g. `9.0.2.36949`, into an interface version, e.g. `90002`.
+fn interface_from_version(version: &str) -> Option<u32> {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next()??;
+
+    Some(major * 10000 + minor * 100 + patch)
+}
+
+/// Normalizes an addon name so the same addon is found across sources,
+/// e.g. `Deadly Boss Mods (DBM)` and `Deadly Boss Mods(DBM)`.
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Converts HTML, e.g. an addon description or changelog, into plain text.
+///
+/// Block elements and line breaks become new lines, list items are prefixed
+/// with a dash and every other tag is removed.
+pub fn html_to_text(html: &str) -> String {
+    // Line breaks in HTML are only whitespace, the markup decides the layout.
+    let text = RE_WHITESPACE.replace_all(html, " ");
+    let text = RE_BLOCK_END.replace_all(&text, "\n\n");
+    let text = RE_LINE_BREAK.replace_all(&text, "\n");
+    let text = RE_LIST_ITEM.replace_all(&text, "\n- ");
+    let text = RE_TAG.replace_all(&text, "");
+    let text = RE_ENTITY.replace_all(&text, |caps: &regex::Captures| {
+        let entity = &caps[1];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+            }
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(std::char::from_u32),
+            _ => None,
+        };
+
+        decoded.map_or_else(|| caps[0].to_string(), |c| c.to_string())
+    });
+
+    // Collapse the whitespace left behind by the markup, keeping at most one
+    // empty line between paragraphs.
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let is_blank = line.is_empty();
+        let last_is_blank = lines.last().map_or(true, |l| l.is_empty());
+        if !(is_blank && last_is_blank) {
+            lines.push(line);
+        }
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Ace3"), normalize_name("ace-3"));
+        assert_eq!(normalize_name("Deadly Boss Mods"), "deadlybossmods");
+    }
+
     #[test]
     fn test_wow_path_resolution() {
         let classic_addon_path =
@@ -111,4 +230,31 @@ mod tests {
             true
         );
     }
+
+    #[test]
+    fn test_game_interface_from_build_info() {
+        let build_info =
+            "Branch!STRING:0|Active!DEC:1|Build Key!HEX:16|Version!STRING:0|Product!STRING:0
+eu|1|a1b2c3|9.0.2.36949|wow
+eu|1|d4e5f6|1.13.6.37497|wow_classic
+eu|0|a7b8c9|9.0.5.37503|wow_beta";
+
+        let retail = build_info_version(build_info, "wow").unwrap();
+        let classic = build_info_version(build_info, "wow_classic").unwrap();
+
+        assert_eq!(interface_from_version(&retail), Some(90002));
+        assert_eq!(interface_from_version(&classic), Some(11306));
+        assert_eq!(build_info_version(build_info, "wow_beta"), None);
+        assert_eq!(build_info_version(build_info, "wow_ptr"), None);
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<h2>Changes</h2>\n<ul>\n<li>Fixed <b>bags</b> &amp; banks</li>\n<li>Works with 9.0.2&nbsp;&#40;Shadowlands&#x29;</li>\n</ul>\n\n\n<p>Thanks<br/>everyone &lt;3</p>";
+
+        assert_eq!(
+            html_to_text(html),
+            "Changes\n\n- Fixed bags & banks\n- Works with 9.0.2 (Shadowlands)\n\nThanks\neveryone <3"
+        );
+    }
 }
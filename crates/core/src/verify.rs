@@ -0,0 +1,202 @@
+use crate::{
+    addon::{Addon, RemotePackage},
+    config::Endpoints,
+    curse_api::fetch_remote_file,
+    parse::{FileFingerprint, Fingerprinter},
+    Result,
+};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Extensions of the files which can be part of a fingerprint. Media such as
+/// textures and sounds are loaded by the code at runtime and never referenced
+/// by includes, so they are not reported as extra files.
+const CODE_EXTENSIONS: [&str; 3] = ["lua", "xml", "toc"];
+
+/// Result of verifying the local files of a single `Addon` against the
+/// remote file it was installed from.
+#[derive(Debug, Clone)]
+pub struct AddonIntegrity {
+    pub addon_id: String,
+    /// Module folders where the local fingerprint differs from the remote.
+    /// Referenced files which were changed or removed are caught here.
+    pub modified: Vec<String>,
+    /// Module folders which doesn't exist locally.
+    pub missing: Vec<String>,
+    /// Code files, relative to the AddOns folder, which no `.toc` or `.xml`
+    /// file references. These are never hashed, so they don't change the
+    /// fingerprint, but they aren't part of the remote file either.
+    pub extra: Vec<String>,
+    /// The remote file the addon was installed from.
+    pub package: RemotePackage,
+}
+
+impl AddonIntegrity {
+    /// Function returns a `bool` indicating if all modules matches the remote file.
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+
+    /// Returns every module folder which needs to be restored.
+    pub fn damaged_modules(&self) -> Vec<String> {
+        [&self.modified[..], &self.missing[..]].concat()
+    }
+}
+
+/// Recomputes the fingerprint of every module for each addon and compares
+/// them against the module fingerprints of the installed remote file.
+///
+/// Cached fingerprints are not used, since the point is to catch changes on disk.
+/// Addons without a `curse_id` and `file_id` can't be verified and are skipped.
+pub async fn verify_addons<P: AsRef<Path>>(
+    endpoints: &Endpoints,
+    addon_directory: P,
+    addons: &[Addon],
+) -> Result<Vec<AddonIntegrity>> {
+    let addon_directory = addon_directory.as_ref();
+
+    let fingerprinter = Fingerprinter::load(endpoints).await?;
+
+    let mut results = vec![];
+
+    for addon in addons {
+        let (curse_id, file_id) = match (addon.curse_id, addon.file_id) {
+            (Some(curse_id), Some(file_id)) => (curse_id, file_id),
+            _ => continue,
+        };
+
+        let file = match fetch_remote_file(endpoints, curse_id, file_id).await {
+            Ok(file) => file,
+            Err(e) => {
+                log::error!("couldn't fetch remote file for {}: {}", &addon.id, e);
+                continue;
+            }
+        };
+
+        let mut missing = vec![];
+        let mut to_verify = vec![];
+        for module in file.modules.iter() {
+            let module_dir = addon_directory.join(&module.foldername);
+
+            if module_dir.is_dir() {
+                to_verify.push((module, module_dir));
+            } else {
+                missing.push(module.foldername.clone());
+            }
+        }
+
+        let verified: Vec<_> = to_verify
+            .par_iter()
+            .map(
+                |(module, module_dir)| match fingerprinter.fingerprint_cached(module_dir, &[]) {
+                    Ok((hash, files)) => {
+                        let extra = extra_files(addon_directory, module_dir, &files);
+
+                        (hash != module.fingerprint, extra)
+                    }
+                    Err(e) => {
+                        log::error!("fingerprinting failed for {:?}: {}", module_dir, e);
+                        (true, vec![])
+                    }
+                },
+            )
+            .collect();
+
+        let mut modified = vec![];
+        let mut extra = vec![];
+        for ((module, _), (is_modified, module_extra)) in to_verify.iter().zip(verified) {
+            if is_modified {
+                modified.push(module.foldername.clone());
+            }
+
+            extra.extend(module_extra);
+        }
+
+        results.push(AddonIntegrity {
+            addon_id: addon.id.clone(),
+            modified,
+            missing,
+            extra,
+            package: RemotePackage::from(&file),
+        });
+    }
+
+    log::debug!(
+        "{} addons verified, {} with integrity issues",
+        results.len(),
+        results.iter().filter(|r| !r.is_intact()).count()
+    );
+
+    Ok(results)
+}
+
+/// Returns the code files in `module_dir` which weren't visited while
+/// fingerprinting it, relative to `addon_directory`.
+fn extra_files(
+    addon_directory: &Path,
+    module_dir: &Path,
+    visited: &[FileFingerprint],
+) -> Vec<String> {
+    let visited: HashSet<_> = visited.iter().map(|file| file.path.as_path()).collect();
+
+    let mut extra: Vec<_> = WalkDir::new(module_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| CODE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+                .unwrap_or_default()
+        })
+        .filter_map(|entry| {
+            let path = entry.path().strip_prefix(addon_directory).ok()?;
+
+            if visited.contains(path) {
+                None
+            } else {
+                Some(path.to_string_lossy().replace("\\", "/"))
+            }
+        })
+        .collect();
+
+    extra.sort();
+    extra
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_files() {
+        let addon_directory =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fingerprint");
+        let fingerprinter = Fingerprinter::curse_defaults();
+
+        // Stray.lua isn't in the toc, and Readme.txt isn't a code file.
+        let module_dir = addon_directory.join("Simple");
+        let (_, files) = fingerprinter.fingerprint_cached(&module_dir, &[]).unwrap();
+        assert_eq!(
+            extra_files(&addon_directory, &module_dir, &files),
+            vec!["Simple/Stray.lua".to_owned()]
+        );
+
+        // Commented out in the toc and in an xml file.
+        let module_dir = addon_directory.join("Chain");
+        let (_, files) = fingerprinter.fingerprint_cached(&module_dir, &[]).unwrap();
+        assert_eq!(
+            extra_files(&addon_directory, &module_dir, &files),
+            vec!["Chain/Old.lua".to_owned(), "Chain/Unused.lua".to_owned()]
+        );
+
+        // Every code file is referenced.
+        let module_dir = addon_directory.join("Whitespace");
+        let (_, files) = fingerprinter.fingerprint_cached(&module_dir, &[]).unwrap();
+        assert!(extra_files(&addon_directory, &module_dir, &files).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    addon::Addon,
+    config::{Endpoints, Flavor},
+    error::ClientError,
+    network::request_async,
+    Result,
+};
+use isahc::prelude::*;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Deserialize)]
+/// Struct for applying wowi details to an `Addon`.
+pub struct WowIPackage {
+    #[serde(rename = "UID")]
+    pub id: String,
+    #[serde(rename = "UIName")]
+    pub name: String,
+    #[serde(rename = "UIVersion")]
+    pub version: String,
+    /// Time of the last update, in milliseconds since the epoch.
+    #[serde(rename = "UIDate")]
+    pub date: i64,
+    #[serde(rename = "UIDownload")]
+    pub download_url: String,
+    #[serde(rename = "UIAuthorName")]
+    pub author: Option<String>,
+    #[serde(rename = "UIDescription")]
+    pub description: Option<String>,
+    #[serde(rename = "UIChangeLog")]
+    pub changelog: Option<String>,
+    /// Urls of the screenshots.
+    #[serde(rename = "UIIMGs")]
+    pub images: Option<Vec<String>>,
+}
+
+impl WowIPackage {
+    pub fn website_url(&self) -> String {
+        website_url(&self.id)
+    }
+}
+
+/// Returns the url of the addon page on WoWInterface.
+pub fn website_url(id: &str) -> String {
+    format!("https://www.wowinterface.com/downloads/info{}", id)
+}
+
+/// Function to fetch the remote packages for `ids`, which contains
+/// information about each addon on the repository.
+pub async fn fetch_remote_packages(
+    endpoints: &Endpoints,
+    ids: &[String],
+) -> Result<Vec<WowIPackage>> {
+    let url = format!("{}/filedetails/{}.json", endpoints.wowi(), ids.join(","));
+    let timeout = Some(30);
+    let mut resp = request_async(&url, vec![], timeout).await?;
+
+    if resp.status().is_success() {
+        let packages = resp.json()?;
+        Ok(packages)
+    } else {
+        Err(ClientError::Custom(format!(
+            "Couldn't fetch details for addon. Server returned: {}",
+            resp.text()?
+        )))
+    }
+}
+
+pub async fn latest_stable_addon_from_id(
+    endpoints: &Endpoints,
+    wowi_id: u32,
+    mut addon: Addon,
+    mut addon_path: PathBuf,
+    flavor: Flavor,
+) -> Result<(u32, Flavor, Addon)> {
+    let wowi_id_string = wowi_id.to_string();
+
+    let package = fetch_remote_packages(endpoints, &[wowi_id_string.clone()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            ClientError::Custom(format!("Couldn't find addon {} on WoWInterface", wowi_id))
+        })?;
+
+    addon_path.push(&package.name);
+
+    addon.title = package.name.clone();
+    addon.id = package.name.clone();
+    addon.author = package.author.clone();
+    addon.notes = package.description.clone();
+    addon.wowi_id = Some(wowi_id_string);
+    addon.path = addon_path;
+
+    addon.apply_wowi_package(&package);
+
+    Ok((wowi_id, flavor, addon))
+}
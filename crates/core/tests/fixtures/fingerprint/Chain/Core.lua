@@ -0,0 +1,2 @@
+local Chain = {}
+Chain.db = ChainDB
@@ -0,0 +1 @@
+LibStub = LibStub or {}
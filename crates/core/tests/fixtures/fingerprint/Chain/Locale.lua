@@ -0,0 +1,2 @@
+local L = {}
+L["Hello"] = "Hello"
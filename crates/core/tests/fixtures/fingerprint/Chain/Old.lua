@@ -0,0 +1 @@
+error("old file")
@@ -0,0 +1 @@
+error("should never be loaded")
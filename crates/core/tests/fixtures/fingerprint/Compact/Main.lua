@@ -0,0 +1,3 @@
+localfunctionmain()
+return1
+end
\ No newline at end of file
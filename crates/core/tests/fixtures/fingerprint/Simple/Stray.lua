@@ -0,0 +1 @@
+error("left behind by an older version")
@@ -0,0 +1,320 @@
+use crate::daemon::{Daemon, DaemonRequest};
+
+use ajour_core::Result;
+
+use async_std::io::{self, BufReader};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::sync::Arc;
+use async_std::task;
+
+use serde_json::{json, Value};
+
+use std::time::Duration;
+
+/// Requests must be sent within this time, or the connection is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request line and headers accepted. Requests have no body.
+const MAX_HEAD_SIZE: usize = 8 * 1024;
+
+/// Serves the API on `127.0.0.1:port`, so only local clients can reach it.
+/// Every request, except CORS preflights, must have an
+/// `Authorization: Bearer <token>` header.
+///
+/// - `GET /status` - the daemon status, as written to the status file
+/// - `GET /addons` - the installed addons found by the last check
+/// - `GET /progress` - the state and download progress of addons being updated
+/// - `POST /refresh` - check for updates, installing the auto updates
+/// - `POST /update` - check for updates, installing every update
+/// - `POST /backup` - backup the AddOns and WTF folders
+pub async fn serve(daemon: Arc<Daemon>, port: u16, token: String) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Couldn't start the API on port {}: {}", port, e);
+            return;
+        }
+    };
+
+    log::info!("API listening on http://127.0.0.1:{}", port);
+
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::error!("API connection failed: {}", e);
+                continue;
+            }
+        };
+
+        let (daemon, token) = (daemon.clone(), token.clone());
+        task::spawn(async move {
+            if let Err(e) = handle_connection(stream, &daemon, &token).await {
+                log::debug!("API request failed: {}", e);
+            }
+        });
+    }
+}
+
+/// The parts of a request the API looks at.
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+}
+
+async fn handle_connection(mut stream: TcpStream, daemon: &Daemon, token: &str) -> Result<()> {
+    let head = io::timeout(READ_TIMEOUT, read_head(&stream)).await?;
+
+    let (status, body) = match parse_request(&head) {
+        None => (400, json!({ "error": "Bad request" })),
+        Some(request) if request.method == "OPTIONS" => (204, Value::Null),
+        Some(request) if !is_authorized(request.authorization.as_deref(), token) => {
+            (401, json!({ "error": "Missing or invalid token" }))
+        }
+        Some(request) => {
+            log::debug!("API {} {}", request.method, request.path);
+
+            respond(daemon, route(&request.method, &request.path)).await?
+        }
+    };
+
+    write_response(&mut stream, status, &body).await
+}
+
+/// Reads the request line and headers, up to the empty line ending them.
+async fn read_head(stream: &TcpStream) -> io::Result<String> {
+    // A single line can't be longer than the limit either, since reading stops
+    // one byte past it.
+    let mut reader = BufReader::new(stream.take(MAX_HEAD_SIZE as u64 + 1));
+    let mut head = String::new();
+
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).await?;
+
+        if read == 0 || line == "\r\n" || line == "\n" {
+            return Ok(head);
+        }
+
+        head.push_str(&line);
+
+        if head.len() > MAX_HEAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request headers too large",
+            ));
+        }
+    }
+}
+
+fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_owned();
+    let target = request_line.next()?;
+    request_line.next().filter(|v| v.starts_with("HTTP/"))?;
+
+    // The query string isn't used.
+    let path = target.split('?').next().unwrap_or_default().to_owned();
+
+    let authorization = lines
+        .filter_map(|line| {
+            let idx = line.find(':')?;
+            let (name, value) = (&line[..idx], &line[idx + 1..]);
+
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                Some(value.trim().to_owned())
+            } else {
+                None
+            }
+        })
+        .next();
+
+    Some(Request {
+        method,
+        path,
+        authorization,
+    })
+}
+
+/// Compares the bearer token in constant time, so it can't be guessed by timing.
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let given = match authorization.and_then(|a| a.strip_prefix("Bearer ")) {
+        Some(given) => given.trim(),
+        None => return false,
+    };
+
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// What a request asks for.
+#[derive(Debug, PartialEq)]
+enum Route {
+    Status,
+    Addons,
+    Progress,
+    Queue(DaemonRequest),
+    MethodNotAllowed,
+    NotFound,
+}
+
+fn route(method: &str, path: &str) -> Route {
+    let request = match path {
+        "/refresh" => Some(DaemonRequest::Refresh),
+        "/update" => Some(DaemonRequest::Update),
+        "/backup" => Some(DaemonRequest::Backup),
+        _ => None,
+    };
+
+    match (method, path, request) {
+        ("GET", "/status", _) => Route::Status,
+        ("GET", "/addons", _) => Route::Addons,
+        ("GET", "/progress", _) => Route::Progress,
+        ("POST", _, Some(request)) => Route::Queue(request),
+        (_, "/status", _) | (_, "/addons", _) | (_, "/progress", _) | (_, _, Some(_)) => {
+            Route::MethodNotAllowed
+        }
+        _ => Route::NotFound,
+    }
+}
+
+async fn respond(daemon: &Daemon, route: Route) -> Result<(u16, Value)> {
+    let response = match route {
+        Route::Status => (200, serde_json::to_value(daemon.status().await)?),
+        Route::Addons => (200, serde_json::to_value(daemon.addons().await)?),
+        Route::Progress => {
+            let state = daemon.status().await.state;
+            let updating = daemon.progress().await;
+
+            (200, json!({ "state": state, "updating": updating }))
+        }
+        Route::Queue(request) => {
+            daemon.request(request).await;
+
+            (202, json!({ "queued": request }))
+        }
+        Route::MethodNotAllowed => (405, json!({ "error": "Method not allowed" })),
+        Route::NotFound => (404, json!({ "error": "Not found" })),
+    };
+
+    Ok(response)
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+
+    let body = if body.is_null() {
+        String::new()
+    } else {
+        serde_json::to_string(body)?
+    };
+
+    // CORS headers let a dashboard in the browser call the API, it still needs the token.
+    let response = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Authorization\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request =
+            parse_request("GET /status?verbose=1 HTTP/1.1\r\nHost: localhost\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/status");
+        assert_eq!(request.authorization, None);
+
+        let request =
+            parse_request("POST /update HTTP/1.1\r\nauthorization:  Bearer secret \r\n").unwrap();
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+
+        let request = parse_request("GET / HTTP/1.0\nAUTHORIZATION: Bearer secret\n").unwrap();
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+
+        assert!(parse_request("").is_none());
+        assert!(parse_request("GET\r\n").is_none());
+        assert!(parse_request("GET /status\r\n").is_none());
+        assert!(parse_request("GET /status FTP/1.1\r\n").is_none());
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(Some("Bearer secret"), "secret"));
+        assert!(is_authorized(Some("Bearer secret "), "secret"));
+
+        assert!(!is_authorized(None, "secret"));
+        assert!(!is_authorized(Some(""), "secret"));
+        assert!(!is_authorized(Some("Bearer "), "secret"));
+        assert!(!is_authorized(Some("Bearer secreT"), "secret"));
+        assert!(!is_authorized(Some("Bearer secre"), "secret"));
+        assert!(!is_authorized(Some("Bearer secrets"), "secret"));
+        assert!(!is_authorized(Some("Basic secret"), "secret"));
+        assert!(!is_authorized(Some("secret"), "secret"));
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET", "/status"), Route::Status);
+        assert_eq!(route("GET", "/addons"), Route::Addons);
+        assert_eq!(route("GET", "/progress"), Route::Progress);
+        assert_eq!(
+            route("POST", "/refresh"),
+            Route::Queue(DaemonRequest::Refresh)
+        );
+        assert_eq!(
+            route("POST", "/update"),
+            Route::Queue(DaemonRequest::Update)
+        );
+        assert_eq!(
+            route("POST", "/backup"),
+            Route::Queue(DaemonRequest::Backup)
+        );
+
+        assert_eq!(route("POST", "/status"), Route::MethodNotAllowed);
+        assert_eq!(route("DELETE", "/addons"), Route::MethodNotAllowed);
+        assert_eq!(route("GET", "/update"), Route::MethodNotAllowed);
+
+        assert_eq!(route("GET", "/"), Route::NotFound);
+        assert_eq!(route("POST", "/install"), Route::NotFound);
+        assert_eq!(route("GET", "/Status"), Route::NotFound);
+    }
+}
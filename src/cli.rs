@@ -71,12 +71,102 @@ pub struct Opts {
     pub data_directory: Option<PathBuf>,
     #[structopt(long = "aa", help = "Enable / Disable Anti-aliasing (true / false)")]
     pub antialiasing: Option<bool>,
+    #[structopt(long = "curse-endpoint", help = "Base url of the Curse API")]
+    pub curse_endpoint: Option<String>,
+    #[structopt(long = "tukui-endpoint", help = "Url of the Tukui API")]
+    pub tukui_endpoint: Option<String>,
+    #[structopt(long = "catalog-endpoint", help = "Url of the addon catalog")]
+    pub catalog_endpoint: Option<String>,
+    #[structopt(
+        long = "github-endpoint",
+        help = "Base url of the GitHub API used to check for new releases"
+    )]
+    pub github_endpoint: Option<String>,
+    #[structopt(long = "wowi-endpoint", help = "Base url of the WoWInterface API")]
+    pub wowi_endpoint: Option<String>,
+    #[structopt(
+        long = "proxy",
+        help = "Proxy url for all requests, e.g. http://proxy.lan:3128 or socks5://proxy.lan:1080"
+    )]
+    pub proxy: Option<String>,
+    #[structopt(
+        long = "proxy-user",
+        help = "Proxy credentials as <username>:<password>"
+    )]
+    pub proxy_user: Option<String>,
+    #[structopt(
+        long = "no-proxy",
+        help = "Comma separated list of hosts which are reached without the proxy",
+        use_delimiter = true
+    )]
+    pub no_proxy: Vec<String>,
+    #[structopt(
+        long = "ca-roots",
+        help = "PEM file with every certificate authority to trust. It replaces the system ones, so it must also hold the public ones for hosts which aren't intercepted"
+    )]
+    pub ca_roots: Option<PathBuf>,
     #[structopt(subcommand)]
     pub command: Option<Command>,
 }
 
 #[derive(Debug, StructOpt)]
 pub enum Command {
+    /// List all installed addons, including which are out of date
+    List,
     /// Update all addons from the command line then exit
-    Update,
+    Update {
+        #[structopt(
+            long,
+            help = "List available updates with their changelog, without updating"
+        )]
+        dry_run: bool,
+    },
+    /// Verify the files of all addons against the remote file they were installed from
+    Verify {
+        #[structopt(long, help = "Reinstall addons with modified or missing files")]
+        repair: bool,
+    },
+    /// Fingerprint all addons from scratch, ignoring any cached fingerprints
+    Rehash,
+    /// Search the catalog, e.g. `ajour search bags category:inventory -source:tukui`
+    Search {
+        #[structopt(
+            help = "Words to search for, \"quoted phrases\", category:, source: and flavor: filters, and -exclusions",
+            required = true,
+            allow_hyphen_values = true
+        )]
+        query: Vec<String>,
+        #[structopt(long, default_value = "20", help = "Maximum number of addons to list")]
+        limit: usize,
+    },
+    /// Keep running and update addons on a schedule until stopped with SIGTERM or Ctrl-C
+    Daemon {
+        #[structopt(
+            long,
+            help = "Minutes between update checks, defaults to `update_check_interval` from the config"
+        )]
+        interval: Option<u64>,
+        #[structopt(
+            long,
+            help = "Path of the JSON status file, defaults to `daemon.json` in the data directory"
+        )]
+        status_file: Option<PathBuf>,
+        #[structopt(
+            long,
+            help = "Update every addon, instead of only those marked as auto update"
+        )]
+        all: bool,
+        #[structopt(
+            long,
+            help = "Serve the control API on this port of 127.0.0.1, requires --api-token"
+        )]
+        api_port: Option<u16>,
+        #[structopt(
+            long,
+            env = "AJOUR_API_TOKEN",
+            hide_env_values = true,
+            help = "Token API clients send as `Authorization: Bearer <token>`"
+        )]
+        api_token: Option<String>,
+    },
 }
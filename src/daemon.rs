@@ -0,0 +1,492 @@
+use crate::api;
+use crate::log_error;
+use crate::update::{apply_updates, pending_updates, read_addons};
+
+use ajour_core::addon::{Addon, ReleaseChannel};
+use ajour_core::backup::{backup_folders, wow_backup_folders};
+use ajour_core::config::{load_config, Flavor, DEFAULT_UPDATE_CHECK_INTERVAL};
+use ajour_core::error::ClientError;
+use ajour_core::fs::config_dir;
+use ajour_core::parse::FingerprintCollection;
+use ajour_core::Result;
+
+use async_std::sync::{Arc, Mutex};
+use async_std::task;
+
+use chrono::{DateTime, Local, TimeZone};
+
+use serde::Serialize;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the daemon wakes up while waiting, to see if it has been asked to
+/// stop or if a request has been queued.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonState {
+    Checking,
+    Updating,
+    BackingUp,
+    Idle,
+    Stopped,
+}
+
+/// Something the daemon has been asked to do through the API.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonRequest {
+    /// Check for updates, installing the auto updates.
+    Refresh,
+    /// Check for updates, installing every update.
+    Update,
+    /// Backup the AddOns and WTF folders.
+    Backup,
+}
+
+/// An addon update, as written to the status file.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddonUpdate {
+    flavor: Flavor,
+    id: String,
+    title: String,
+    version: Option<String>,
+    new_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl AddonUpdate {
+    fn new(flavor: Flavor, addon: &Addon) -> Self {
+        AddonUpdate {
+            flavor,
+            id: addon.id.clone(),
+            title: addon.title.clone(),
+            version: addon.version.clone(),
+            new_version: addon
+                .relevant_release_package()
+                .map(|package| package.version.clone()),
+            error: None,
+        }
+    }
+}
+
+/// An installed addon, as found by the last check.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledAddon {
+    flavor: Flavor,
+    id: String,
+    title: String,
+    version: Option<String>,
+    release_channel: ReleaseChannel,
+    /// The version it can be updated to, if any.
+    update: Option<String>,
+    is_ignored: bool,
+    is_auto_update: bool,
+}
+
+/// Download progress of an addon being updated.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgress {
+    flavor: Flavor,
+    id: String,
+    title: String,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Status of the daemon, written to the status file whenever it changes.
+#[derive(Debug, Clone, Serialize)]
+pub struct DaemonStatus {
+    pub state: DaemonState,
+    pid: u32,
+    started_at: DateTime<Local>,
+    last_check: Option<DateTime<Local>>,
+    next_check: Option<DateTime<Local>>,
+    last_backup: Option<DateTime<Local>>,
+    last_error: Option<String>,
+    /// Updates which aren't installed automatically.
+    available: Vec<AddonUpdate>,
+    /// Updates installed by the last check.
+    updated: Vec<AddonUpdate>,
+    /// Updates which failed to install during the last check.
+    failed: Vec<AddonUpdate>,
+}
+
+impl DaemonStatus {
+    fn new() -> Self {
+        DaemonStatus {
+            state: DaemonState::Idle,
+            pid: std::process::id(),
+            started_at: Local::now(),
+            last_check: None,
+            next_check: None,
+            last_backup: None,
+            last_error: None,
+            available: vec![],
+            updated: vec![],
+            failed: vec![],
+        }
+    }
+}
+
+/// State shared between the daemon and the API.
+pub struct Daemon {
+    status: Mutex<DaemonStatus>,
+    status_file: PathBuf,
+    /// Addons found by the last check.
+    addons: Mutex<Vec<InstalledAddon>>,
+    /// Addons being updated, sharing their download progress with the download.
+    updating: Mutex<Vec<(Flavor, Addon)>>,
+    requests: Mutex<Vec<DaemonRequest>>,
+    stop: AtomicBool,
+}
+
+impl Daemon {
+    fn new(status_file: PathBuf) -> Self {
+        Daemon {
+            status: Mutex::new(DaemonStatus::new()),
+            status_file,
+            addons: Default::default(),
+            updating: Default::default(),
+            requests: Default::default(),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    pub async fn status(&self) -> DaemonStatus {
+        self.status.lock().await.clone()
+    }
+
+    pub async fn addons(&self) -> Vec<InstalledAddon> {
+        self.addons.lock().await.clone()
+    }
+
+    pub async fn progress(&self) -> Vec<UpdateProgress> {
+        self.updating
+            .lock()
+            .await
+            .iter()
+            .map(|(flavor, addon)| UpdateProgress {
+                flavor: *flavor,
+                id: addon.id.clone(),
+                title: addon.title.clone(),
+                downloaded: addon.download_progress.downloaded(),
+                total: addon.download_progress.total(),
+            })
+            .collect()
+    }
+
+    /// Queues `request`, which runs once the daemon is idle.
+    pub async fn request(&self, request: DaemonRequest) {
+        let mut requests = self.requests.lock().await;
+
+        if !requests.contains(&request) {
+            requests.push(request);
+        }
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    async fn set_state(&self, state: DaemonState) {
+        self.status.lock().await.state = state;
+        self.write_status().await;
+    }
+
+    /// Writes the status to a temporary file which then replaces the status
+    /// file, so readers never see a partially written file.
+    async fn write_status(&self) {
+        let status = self.status.lock().await;
+
+        let result = serde_json::to_string_pretty(&*status)
+            .map_err(ClientError::from)
+            .and_then(|json| {
+                let temp_path = self.status_file.with_extension("json.tmp");
+
+                std::fs::write(&temp_path, json)?;
+                std::fs::rename(&temp_path, &self.status_file)?;
+
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            log::error!("Couldn't write daemon status: {}", e);
+        }
+    }
+}
+
+/// Checks for addon updates every `interval` minutes until the process is
+/// asked to stop, installing the updates of addons marked as auto update, or
+/// of every addon if `update_all` is set.
+///
+/// The interval defaults to `update_check_interval` from the config. The
+/// status is written as JSON to `status_file`, `daemon.json` in the data
+/// directory by default. With an `api_port`, the API is served on localhost
+/// for clients presenting `api_token`.
+pub fn run_daemon(
+    interval: Option<u64>,
+    status_file: Option<PathBuf>,
+    update_all: bool,
+    api_port: Option<u16>,
+    api_token: Option<String>,
+) -> Result<()> {
+    if interval == Some(0) {
+        return Err(ClientError::Custom(
+            "The interval must be at least 1 minute".to_string(),
+        ));
+    }
+
+    let api_token = match (api_port, api_token) {
+        (Some(_), Some(token)) if !token.trim().is_empty() => Some(token),
+        (Some(_), _) => {
+            return Err(ClientError::Custom(
+                "The API needs a token, set it with --api-token or AJOUR_API_TOKEN".to_string(),
+            ))
+        }
+        (None, _) => None,
+    };
+
+    let status_file = status_file.unwrap_or_else(|| config_dir().join("daemon.json"));
+    let daemon = Arc::new(Daemon::new(status_file));
+
+    // SIGINT and SIGTERM let the running check finish before exiting.
+    {
+        let daemon = daemon.clone();
+
+        ctrlc::set_handler(move || {
+            log::info!("Stopping daemon...");
+
+            daemon.stop.store(true, Ordering::SeqCst);
+        })
+        .map_err(|e| ClientError::Custom(format!("Couldn't listen for signals: {}", e)))?;
+    }
+
+    log::info!(
+        "Daemon started, writing its status to {}",
+        daemon.status_file.display()
+    );
+
+    task::block_on(async {
+        if let (Some(port), Some(token)) = (api_port, api_token) {
+            task::spawn(api::serve(daemon.clone(), port, token));
+        }
+
+        // Kept between checks, so only changed addons are fingerprinted again.
+        let fingerprint_collection: Arc<Mutex<_>> = Default::default();
+
+        let mut next_check = Instant::now();
+
+        while !daemon.is_stopping() {
+            let mut requests = std::mem::take(&mut *daemon.requests.lock().await);
+            if Instant::now() >= next_check && !requests.contains(&DaemonRequest::Refresh) {
+                requests.insert(0, DaemonRequest::Refresh);
+            }
+
+            if requests.is_empty() {
+                task::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+
+            for request in requests {
+                if daemon.is_stopping() {
+                    break;
+                }
+
+                let result = match request {
+                    DaemonRequest::Refresh => {
+                        check_for_updates(&daemon, fingerprint_collection.clone(), update_all).await
+                    }
+                    DaemonRequest::Update => {
+                        check_for_updates(&daemon, fingerprint_collection.clone(), true).await
+                    }
+                    DaemonRequest::Backup => backup(&daemon).await,
+                };
+
+                if let Err(e) = result {
+                    log_error(&e);
+
+                    daemon.status.lock().await.last_error = Some(e.to_string());
+                }
+
+                // Any check restarts the schedule.
+                if request != DaemonRequest::Backup {
+                    let interval = check_interval(interval).await;
+                    next_check = Instant::now() + interval;
+
+                    daemon.status.lock().await.next_check = chrono::Duration::from_std(interval)
+                        .ok()
+                        .map(|interval| Local::now() + interval);
+                }
+
+                daemon.set_state(DaemonState::Idle).await;
+            }
+        }
+
+        daemon.status.lock().await.next_check = None;
+        daemon.set_state(DaemonState::Stopped).await;
+
+        log::info!("Daemon stopped");
+
+        Result::Ok(())
+    })
+}
+
+/// Time between checks. The config is read again for every check, so changes
+/// made from the GUI apply.
+async fn check_interval(minutes: Option<u64>) -> Duration {
+    match minutes {
+        Some(minutes) => Duration::from_secs(minutes * 60),
+        None => load_config()
+            .await
+            .ok()
+            .and_then(|config| config.update_check_interval())
+            .unwrap_or_else(|| Duration::from_secs(DEFAULT_UPDATE_CHECK_INTERVAL * 60)),
+    }
+}
+
+/// Looks for updates and installs the ones the config allows, or all of them
+/// if `update_all` is set, recording the outcome in the status.
+async fn check_for_updates(
+    daemon: &Daemon,
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    update_all: bool,
+) -> Result<()> {
+    log::info!("Checking for addon updates...");
+
+    daemon.set_state(DaemonState::Checking).await;
+
+    let config = load_config().await?;
+    let endpoints = config.resolved_endpoints();
+
+    let addons_by_flavor = read_addons(&config, fingerprint_collection.clone()).await?;
+
+    // Keep the installed addons around for the API.
+    let installed_addons = addons_by_flavor
+        .iter()
+        .flat_map(|(flavor, addons)| {
+            let config = &config;
+
+            addons.iter().map(move |addon| InstalledAddon {
+                flavor: *flavor,
+                id: addon.id.clone(),
+                title: addon.title.clone(),
+                version: addon.version.clone(),
+                release_channel: addon.release_channel,
+                update: addon
+                    .relevant_release_package()
+                    .filter(|package| addon.is_updatable(package))
+                    .map(|package| package.version.clone()),
+                is_ignored: config
+                    .addons
+                    .ignored
+                    .get(flavor)
+                    .map(|ids| ids.iter().any(|i| i == &addon.id))
+                    .unwrap_or_default(),
+                is_auto_update: config.addons.is_auto_update(
+                    *flavor,
+                    &addon.id,
+                    addon.release_channel,
+                ),
+            })
+        })
+        .collect();
+    *daemon.addons.lock().await = installed_addons;
+
+    let (addons_to_update, available): (Vec<_>, Vec<_>) =
+        pending_updates(&config, fingerprint_collection, addons_by_flavor)
+            .into_iter()
+            .partition(|(_, flavor, addon, ..)| {
+                update_all
+                    || config
+                        .addons
+                        .is_auto_update(*flavor, &addon.id, addon.release_channel)
+            });
+
+    {
+        let mut status = daemon.status.lock().await;
+        status.last_check = Some(Local::now());
+        status.last_error = None;
+        status.available = available
+            .iter()
+            .map(|(_, flavor, addon, ..)| AddonUpdate::new(*flavor, addon))
+            .collect();
+        status.updated.clear();
+        status.failed.clear();
+
+        log::info!(
+            "{} addons to update, {} other updates available",
+            addons_to_update.len(),
+            status.available.len()
+        );
+    }
+
+    if addons_to_update.is_empty() {
+        return Ok(());
+    }
+
+    // The clones share the download progress with the addons being downloaded.
+    *daemon.updating.lock().await = addons_to_update
+        .iter()
+        .map(|(_, flavor, addon, ..)| (*flavor, addon.clone()))
+        .collect();
+    daemon.set_state(DaemonState::Updating).await;
+
+    let results = apply_updates(&endpoints, addons_to_update).await;
+
+    daemon.updating.lock().await.clear();
+
+    let mut status = daemon.status.lock().await;
+    for (flavor, addon, result) in results {
+        let mut update = AddonUpdate::new(flavor, &addon);
+
+        match result {
+            Ok(_) => {
+                log::info!("\t{} - {} updated", &addon.id, flavor);
+
+                status.updated.push(update);
+            }
+            Err(e) => {
+                log_error(&e);
+
+                update.error = Some(e.to_string());
+                status.failed.push(update);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Backups the AddOns and WTF folders to the backup directory from the config.
+async fn backup(daemon: &Daemon) -> Result<()> {
+    log::info!("Backing up...");
+
+    daemon.set_state(DaemonState::BackingUp).await;
+
+    let config = load_config().await?;
+    let dest = config.backup_directory.clone().ok_or_else(|| {
+        ClientError::Custom(
+            "No backup directory set. Launch Ajour and choose one in settings.".to_string(),
+        )
+    })?;
+
+    let src_folders = wow_backup_folders(&config);
+    if src_folders.is_empty() {
+        return Err(ClientError::Custom(
+            "No WoW directory set, or it has no AddOns or WTF folders.".to_string(),
+        ));
+    }
+
+    let as_of = backup_folders(src_folders, dest).await?;
+
+    daemon.status.lock().await.last_backup = Local.from_local_datetime(&as_of).single();
+
+    log::info!("Backup finished");
+
+    Ok(())
+}
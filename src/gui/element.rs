@@ -2,21 +2,25 @@
 
 use {
     super::{
-        style, AjourMode, AjourState, BackupState, CatalogColumnKey, CatalogColumnState,
-        CatalogRow, ColumnKey, ColumnSettings, ColumnState, DirectoryType, Interaction, Message,
-        ReleaseChannel, ScaleState, SortDirection, ThemeState,
+        style, AjourMode, AjourState, AutoUpdateSummary, BackupState, CatalogColumnKey,
+        CatalogColumnState, CatalogDetails, CatalogInstallStatus, CatalogRow, ChangelogState,
+        ColumnKey, ColumnSettings, ColumnState, DirectoryType, ExpandedCatalogAddon, Interaction,
+        Message, NetworkField, NetworkState, PendingChangelog, ReleaseChannel, ScaleState,
+        SortDirection, ThemeState,
     },
     crate::VERSION,
     ajour_core::{
         addon::{Addon, AddonState},
         catalog::Catalog,
         config::{Config, Flavor},
+        dependency::{AddonDependencies, DeletePlan},
         theme::ColorPalette,
     },
     chrono::prelude::*,
     iced::{
-        button, scrollable, Align, Button, Checkbox, Column, Container, Element,
-        HorizontalAlignment, Length, PickList, Row, Scrollable, Space, Text, VerticalAlignment,
+        button, scrollable, text_input, Align, Button, Checkbox, Column, Container, Element,
+        HorizontalAlignment, Length, PickList, ProgressBar, Row, Scrollable, Space, Text,
+        TextInput, VerticalAlignment,
     },
     num_format::{Locale, ToFormattedString},
     widgets::{header, Header},
@@ -30,10 +34,12 @@ pub static DEFAULT_PADDING: u16 = 10;
 pub fn settings_container<'a, 'b>(
     color_palette: ColorPalette,
     directory_button_state: &'a mut button::State,
+    rehash_button_state: &'a mut button::State,
     config: &Config,
     theme_state: &'a mut ThemeState,
     scale_state: &'a mut ScaleState,
     backup_state: &'a mut BackupState,
+    network_state: &'a mut NetworkState,
     column_settings: &'a mut ColumnSettings,
     column_config: &'b [(ColumnKey, Length, bool)],
 ) -> Container<'a, Message> {
@@ -137,6 +143,27 @@ pub fn settings_container<'a, 'b>(
         (scale_title_row, scale_buttons_row)
     };
 
+    // Release channels where every addon is updated without asking.
+    let (auto_update_title_row, auto_update_column) = {
+        let auto_update_title = Text::new("Auto update").size(DEFAULT_FONT_SIZE);
+        let auto_update_title_row = Row::new().push(auto_update_title);
+
+        let mut auto_update_column = Column::new().spacing(5);
+        for channel in ReleaseChannel::ALL.iter().copied() {
+            let is_checked = config.addons.auto_update_channels.contains(&channel);
+            let checkbox = Checkbox::new(is_checked, channel.to_string(), move |is_checked| {
+                Message::Interaction(Interaction::ToggleAutoUpdateChannel(channel, is_checked))
+            })
+            .text_size(DEFAULT_FONT_SIZE)
+            .spacing(5)
+            .style(style::DefaultCheckbox(color_palette));
+
+            auto_update_column = auto_update_column.push(checkbox);
+        }
+
+        (auto_update_title_row, auto_update_column)
+    };
+
     // Small space below content.
     let bottom_space = Space::new(Length::FillPortion(1), Length::Units(DEFAULT_PADDING));
 
@@ -250,6 +277,128 @@ pub fn settings_container<'a, 'b>(
         (backup_title_row, backup_directory_row, backup_now_row)
     };
 
+    let (fingerprints_title_row, rehash_row) = {
+        // Title for the Fingerprints section.
+        let fingerprints_title_text = Text::new("Fingerprints").size(DEFAULT_FONT_SIZE);
+        let fingerprints_title_row = Row::new().push(fingerprints_title_text);
+
+        // Rehash button, which throws away all cached fingerprints.
+        let rehash_button_title_container =
+            Container::new(Text::new("Rehash All").size(DEFAULT_FONT_SIZE))
+                .width(Length::FillPortion(1))
+                .center_x()
+                .align_x(Align::Center);
+        let mut rehash_button = Button::new(rehash_button_state, rehash_button_title_container)
+            .width(Length::Units(100))
+            .style(style::DefaultBoxedButton(color_palette));
+
+        if config.wow.directory.is_some() {
+            rehash_button = rehash_button.on_press(Interaction::Rehash);
+        }
+
+        let rehash_button: Element<Interaction> = rehash_button.into();
+
+        let rehash_text = Text::new("Fingerprint all addons again, ignoring cached fingerprints")
+            .size(DEFAULT_FONT_SIZE)
+            .vertical_alignment(VerticalAlignment::Center);
+        let rehash_text_container = Container::new(rehash_text)
+            .height(Length::Units(25))
+            .center_y()
+            .style(style::NormalForegroundContainer(color_palette));
+
+        let rehash_row = Row::new()
+            .push(rehash_button.map(Message::Interaction))
+            .push(Space::new(Length::Units(DEFAULT_PADDING), Length::Units(0)))
+            .push(rehash_text_container);
+
+        (fingerprints_title_row, rehash_row)
+    };
+
+    let (network_title_row, proxy_row, network_options_row, password_note_row) = {
+        // Title for the Network section.
+        let network_title_text = Text::new("Network").size(DEFAULT_FONT_SIZE);
+        let network_title_row = Row::new().push(network_title_text);
+
+        let proxy_input = network_input(
+            color_palette,
+            &mut network_state.proxy_input_state,
+            "Proxy, e.g. http://proxy.lan:3128",
+            &network_state.proxy,
+            NetworkField::Proxy,
+        )
+        .width(Length::FillPortion(2));
+        let username_input = network_input(
+            color_palette,
+            &mut network_state.proxy_username_input_state,
+            "Username",
+            &network_state.proxy_username,
+            NetworkField::ProxyUsername,
+        )
+        .width(Length::FillPortion(1));
+        let password_input = network_input(
+            color_palette,
+            &mut network_state.proxy_password_input_state,
+            "Password",
+            &network_state.proxy_password,
+            NetworkField::ProxyPassword,
+        )
+        .password()
+        .width(Length::FillPortion(1));
+
+        let proxy_row = Row::new()
+            .spacing(1)
+            .push(proxy_input)
+            .push(username_input)
+            .push(password_input);
+
+        let no_proxy_input = network_input(
+            color_palette,
+            &mut network_state.no_proxy_input_state,
+            "No proxy for, e.g. localhost,.lan",
+            &network_state.no_proxy,
+            NetworkField::NoProxy,
+        )
+        .width(Length::FillPortion(1));
+        let ca_roots_input = network_input(
+            color_palette,
+            &mut network_state.ca_roots_input_state,
+            "CA roots (.pem), replaces the system ones",
+            &network_state.ca_roots,
+            NetworkField::CaRoots,
+        )
+        .width(Length::FillPortion(1));
+
+        let apply_button: Element<Interaction> = Button::new(
+            &mut network_state.apply_btn_state,
+            Text::new("Apply").size(DEFAULT_FONT_SIZE),
+        )
+        .style(style::DefaultBoxedButton(color_palette))
+        .on_press(Interaction::ApplyNetworkSettings)
+        .into();
+
+        let network_options_row = Row::new()
+            .spacing(1)
+            .push(no_proxy_input)
+            .push(ca_roots_input)
+            .push(Space::new(Length::Units(DEFAULT_PADDING), Length::Units(0)))
+            .push(apply_button.map(Message::Interaction));
+
+        let password_note_text =
+            Text::new("The proxy password is saved as plain text in ajour.yml")
+                .size(DEFAULT_FONT_SIZE);
+        let password_note_row = Row::new().push(
+            Container::new(password_note_text)
+                .style(style::NormalForegroundContainer(color_palette)),
+        );
+
+        (
+            network_title_row,
+            proxy_row,
+            network_options_row,
+            password_note_row,
+        )
+    };
+
     let (columns_title_row, columns_scrollable) = {
         // Title for the Columns section.
         let columns_title_text = Text::new("Columns").size(DEFAULT_FONT_SIZE);
@@ -356,6 +505,24 @@ pub fn settings_container<'a, 'b>(
         .push(backup_now_row)
         .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
         .push(backup_directory_row)
+        .push(Space::new(
+            Length::Units(0),
+            Length::Units(DEFAULT_PADDING + DEFAULT_PADDING),
+        ))
+        .push(fingerprints_title_row)
+        .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
+        .push(rehash_row)
+        .push(Space::new(
+            Length::Units(0),
+            Length::Units(DEFAULT_PADDING + DEFAULT_PADDING),
+        ))
+        .push(network_title_row)
+        .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
+        .push(proxy_row)
+        .push(Space::new(Length::Units(0), Length::Units(1)))
+        .push(network_options_row)
+        .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
+        .push(password_note_row)
         .push(bottom_space);
 
     let middle_column = Column::new()
@@ -366,7 +533,11 @@ pub fn settings_container<'a, 'b>(
         .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
         .push(theme_info_row)
         .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
-        .push(theme_data_row);
+        .push(theme_data_row)
+        .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
+        .push(auto_update_title_row)
+        .push(Space::new(Length::Units(0), Length::Units(DEFAULT_PADDING)))
+        .push(auto_update_column);
 
     let left_spacer = Space::new(Length::Units(DEFAULT_PADDING), Length::Units(0));
     let right_spacer = Space::new(Length::Units(DEFAULT_PADDING + 5), Length::Units(0));
@@ -406,10 +577,32 @@ pub fn settings_container<'a, 'b>(
         .padding(DEFAULT_PADDING + DEFAULT_PADDING)
 }
 
+/// Text input for one of the network settings.
+fn network_input<'a>(
+    color_palette: ColorPalette,
+    state: &'a mut text_input::State,
+    placeholder: &str,
+    value: &str,
+    field: NetworkField,
+) -> TextInput<'a, Message> {
+    TextInput::new(state, placeholder, value, move |value| {
+        Message::Interaction(Interaction::NetworkSettingChanged(field, value))
+    })
+    .size(DEFAULT_FONT_SIZE)
+    .padding(5)
+    .style(style::CatalogQueryInput(color_palette))
+}
+
 pub fn addon_data_cell<'a, 'b>(
     color_palette: ColorPalette,
     addon: &'a mut Addon,
     is_addon_expanded: bool,
+    game_interface: Option<u32>,
+    dependencies: Option<AddonDependencies>,
+    delete_plan: Option<DeletePlan>,
+    changelog: Option<&PendingChangelog>,
+    is_auto_update: bool,
+    is_channel_auto_update: bool,
     column_config: &'b [(ColumnKey, Length, bool)],
 ) -> Container<'a, Message> {
     let default_height = Length::Units(26);
@@ -591,6 +784,40 @@ pub fn addon_data_cell<'a, 'b>(
         row_containers.push((idx, game_version_container));
     }
 
+    if let Some((idx, width)) = column_config
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (key, width, hidden))| {
+            if *key == ColumnKey::Interface && !hidden {
+                Some((idx, width))
+            } else {
+                None
+            }
+        })
+        .next()
+    {
+        let interface = addon
+            .toc
+            .interface
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let interface = Text::new(interface).size(DEFAULT_FONT_SIZE);
+        let mut interface_container = Container::new(interface)
+            .height(default_height)
+            .width(*width)
+            .center_y()
+            .padding(5)
+            .style(style::NormalForegroundContainer(color_palette));
+
+        // Out of date addons won't be loaded by the game, so we highlight them.
+        if addon.is_out_of_date(game_interface) {
+            interface_container =
+                interface_container.style(style::NormalErrorForegroundContainer(color_palette));
+        }
+
+        row_containers.push((idx, interface_container));
+    }
+
     if let Some((idx, width)) = column_config
         .iter()
         .enumerate()
@@ -632,15 +859,33 @@ pub fn addon_data_cell<'a, 'b>(
                     .center_x()
                     .style(style::BrightForegroundContainer(color_palette))
             }
-            AddonState::Downloading => {
-                Container::new(Text::new("Downloading").size(DEFAULT_FONT_SIZE))
-                    .height(default_height)
-                    .width(*width)
-                    .center_y()
-                    .center_x()
-                    .padding(5)
-                    .style(style::NormalForegroundContainer(color_palette))
-            }
+            AddonState::Downloading => match addon.download_progress.fraction() {
+                Some(fraction) => Container::new(
+                    ProgressBar::new(0.0..=1.0, fraction)
+                        .height(Length::Units(DEFAULT_FONT_SIZE))
+                        .style(style::DownloadProgressBar(color_palette)),
+                )
+                .height(default_height)
+                .width(*width)
+                .center_y()
+                .center_x()
+                .padding(5)
+                .style(style::NormalForegroundContainer(color_palette)),
+                // The server didn't tell us the size, so we can only show the bytes.
+                None => Container::new(
+                    Text::new(format!(
+                        "Downloading {} KB",
+                        addon.download_progress.downloaded() / 1024
+                    ))
+                    .size(DEFAULT_FONT_SIZE),
+                )
+                .height(default_height)
+                .width(*width)
+                .center_y()
+                .center_x()
+                .padding(5)
+                .style(style::NormalForegroundContainer(color_palette)),
+            },
             AddonState::Unpacking => Container::new(Text::new("Unpacking").size(DEFAULT_FONT_SIZE))
                 .height(default_height)
                 .width(*width)
@@ -655,6 +900,13 @@ pub fn addon_data_cell<'a, 'b>(
                 .center_x()
                 .padding(5)
                 .style(style::NormalForegroundContainer(color_palette)),
+            AddonState::Modified => Container::new(Text::new("Modified").size(DEFAULT_FONT_SIZE))
+                .height(default_height)
+                .width(*width)
+                .center_y()
+                .center_x()
+                .padding(5)
+                .style(style::NormalForegroundContainer(color_palette)),
             AddonState::Ignored => Container::new(Text::new("Ignored").size(DEFAULT_FONT_SIZE))
                 .height(default_height)
                 .width(*width)
@@ -683,6 +935,7 @@ pub fn addon_data_cell<'a, 'b>(
     let mut addon_column = Column::new().push(row);
 
     if is_addon_expanded {
+        let is_modified = addon.is_modified();
         let notes = addon
             .notes
             .clone()
@@ -757,6 +1010,19 @@ pub fn addon_data_cell<'a, 'b>(
 
         let force_download_button: Element<Interaction> = force_download_button.into();
 
+        let mut repair_button = Button::new(
+            &mut addon.repair_btn_state,
+            Text::new("Repair").size(DEFAULT_FONT_SIZE),
+        )
+        .style(style::DefaultButton(color_palette));
+
+        // If the addon is modified and we know which file it came from, enable repair.
+        if is_modified && addon.repair_package.is_some() {
+            repair_button = repair_button.on_press(Interaction::Repair(addon.id.clone()));
+        }
+
+        let repair_button: Element<Interaction> = repair_button.into();
+
         let is_ignored = addon.state == AddonState::Ignored;
         let ignore_button_text = if is_ignored {
             Text::new("Unignore").size(DEFAULT_FONT_SIZE)
@@ -776,17 +1042,48 @@ pub fn addon_data_cell<'a, 'b>(
 
         let ignore_button: Element<Interaction> = ignore_button.into();
 
+        let delete_button_text = if delete_plan.is_some() {
+            "Confirm delete"
+        } else {
+            "Delete"
+        };
         let delete_button: Element<Interaction> = Button::new(
             &mut addon.delete_btn_state,
-            Text::new("Delete").size(DEFAULT_FONT_SIZE),
+            Text::new(delete_button_text).size(DEFAULT_FONT_SIZE),
         )
         .on_press(Interaction::Delete(addon.id.clone()))
         .style(style::DefaultDeleteButton(color_palette))
         .into();
 
+        // Addons following an auto update channel can't be turned off one by one.
+        let id = addon.id.clone();
+        let auto_update_checkbox = if is_channel_auto_update {
+            Checkbox::new(
+                true,
+                format!("Auto update ({} channel)", addon.release_channel),
+                move |is_checked| {
+                    Message::Interaction(Interaction::ToggleAutoUpdate(id.clone(), is_checked))
+                },
+            )
+            .style(style::AlwaysCheckedCheckbox(color_palette))
+        } else {
+            Checkbox::new(is_auto_update, "Auto update", move |is_checked| {
+                Message::Interaction(Interaction::ToggleAutoUpdate(id.clone(), is_checked))
+            })
+            .style(style::DefaultCheckbox(color_palette))
+        }
+        .text_size(DEFAULT_FONT_SIZE)
+        .spacing(5);
+        let auto_update_container = Container::new(auto_update_checkbox)
+            .center_y()
+            .padding(5)
+            .style(style::NormalForegroundContainer(color_palette));
+
         let test_row = Row::new()
             .push(release_channel_list)
-            .push(release_date_text_container);
+            .push(release_date_text_container)
+            .push(Space::new(Length::Fill, Length::Units(0)))
+            .push(auto_update_container);
 
         let button_row = Row::new()
             .push(Space::new(Length::Fill, Length::Units(0)))
@@ -794,11 +1091,13 @@ pub fn addon_data_cell<'a, 'b>(
             .push(Space::new(Length::Units(5), Length::Units(0)))
             .push(force_download_button.map(Message::Interaction))
             .push(Space::new(Length::Units(5), Length::Units(0)))
+            .push(repair_button.map(Message::Interaction))
+            .push(Space::new(Length::Units(5), Length::Units(0)))
             .push(ignore_button.map(Message::Interaction))
             .push(Space::new(Length::Units(5), Length::Units(0)))
             .push(delete_button.map(Message::Interaction))
             .width(Length::Fill);
-        let column = Column::new()
+        let mut column = Column::new()
             .push(author_title_container)
             .push(Space::new(Length::Units(0), Length::Units(3)))
             .push(author_text)
@@ -806,7 +1105,176 @@ pub fn addon_data_cell<'a, 'b>(
             .push(notes_title_container)
             .push(Space::new(Length::Units(0), Length::Units(3)))
             .push(notes_text)
-            .push(Space::new(Length::Units(0), Length::Units(15)))
+            .push(Space::new(Length::Units(0), Length::Units(15)));
+
+        // Show what changed in the update before it's installed.
+        if let Some(changelog) = changelog {
+            let changelog_title_text =
+                Text::new(format!("Changelog for {}", changelog.version)).size(DEFAULT_FONT_SIZE);
+            let changelog_title_container = Container::new(changelog_title_text)
+                .style(style::BrightForegroundContainer(color_palette));
+
+            let changelog_text: Element<Message> = match &changelog.state {
+                ChangelogState::Loading => Text::new("Loading changelog...")
+                    .size(DEFAULT_FONT_SIZE)
+                    .into(),
+                ChangelogState::Loaded(Some(text)) => Text::new(changelog_text(text))
+                    .size(DEFAULT_FONT_SIZE)
+                    .into(),
+                ChangelogState::Loaded(None) => Text::new("No changelog for this version.")
+                    .size(DEFAULT_FONT_SIZE)
+                    .into(),
+                ChangelogState::Failed(error) => Container::new(
+                    Text::new(format!("Could not load changelog: {}", error))
+                        .size(DEFAULT_FONT_SIZE),
+                )
+                .style(style::NormalErrorForegroundContainer(color_palette))
+                .into(),
+            };
+
+            column = column
+                .push(changelog_title_container)
+                .push(Space::new(Length::Units(0), Length::Units(3)))
+                .push(changelog_text)
+                .push(Space::new(Length::Units(0), Length::Units(15)));
+        }
+
+        // List the module folders which differs from the remote file.
+        if is_modified {
+            let modified_title_text = Text::new("Modified modules").size(DEFAULT_FONT_SIZE);
+            let modified_title_container = Container::new(modified_title_text)
+                .style(style::BrightForegroundContainer(color_palette));
+            let modified_text =
+                Text::new(addon.modified_modules.join(", ")).size(DEFAULT_FONT_SIZE);
+
+            column = column
+                .push(modified_title_container)
+                .push(Space::new(Length::Units(0), Length::Units(3)))
+                .push(modified_text)
+                .push(Space::new(Length::Units(0), Length::Units(15)));
+        }
+
+        // List code files which no toc or xml file references.
+        if !addon.extra_files.is_empty() {
+            let extra_title_text = Text::new("Extra files").size(DEFAULT_FONT_SIZE);
+            let extra_title_container = Container::new(extra_title_text)
+                .style(style::BrightForegroundContainer(color_palette));
+            let extra_text = Text::new(addon.extra_files.join(", ")).size(DEFAULT_FONT_SIZE);
+
+            column = column
+                .push(extra_title_container)
+                .push(Space::new(Length::Units(0), Length::Units(3)))
+                .push(extra_text)
+                .push(Space::new(Length::Units(0), Length::Units(15)));
+        }
+
+        // List bundled modules, external dependencies and addons depending on this one.
+        let mut dependents = vec![];
+        if let Some(dependencies) = dependencies {
+            dependents = dependencies.dependents.clone();
+
+            let has_dependencies = !dependencies.bundled.is_empty()
+                || !dependencies.required.is_empty()
+                || !dependencies.optional.is_empty();
+
+            if has_dependencies {
+                let dependencies_title_text = Text::new("Dependencies").size(DEFAULT_FONT_SIZE);
+                let dependencies_title_container = Container::new(dependencies_title_text)
+                    .style(style::BrightForegroundContainer(color_palette));
+
+                column = column
+                    .push(dependencies_title_container)
+                    .push(Space::new(Length::Units(0), Length::Units(3)));
+
+                for (title, names) in [
+                    ("Bundled", &dependencies.bundled),
+                    ("Required", &dependencies.required),
+                    ("Optional", &dependencies.optional),
+                ]
+                .iter()
+                {
+                    if !names.is_empty() {
+                        let text = Text::new(format!("{}: {}", title, names.join(", ")))
+                            .size(DEFAULT_FONT_SIZE);
+                        column = column.push(text);
+                    }
+                }
+
+                column = column.push(Space::new(Length::Units(0), Length::Units(15)));
+            }
+
+            if !dependencies.missing.is_empty() {
+                let missing_text = Text::new(format!(
+                    "Missing required dependencies: {}",
+                    dependencies.missing.join(", ")
+                ))
+                .size(DEFAULT_FONT_SIZE);
+                let missing_text_container = Container::new(missing_text)
+                    .center_y()
+                    .style(style::NormalErrorForegroundContainer(color_palette));
+
+                let install_button: Element<Interaction> = Button::new(
+                    &mut addon.install_dependencies_btn_state,
+                    Text::new("Install missing").size(DEFAULT_FONT_SIZE),
+                )
+                .on_press(Interaction::InstallDependencies(addon.id.clone()))
+                .style(style::DefaultButton(color_palette))
+                .into();
+
+                let missing_row = Row::new()
+                    .push(missing_text_container)
+                    .push(Space::new(Length::Units(5), Length::Units(0)))
+                    .push(install_button.map(Message::Interaction));
+
+                column = column
+                    .push(missing_row)
+                    .push(Space::new(Length::Units(0), Length::Units(15)));
+            }
+        }
+
+        // Preview exactly which folders are removed before the delete is confirmed.
+        if let Some(delete_plan) = delete_plan {
+            let delete_title_text = Text::new("Delete").size(DEFAULT_FONT_SIZE);
+            let delete_title_container = Container::new(delete_title_text)
+                .style(style::BrightForegroundContainer(color_palette));
+            let remove_text = Text::new(format!(
+                "Folders to be removed: {}",
+                delete_plan.remove.join(", ")
+            ))
+            .size(DEFAULT_FONT_SIZE);
+
+            column = column
+                .push(delete_title_container)
+                .push(Space::new(Length::Units(0), Length::Units(3)))
+                .push(remove_text);
+
+            if !delete_plan.keep.is_empty() {
+                let keep_text = Text::new(format!(
+                    "Folders kept, since other addons ship them: {}",
+                    delete_plan.keep.join(", ")
+                ))
+                .size(DEFAULT_FONT_SIZE);
+
+                column = column.push(keep_text);
+            }
+
+            // Warn before deleting an addon other addons depend on.
+            if !dependents.is_empty() {
+                let warning_text = Text::new(format!(
+                    "Required by {}. Deleting it will break these addons.",
+                    dependents.join(", ")
+                ))
+                .size(DEFAULT_FONT_SIZE);
+                let warning_text_container = Container::new(warning_text)
+                    .style(style::NormalErrorForegroundContainer(color_palette));
+
+                column = column.push(warning_text_container);
+            }
+
+            column = column.push(Space::new(Length::Units(0), Length::Units(15)));
+        }
+
+        let column = column
             .push(release_channel_title_container)
             .push(Space::new(Length::Units(0), Length::Units(3)))
             .push(test_row)
@@ -837,6 +1305,17 @@ pub fn addon_data_cell<'a, 'b>(
         .style(style::Row(color_palette))
 }
 
+/// Cuts long changelogs, the rest can be read on the website.
+fn changelog_text(changelog: &str) -> String {
+    let mut lines = changelog.lines().take(31).collect::<Vec<_>>();
+    if lines.len() > 30 {
+        lines.truncate(30);
+        lines.push("...");
+    }
+
+    lines.join("\n")
+}
+
 fn row_title<T: PartialEq>(
     column_key: T,
     previous_column_key: Option<T>,
@@ -920,10 +1399,13 @@ pub fn menu_addons_container<'a>(
     color_palette: ColorPalette,
     update_all_button_state: &'a mut button::State,
     refresh_button_state: &'a mut button::State,
+    verify_button_state: &'a mut button::State,
     retail_btn_state: &'a mut button::State,
     classic_btn_state: &'a mut button::State,
     state: &AjourState,
     addons: &[Addon],
+    game_interface: Option<u32>,
+    out_of_date_filter: bool,
     config: &'a mut Config,
 ) -> Container<'a, Message> {
     // A row contain general settings.
@@ -941,10 +1423,19 @@ pub fn menu_addons_container<'a>(
     )
     .style(style::DefaultButton(color_palette));
 
+    let mut verify_button = Button::new(
+        verify_button_state,
+        Text::new("Verify").size(DEFAULT_FONT_SIZE),
+    )
+    .style(style::DefaultButton(color_palette));
+
     // Is any addon performing an action.
-    let addons_performing_actions = addons
-        .iter()
-        .any(|a| matches!(a.state, AddonState::Downloading | AddonState::Unpacking));
+    let addons_performing_actions = addons.iter().any(|a| {
+        matches!(
+            a.state,
+            AddonState::Downloading | AddonState::Unpacking | AddonState::Fingerprint
+        )
+    });
 
     let ajour_performing_actions = matches!(state, AjourState::Loading);
     let ajour_welcome = matches!(state, AjourState::Welcome);
@@ -972,8 +1463,17 @@ pub fn menu_addons_container<'a>(
         refresh_button = refresh_button.on_press(Interaction::Refresh);
     }
 
+    // Enable verify_button if:
+    //   - We have addons.
+    //   - No addon is performing any task.
+    //   - Ajour isn't loading
+    if !addons.is_empty() && !addons_performing_actions && !ajour_performing_actions {
+        verify_button = verify_button.on_press(Interaction::Verify);
+    }
+
     let update_all_button: Element<Interaction> = update_all_button.into();
     let refresh_button: Element<Interaction> = refresh_button.into();
+    let verify_button: Element<Interaction> = verify_button.into();
 
     let mut retail_button = Button::new(
         retail_btn_state,
@@ -1010,6 +1510,24 @@ pub fn menu_addons_container<'a>(
         .push(classic_button.map(Message::Interaction))
         .spacing(1);
 
+    // Filter which only shows addons the game won't load.
+    let num_out_of_date = addons
+        .iter()
+        .filter(|a| a.is_out_of_date(game_interface))
+        .count();
+    let out_of_date_checkbox = Checkbox::new(
+        out_of_date_filter,
+        format!("Out of date ({})", num_out_of_date),
+        |is_checked| Message::Interaction(Interaction::OutOfDateFilter(is_checked)),
+    )
+    .text_size(DEFAULT_FONT_SIZE)
+    .spacing(5)
+    .style(style::DefaultCheckbox(color_palette));
+    let out_of_date_container = Container::new(out_of_date_checkbox)
+        .center_y()
+        .padding(5)
+        .style(style::NormalBackgroundContainer(color_palette));
+
     // Displays text depending on the state of the app.
     let flavor = config.wow.flavor;
     let ignored_addons = config.addons.ignored.get(&flavor);
@@ -1055,7 +1573,11 @@ pub fn menu_addons_container<'a>(
         .push(Space::new(Length::Units(7), Length::Units(0)))
         .push(update_all_button.map(Message::Interaction))
         .push(Space::new(Length::Units(7), Length::Units(0)))
+        .push(verify_button.map(Message::Interaction))
+        .push(Space::new(Length::Units(7), Length::Units(0)))
         .push(segmented_flavor_control_container)
+        .push(Space::new(Length::Units(7), Length::Units(0)))
+        .push(out_of_date_container)
         .push(status_container)
         .push(error_container);
 
@@ -1078,13 +1600,21 @@ pub fn menu_container<'a>(
     catalog_mode_btn_state: &'a mut button::State,
     needs_update: Option<&'a str>,
     new_release_button_state: &'a mut button::State,
+    num_updates: usize,
 ) -> Container<'a, Message> {
     // A row contain general settings.
     let mut settings_row = Row::new().height(Length::Units(40));
 
+    // Badge with the number of addons with an update available.
+    let addons_mode_text = if num_updates > 0 {
+        format!("My Addons ({})", num_updates)
+    } else {
+        "My Addons".to_owned()
+    };
+
     let mut addons_mode_button = Button::new(
         addon_mode_button_state,
-        Text::new("My Addons").size(DEFAULT_FONT_SIZE),
+        Text::new(addons_mode_text).size(DEFAULT_FONT_SIZE),
     )
     .style(style::DisabledDefaultButton(color_palette));
 
@@ -1245,6 +1775,50 @@ pub fn status_container<'a>(
         .height(Length::Fill)
 }
 
+/// Container listing the addons which were updated without asking.
+pub fn auto_update_summary_container<'a>(
+    color_palette: ColorPalette,
+    summary: &'a mut AutoUpdateSummary,
+) -> Container<'a, Message> {
+    let mut summary_column = Column::new().spacing(5);
+
+    if !summary.updated.is_empty() {
+        let updated_text = Text::new(format!(
+            "Automatically updated: {}",
+            summary.updated.join(", ")
+        ))
+        .size(DEFAULT_FONT_SIZE);
+        summary_column = summary_column.push(updated_text);
+    }
+
+    if !summary.failed.is_empty() {
+        let failed_text = Text::new(format!(
+            "Failed to update automatically: {}",
+            summary.failed.join(", ")
+        ))
+        .size(DEFAULT_FONT_SIZE);
+        summary_column = summary_column.push(failed_text);
+    }
+
+    let dismiss_button: Element<Interaction> = Button::new(
+        &mut summary.dismiss_btn_state,
+        Text::new("Dismiss").size(DEFAULT_FONT_SIZE),
+    )
+    .style(style::DefaultButton(color_palette))
+    .on_press(Interaction::DismissAutoUpdateSummary)
+    .into();
+
+    let summary_row = Row::new()
+        .push(summary_column.width(Length::Fill))
+        .push(dismiss_button.map(Message::Interaction))
+        .align_items(Align::Center)
+        .padding(DEFAULT_PADDING);
+
+    Container::new(summary_row)
+        .width(Length::Fill)
+        .style(style::BrightForegroundContainer(color_palette))
+}
+
 pub fn catalog_row_titles<'a>(
     color_palette: ColorPalette,
     catalog: &Catalog,
@@ -1318,23 +1892,39 @@ pub fn catalog_row_titles<'a>(
 pub fn catalog_data_cell<'a, 'b>(
     color_palette: ColorPalette,
     addon: &'a mut CatalogRow,
+    expanded: Option<&'a mut ExpandedCatalogAddon>,
     column_config: &'b [(CatalogColumnKey, Length)],
-    retail_downloading: bool,
-    retail_installed: bool,
-    classic_downloading: bool,
-    classic_installed: bool,
+    release_channel: ReleaseChannel,
+    install_both_flavors: bool,
+    retail_status: CatalogInstallStatus,
+    classic_status: CatalogInstallStatus,
 ) -> Container<'a, Message> {
     let default_height = Length::Units(26);
 
     let mut row_containers = vec![];
 
     let addon_data = &addon.addon;
-    let website_state = &mut addon.website_state;
+    let sources = &addon.sources;
+    let source_state = &mut addon.source_state;
+    let details_btn_state = &mut addon.details_btn_state;
     let retail_install_state = &mut addon.retail_install_state;
     let classic_install_state = &mut addon.classic_install_state;
 
-    let retail_exists = addon_data.flavors.contains(&Flavor::Retail);
-    let classic_exists = addon_data.flavors.contains(&Flavor::Classic);
+    // Flavors to install for when pressing the install button of `flavor`.
+    let install_flavors = |flavor: Flavor| -> Vec<Flavor> {
+        if !install_both_flavors {
+            return vec![flavor];
+        }
+
+        let mut flavors = vec![];
+        if retail_status == CatalogInstallStatus::NotInstalled {
+            flavors.push(Flavor::Retail);
+        }
+        if classic_status == CatalogInstallStatus::NotInstalled {
+            flavors.push(Flavor::Classic);
+        }
+        flavors
+    };
 
     if let Some((idx, width)) = column_config
         .iter()
@@ -1348,16 +1938,7 @@ pub fn catalog_data_cell<'a, 'b>(
         })
         .next()
     {
-        let retail_install = Text::new(if !retail_exists {
-            "N/A"
-        } else if retail_downloading {
-            "Downloading"
-        } else if retail_installed {
-            "Installed"
-        } else {
-            "Retail"
-        })
-        .size(DEFAULT_FONT_SIZE);
+        let retail_install = Text::new(retail_status.title()).size(DEFAULT_FONT_SIZE);
 
         let retail_install_wrapper = Container::new(retail_install)
             .width(*width)
@@ -1368,12 +1949,21 @@ pub fn catalog_data_cell<'a, 'b>(
             .style(style::DefaultButton(color_palette))
             .width(*width);
 
-        if !retail_installed && !retail_downloading && retail_exists {
-            retail_install_button = retail_install_button.on_press(Interaction::CatalogInstall(
-                addon_data.source,
-                Flavor::Retail,
-                addon_data.id,
-            ));
+        match &retail_status {
+            CatalogInstallStatus::NotInstalled => {
+                retail_install_button =
+                    retail_install_button.on_press(Interaction::CatalogInstall(
+                        addon_data.source.clone(),
+                        install_flavors(Flavor::Retail),
+                        addon_data.id,
+                        release_channel,
+                    ));
+            }
+            CatalogInstallStatus::Updatable(id) => {
+                retail_install_button = retail_install_button
+                    .on_press(Interaction::CatalogUpdate(Flavor::Retail, id.clone()));
+            }
+            _ => {}
         }
 
         let retail_install_button: Element<Interaction> = retail_install_button.into();
@@ -1400,16 +1990,7 @@ pub fn catalog_data_cell<'a, 'b>(
         })
         .next()
     {
-        let classic_install = Text::new(if !classic_exists {
-            "N/A"
-        } else if classic_downloading {
-            "Downloading"
-        } else if classic_installed {
-            "Installed"
-        } else {
-            "Classic"
-        })
-        .size(DEFAULT_FONT_SIZE);
+        let classic_install = Text::new(classic_status.title()).size(DEFAULT_FONT_SIZE);
 
         let classic_install_wrapper = Container::new(classic_install)
             .width(*width)
@@ -1421,12 +2002,21 @@ pub fn catalog_data_cell<'a, 'b>(
                 .style(style::DefaultButton(color_palette))
                 .width(*width);
 
-        if !classic_installed && !classic_downloading && classic_exists {
-            classic_install_button = classic_install_button.on_press(Interaction::CatalogInstall(
-                addon_data.source,
-                Flavor::Classic,
-                addon_data.id,
-            ));
+        match &classic_status {
+            CatalogInstallStatus::NotInstalled => {
+                classic_install_button =
+                    classic_install_button.on_press(Interaction::CatalogInstall(
+                        addon_data.source.clone(),
+                        install_flavors(Flavor::Classic),
+                        addon_data.id,
+                        release_channel,
+                    ));
+            }
+            CatalogInstallStatus::Updatable(id) => {
+                classic_install_button = classic_install_button
+                    .on_press(Interaction::CatalogUpdate(Flavor::Classic, id.clone()));
+            }
+            _ => {}
         }
 
         let classic_install_button: Element<Interaction> = classic_install_button.into();
@@ -1455,10 +2045,17 @@ pub fn catalog_data_cell<'a, 'b>(
         .next()
     {
         let title = Text::new(&addon_data.name).size(DEFAULT_FONT_SIZE);
-        let title_button: Element<Interaction> = Button::new(website_state, title)
-            .style(style::BrightTextButton(color_palette))
-            .on_press(Interaction::OpenLink(addon_data.website_url.clone()))
-            .into();
+        let mut title_button = Button::new(details_btn_state, title).on_press(
+            Interaction::ExpandCatalogAddon(addon_data.source.clone(), addon_data.id),
+        );
+
+        if expanded.is_some() {
+            title_button = title_button.style(style::SelectedBrightTextButton(color_palette));
+        } else {
+            title_button = title_button.style(style::BrightTextButton(color_palette));
+        }
+
+        let title_button: Element<Interaction> = title_button.into();
 
         let title_container = Container::new(title_button.map(Message::Interaction))
             .height(default_height)
@@ -1504,7 +2101,31 @@ pub fn catalog_data_cell<'a, 'b>(
         })
         .next()
     {
-        let source = Text::new(&format!("{}", addon_data.source)).size(DEFAULT_FONT_SIZE);
+        // Addons available from several sources can be installed from any of them.
+        let source: Element<Message> = if sources.len() > 1 {
+            let (current_source, id) = (addon_data.source.clone(), addon_data.id);
+
+            PickList::new(
+                source_state,
+                sources,
+                Some(addon_data.source.clone()),
+                move |source| {
+                    Message::Interaction(Interaction::CatalogAddonSourceSelected(
+                        current_source.clone(),
+                        id,
+                        source,
+                    ))
+                },
+            )
+            .text_size(DEFAULT_FONT_SIZE)
+            .width(Length::Fill)
+            .style(style::SecondaryPickList(color_palette))
+            .into()
+        } else {
+            Text::new(&format!("{}", addon_data.source))
+                .size(DEFAULT_FONT_SIZE)
+                .into()
+        };
         let source_container = Container::new(source)
             .height(default_height)
             .width(*width)
@@ -1557,7 +2178,256 @@ pub fn catalog_data_cell<'a, 'b>(
 
     row = row.push(right_spacer);
 
-    Container::new(row)
+    let mut addon_column = Column::new().push(row);
+
+    if let Some(expanded) = expanded {
+        let left_spacer = Space::new(Length::Units(DEFAULT_PADDING), Length::Units(0));
+
+        let ExpandedCatalogAddon {
+            details,
+            required_dependencies,
+            optional_dependencies,
+            website_btn_state,
+            screenshot_btn_states,
+            file_btn_states,
+            ..
+        } = expanded;
+
+        let mut column = Column::new();
+
+        match details {
+            CatalogDetails::Loading => {
+                let loading_text = Text::new("Loading details...").size(DEFAULT_FONT_SIZE);
+
+                column = column.push(loading_text);
+            }
+            CatalogDetails::Failed(error) => {
+                let error_text =
+                    Text::new(format!("Could not load details: {}", error)).size(DEFAULT_FONT_SIZE);
+                let error_text_container = Container::new(error_text)
+                    .style(style::NormalErrorForegroundContainer(color_palette));
+
+                column = column.push(error_text_container);
+            }
+            CatalogDetails::Loaded(details) => {
+                if let Some(author) = &details.author {
+                    let author_title_text = Text::new("Author(s)").size(DEFAULT_FONT_SIZE);
+                    let author_title_container = Container::new(author_title_text)
+                        .style(style::BrightForegroundContainer(color_palette));
+                    let author_text = Text::new(author).size(DEFAULT_FONT_SIZE);
+
+                    column = column
+                        .push(author_title_container)
+                        .push(Space::new(Length::Units(0), Length::Units(3)))
+                        .push(author_text)
+                        .push(Space::new(Length::Units(0), Length::Units(15)));
+                }
+
+                let description_title_text = Text::new("Description").size(DEFAULT_FONT_SIZE);
+                let description_title_container = Container::new(description_title_text)
+                    .style(style::BrightForegroundContainer(color_palette));
+                let description_text = Text::new(if details.description.is_empty() {
+                    "No description for addon."
+                } else {
+                    details.description.as_str()
+                })
+                .size(DEFAULT_FONT_SIZE);
+
+                column = column
+                    .push(description_title_container)
+                    .push(Space::new(Length::Units(0), Length::Units(3)))
+                    .push(description_text)
+                    .push(Space::new(Length::Units(0), Length::Units(15)));
+
+                // Screenshots are opened in the browser.
+                if !details.screenshots.is_empty() {
+                    let screenshots_title_text = Text::new("Screenshots").size(DEFAULT_FONT_SIZE);
+                    let screenshots_title_container = Container::new(screenshots_title_text)
+                        .style(style::BrightForegroundContainer(color_palette));
+
+                    let mut screenshots_row = Row::new().spacing(5);
+                    for (idx, (url, state)) in details
+                        .screenshots
+                        .iter()
+                        .zip(screenshot_btn_states.iter_mut())
+                        .enumerate()
+                    {
+                        let screenshot_button: Element<Interaction> = Button::new(
+                            state,
+                            Text::new(format!("#{}", idx + 1)).size(DEFAULT_FONT_SIZE),
+                        )
+                        .style(style::DefaultButton(color_palette))
+                        .on_press(Interaction::OpenLink(url.clone()))
+                        .into();
+
+                        screenshots_row =
+                            screenshots_row.push(screenshot_button.map(Message::Interaction));
+                    }
+
+                    column = column
+                        .push(screenshots_title_container)
+                        .push(Space::new(Length::Units(0), Length::Units(3)))
+                        .push(screenshots_row)
+                        .push(Space::new(Length::Units(0), Length::Units(15)));
+                }
+
+                // The newest files of each release channel.
+                let mut files_per_channel: Vec<Vec<_>> = vec![vec![], vec![], vec![]];
+                for (idx, (file, state)) in details
+                    .files
+                    .iter()
+                    .zip(file_btn_states.iter_mut())
+                    .enumerate()
+                {
+                    let channel_idx = ReleaseChannel::ALL
+                        .iter()
+                        .position(|c| *c == file.release_channel)
+                        .unwrap_or_default();
+                    let files = &mut files_per_channel[channel_idx];
+
+                    if files.len() < 5 {
+                        files.push((idx, file, state));
+                    }
+                }
+
+                for (release_channel, files) in ReleaseChannel::ALL
+                    .iter()
+                    .zip(files_per_channel.into_iter())
+                {
+                    if files.is_empty() {
+                        continue;
+                    }
+
+                    let files_title_text =
+                        Text::new(format!("{} files", release_channel)).size(DEFAULT_FONT_SIZE);
+                    let files_title_container = Container::new(files_title_text)
+                        .style(style::BrightForegroundContainer(color_palette));
+
+                    column = column
+                        .push(files_title_container)
+                        .push(Space::new(Length::Units(0), Length::Units(3)));
+
+                    for (idx, file, state) in files {
+                        let date = file
+                            .date_time
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_else(|| "-".to_owned());
+                        let file_text = Text::new(format!(
+                            "{} ({}, {}) {}",
+                            file.version,
+                            file.flavor,
+                            if file.game_versions.is_empty() {
+                                "-".to_owned()
+                            } else {
+                                file.game_versions.join(", ")
+                            },
+                            date
+                        ))
+                        .size(DEFAULT_FONT_SIZE);
+
+                        let install_button: Element<Interaction> =
+                            Button::new(state, Text::new("Install").size(DEFAULT_FONT_SIZE))
+                                .style(style::DefaultButton(color_palette))
+                                .on_press(Interaction::CatalogInstallFile(idx))
+                                .into();
+
+                        let file_row = Row::new()
+                            .push(
+                                Container::new(file_text)
+                                    .width(Length::Fill)
+                                    .center_y()
+                                    .height(Length::Units(26)),
+                            )
+                            .push(install_button.map(Message::Interaction))
+                            .align_items(Align::Center);
+
+                        column = column.push(file_row);
+                    }
+
+                    column = column.push(Space::new(Length::Units(0), Length::Units(15)));
+                }
+
+                if let Some(changelog) = &details.changelog {
+                    let changelog_title_text = Text::new("Changelog").size(DEFAULT_FONT_SIZE);
+                    let changelog_title_container = Container::new(changelog_title_text)
+                        .style(style::BrightForegroundContainer(color_palette));
+
+                    let changelog_text =
+                        Text::new(changelog_text(changelog)).size(DEFAULT_FONT_SIZE);
+
+                    column = column
+                        .push(changelog_title_container)
+                        .push(Space::new(Length::Units(0), Length::Units(3)))
+                        .push(changelog_text)
+                        .push(Space::new(Length::Units(0), Length::Units(15)));
+                }
+
+                if !required_dependencies.is_empty() || !optional_dependencies.is_empty() {
+                    let dependencies_title_text = Text::new("Dependencies").size(DEFAULT_FONT_SIZE);
+                    let dependencies_title_container = Container::new(dependencies_title_text)
+                        .style(style::BrightForegroundContainer(color_palette));
+
+                    column = column
+                        .push(dependencies_title_container)
+                        .push(Space::new(Length::Units(0), Length::Units(3)));
+
+                    if !required_dependencies.is_empty() {
+                        let required_text =
+                            Text::new(format!("Required: {}", required_dependencies.join(", ")))
+                                .size(DEFAULT_FONT_SIZE);
+
+                        column = column.push(required_text);
+                    }
+
+                    if !optional_dependencies.is_empty() {
+                        let optional_text =
+                            Text::new(format!("Optional: {}", optional_dependencies.join(", ")))
+                                .size(DEFAULT_FONT_SIZE);
+
+                        column = column.push(optional_text);
+                    }
+
+                    column = column.push(Space::new(Length::Units(0), Length::Units(15)));
+                }
+            }
+        }
+
+        let website_button: Element<Interaction> = Button::new(
+            website_btn_state,
+            Text::new("Website").size(DEFAULT_FONT_SIZE),
+        )
+        .on_press(Interaction::OpenLink(addon_data.website_url.clone()))
+        .style(style::DefaultButton(color_palette))
+        .into();
+
+        let button_row = Row::new()
+            .push(Space::new(Length::Fill, Length::Units(0)))
+            .push(website_button.map(Message::Interaction))
+            .width(Length::Fill);
+
+        let column = column
+            .push(Space::new(Length::Units(0), Length::Units(5)))
+            .push(button_row);
+        let details_container = Container::new(column)
+            .width(Length::Fill)
+            .padding(20)
+            .style(style::FadedNormalForegroundContainer(color_palette));
+
+        let row = Row::new()
+            .push(left_spacer)
+            .push(details_container)
+            .push(Space::new(
+                Length::Units(DEFAULT_PADDING + 5),
+                Length::Units(0),
+            ))
+            .spacing(1);
+
+        addon_column = addon_column
+            .push(Space::new(Length::FillPortion(1), Length::Units(1)))
+            .push(row);
+    }
+
+    Container::new(addon_column)
         .width(Length::Fill)
         .style(style::Row(color_palette))
 }
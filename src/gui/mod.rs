@@ -6,34 +6,36 @@ use crate::cli::Opts;
 use crate::VERSION;
 use ajour_core::{
     addon::{Addon, AddonState, ReleaseChannel},
-    catalog::get_catalog,
-    catalog::{self, Catalog, CatalogAddon},
-    config::{load_config, ColumnConfigV2, Config, Flavor},
+    catalog::{self, Catalog, CatalogAddon, CatalogAddonDetails},
+    catalog::{load_cached_catalog, refresh_catalog},
+    config::{load_config, ColumnConfigV2, Config, Endpoints, Flavor, Network},
+    dependency::{delete_plan, DependencyGraph},
     error::ClientError,
     fs::PersistentData,
     parse::FingerprintCollection,
     theme::{load_user_themes, Theme},
     utility::needs_update,
+    verify::AddonIntegrity,
     Result,
 };
 use async_std::sync::{Arc, Mutex};
 use chrono::NaiveDateTime;
 use iced::{
-    button, pick_list, scrollable, text_input, Application, Column, Command, Container, Element,
-    Length, PickList, Row, Settings, Space, Subscription, TextInput,
+    button, pick_list, scrollable, text_input, Application, Checkbox, Column, Command, Container,
+    Element, Length, PickList, Row, Settings, Space, Subscription, TextInput,
 };
 use image::ImageFormat;
-use isahc::{
-    config::{Configurable, RedirectPolicy},
-    HttpClient,
-};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use widgets::header;
 
 use element::{DEFAULT_FONT_SIZE, DEFAULT_PADDING};
 static WINDOW_ICON: &[u8] = include_bytes!("../../resources/windows/ajour.ico");
 
+/// How often download progress is redrawn.
+const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub enum AjourState {
     Error(ClientError),
@@ -64,6 +66,7 @@ impl std::fmt::Display for AjourMode {
 #[derive(Debug, Clone)]
 pub enum Interaction {
     Delete(String),
+    InstallDependencies(String),
     Expand(String),
     Ignore(String),
     OpenDirectory(DirectoryType),
@@ -73,6 +76,9 @@ pub enum Interaction {
     Unignore(String),
     Update(String),
     UpdateAll,
+    Repair(String),
+    Verify,
+    Rehash,
     SortColumn(ColumnKey),
     SortCatalogColumn(CatalogColumnKey),
     FlavorSelected(Flavor),
@@ -81,15 +87,28 @@ pub enum Interaction {
     ScaleDown,
     Backup,
     ToggleColumn(bool, ColumnKey),
+    OutOfDateFilter(bool),
     MoveColumnLeft(ColumnKey),
     MoveColumnRight(ColumnKey),
     ModeSelected(AjourMode),
     CatalogQuery(String),
-    CatalogInstall(catalog::Source, Flavor, u32),
+    CatalogInstall(catalog::Source, Vec<Flavor>, u32, ReleaseChannel),
+    CatalogAddonSourceSelected(catalog::Source, u32, catalog::Source),
+    ExpandCatalogAddon(catalog::Source, u32),
+    CatalogInstallFile(usize),
     CatalogCategorySelected(CatalogCategory),
     CatalogResultSizeSelected(CatalogResultSize),
     CatalogFlavorSelected(CatalogFlavor),
     CatalogSourceSelected(CatalogSource),
+    CatalogReleaseChannelSelected(ReleaseChannel),
+    CatalogInstallBothFlavors(bool),
+    CatalogNotInstalledFilter(bool),
+    CatalogUpdate(Flavor, String),
+    NetworkSettingChanged(NetworkField, String),
+    ApplyNetworkSettings,
+    ToggleAutoUpdate(String, bool),
+    ToggleAutoUpdateChannel(ReleaseChannel, bool),
+    DismissAutoUpdateSummary,
 }
 
 #[derive(Debug)]
@@ -105,31 +124,49 @@ pub enum Message {
     ThemeSelected(String),
     ReleaseChannelSelected(ReleaseChannel),
     ThemesLoaded(Vec<Theme>),
-    UnpackedAddon((Flavor, String, Result<()>)),
+    UnpackedAddon((Flavor, String, Result<Vec<String>>)),
+    RepairedAddon((Flavor, String, Result<()>)),
+    VerifiedAddons((Flavor, Result<Vec<AddonIntegrity>>)),
+    DependencyGraphRead((Flavor, DependencyGraph)),
+    FingerprintCollectionCleared(Result<()>),
     UpdateWowDirectory(Option<PathBuf>),
     UpdateBackupDirectory(Option<PathBuf>),
     RuntimeEvent(iced_native::Event),
+    DownloadProgress(Instant),
+    CheckForUpdates(Instant),
+    RemotePackagesRefreshed((Flavor, Result<Vec<Addon>>)),
     LatestBackup(Option<NaiveDateTime>),
     BackupFinished(Result<NaiveDateTime>),
-    CatalogDownloaded(Result<Catalog>),
+    CatalogCacheLoaded(Option<Catalog>),
+    CatalogDownloaded(Result<Option<Catalog>>),
     CatalogInstallAddonFetched(Result<(u32, Flavor, Addon)>),
+    CatalogAddonDetailsFetched((catalog::Source, u32, Result<CatalogAddonDetails>)),
+    ChangelogFetched((String, String, Result<Option<String>>)),
 }
 
 pub struct Ajour {
     addons: HashMap<Flavor, Vec<Addon>>,
+    game_interfaces: HashMap<Flavor, u32>,
+    dependency_graphs: HashMap<Flavor, DependencyGraph>,
+    delete_confirmation: Option<String>,
+    out_of_date_filter: bool,
     addons_scrollable_state: scrollable::State,
     config: Config,
     directory_btn_state: button::State,
     expanded_addon: Option<Addon>,
+    /// Changelog of the update available for the expanded addon.
+    pending_changelog: Option<PendingChangelog>,
+    auto_update_summary: AutoUpdateSummary,
     is_showing_settings: bool,
     needs_update: Option<String>,
     new_release_button_state: button::State,
     refresh_btn_state: button::State,
     settings_btn_state: button::State,
-    shared_client: Arc<HttpClient>,
     state: AjourState,
     mode: AjourMode,
     update_all_btn_state: button::State,
+    verify_btn_state: button::State,
+    rehash_btn_state: button::State,
     header_state: HeaderState,
     theme_state: ThemeState,
     fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
@@ -139,36 +176,39 @@ pub struct Ajour {
     catalog_mode_btn_state: button::State,
     scale_state: ScaleState,
     backup_state: BackupState,
+    network_state: NetworkState,
     column_settings: ColumnSettings,
     onboarding_directory_btn_state: button::State,
     catalog: Option<Catalog>,
     catalog_search_state: CatalogSearchState,
     catalog_header_state: CatalogHeaderState,
+    expanded_catalog_addon: Option<ExpandedCatalogAddon>,
 }
 
 impl Default for Ajour {
     fn default() -> Self {
         Self {
             addons: HashMap::new(),
+            game_interfaces: HashMap::new(),
+            dependency_graphs: HashMap::new(),
+            delete_confirmation: None,
+            out_of_date_filter: false,
             addons_scrollable_state: Default::default(),
             config: Config::default(),
             directory_btn_state: Default::default(),
             expanded_addon: None,
+            pending_changelog: None,
+            auto_update_summary: Default::default(),
             is_showing_settings: false,
             needs_update: None,
             new_release_button_state: Default::default(),
             refresh_btn_state: Default::default(),
             settings_btn_state: Default::default(),
-            shared_client: Arc::new(
-                HttpClient::builder()
-                    .redirect_policy(RedirectPolicy::Follow)
-                    .max_connections_per_host(6)
-                    .build()
-                    .unwrap(),
-            ),
             state: AjourState::Loading,
             mode: AjourMode::MyAddons,
             update_all_btn_state: Default::default(),
+            verify_btn_state: Default::default(),
+            rehash_btn_state: Default::default(),
             header_state: Default::default(),
             theme_state: Default::default(),
             fingerprint_collection: Arc::new(Mutex::new(None)),
@@ -178,15 +218,28 @@ impl Default for Ajour {
             catalog_mode_btn_state: Default::default(),
             scale_state: Default::default(),
             backup_state: Default::default(),
+            network_state: Default::default(),
             column_settings: Default::default(),
             onboarding_directory_btn_state: Default::default(),
             catalog: None,
             catalog_search_state: Default::default(),
             catalog_header_state: Default::default(),
+            expanded_catalog_addon: None,
         }
     }
 }
 
+impl Ajour {
+    /// Returns how many addons have an update available, across flavors.
+    fn num_updates(&self) -> usize {
+        self.addons
+            .values()
+            .flatten()
+            .filter(|a| a.state == AddonState::Updatable)
+            .count()
+    }
+}
+
 impl Application for Ajour {
     type Executor = iced::executor::Default;
     type Message = Message;
@@ -195,16 +248,38 @@ impl Application for Ajour {
     fn new(_flags: ()) -> (Self, Command<Message>) {
         let init_commands = vec![
             Command::perform(load_config(), Message::Parse),
-            Command::perform(needs_update(VERSION), Message::NeedsUpdate),
+            Command::perform(
+                async { needs_update(&load_endpoints().await, VERSION).await },
+                Message::NeedsUpdate,
+            ),
             Command::perform(load_user_themes(), Message::ThemesLoaded),
-            Command::perform(get_catalog(), Message::CatalogDownloaded),
+            // The cached catalog is shown right away, while it is refreshed.
+            Command::perform(
+                async {
+                    let config = load_config().await.unwrap_or_default();
+                    load_cached_catalog(&config.resolved_endpoints(), &config.catalog_feeds).await
+                },
+                Message::CatalogCacheLoaded,
+            ),
+            Command::perform(
+                async {
+                    let config = load_config().await.unwrap_or_default();
+                    refresh_catalog(&config.resolved_endpoints(), &config.catalog_feeds).await
+                },
+                Message::CatalogDownloaded,
+            ),
         ];
 
         (Ajour::default(), Command::batch(init_commands))
     }
 
     fn title(&self) -> String {
-        String::from("Ajour")
+        // Pending updates are shown in the title, which shows in the taskbar.
+        match self.num_updates() {
+            0 => String::from("Ajour"),
+            1 => String::from("Ajour (1 update)"),
+            n => format!("Ajour ({} updates)", n),
+        }
     }
 
     fn scale_factor(&self) -> f64 {
@@ -212,7 +287,33 @@ impl Application for Ajour {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        iced_native::subscription::events().map(Message::RuntimeEvent)
+        let runtime_events = iced_native::subscription::events().map(Message::RuntimeEvent);
+
+        // Redraw regularly while downloading, so the progress bars move.
+        let is_downloading = self
+            .addons
+            .values()
+            .flatten()
+            .any(|a| a.state == AddonState::Downloading);
+
+        let mut subscriptions = vec![runtime_events];
+
+        if is_downloading {
+            subscriptions.push(
+                iced_futures::time::every(DOWNLOAD_PROGRESS_INTERVAL)
+                    .map(Message::DownloadProgress),
+            );
+        }
+
+        // Check for addon updates in the background once addons are loaded.
+        if let Some(interval) = self.config.update_check_interval() {
+            if !matches!(self.state, AjourState::Welcome | AjourState::Loading) {
+                subscriptions
+                    .push(iced_futures::time::every(interval).map(Message::CheckForUpdates));
+            }
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
@@ -252,6 +353,8 @@ impl Application for Ajour {
             !&addons.is_empty()
         };
 
+        let num_updates = self.num_updates();
+
         // Menu container at the top of the applications.
         // This has all global buttons, such as Settings, Update All, etc.
         let menu_container = element::menu_container(
@@ -263,6 +366,7 @@ impl Application for Ajour {
             &mut self.catalog_mode_btn_state,
             self.needs_update.as_deref(),
             &mut self.new_release_button_state,
+            num_updates,
         );
 
         let column_config = self.header_state.column_config();
@@ -277,10 +381,12 @@ impl Application for Ajour {
             let settings_container = element::settings_container(
                 color_palette,
                 &mut self.directory_btn_state,
+                &mut self.rehash_btn_state,
                 &cloned_config,
                 &mut self.theme_state,
                 &mut self.scale_state,
                 &mut self.backup_state,
+                &mut self.network_state,
                 &mut self.column_settings,
                 &column_config,
             );
@@ -297,9 +403,22 @@ impl Application for Ajour {
 
         match self.mode {
             AjourMode::MyAddons => {
+                // Interface version of the game client, used to find out of date addons.
+                let game_interface = self.game_interfaces.get(&flavor).copied();
+
+                // Dependency graph, used to resolve the dependencies of the expanded addon.
+                let dependency_graph = self.dependency_graphs.get(&flavor);
+
                 // Get mutable addons for current flavor.
                 let addons = self.addons.entry(flavor).or_default();
 
+                // Folders which would be deleted, shown before the delete is confirmed.
+                let mut pending_delete_plan = self
+                    .delete_confirmation
+                    .as_ref()
+                    .and_then(|id| addons.iter().find(|a| &a.id == id))
+                    .map(|addon| delete_plan(addon, addons));
+
                 // Check if we have any addons.
                 let has_addons = !&addons.is_empty();
 
@@ -308,14 +427,26 @@ impl Application for Ajour {
                     color_palette,
                     &mut self.update_all_btn_state,
                     &mut self.refresh_btn_state,
+                    &mut self.verify_btn_state,
                     &mut self.retail_btn_state,
                     &mut self.classic_btn_state,
                     &self.state,
                     addons,
+                    game_interface,
+                    self.out_of_date_filter,
                     &mut self.config,
                 );
                 content = content.push(menu_addons_container);
 
+                // Summary of the addons which were updated without asking.
+                if self.auto_update_summary.is_finished() {
+                    let auto_update_summary_container = element::auto_update_summary_container(
+                        color_palette,
+                        &mut self.auto_update_summary,
+                    );
+                    content = content.push(auto_update_summary_container);
+                }
+
                 // Addon row titles is a row of titles above the addon scrollable.
                 // This is to add titles above each section of the addon row, to let
                 // the user easily identify what the value is.
@@ -334,19 +465,56 @@ impl Application for Ajour {
                     element::addon_scrollable(color_palette, &mut self.addons_scrollable_state);
 
                 // Loops though the addons.
-                for addon in addons {
+                for addon in addons.iter_mut() {
+                    // Skips addons which are filtered away.
+                    if self.out_of_date_filter && !addon.is_out_of_date(game_interface) {
+                        continue;
+                    }
+
                     // Checks if the current addon is expanded.
                     let is_addon_expanded = match &self.expanded_addon {
                         Some(expanded_addon) => addon.id == expanded_addon.id,
                         None => false,
                     };
 
+                    // Dependencies are only shown for the expanded addon.
+                    let dependencies = dependency_graph
+                        .filter(|_| is_addon_expanded)
+                        .map(|graph| graph.resolve(addon));
+                    let delete_plan = if self.delete_confirmation.as_ref() == Some(&addon.id) {
+                        pending_delete_plan.take()
+                    } else {
+                        None
+                    };
+
+                    let changelog = self
+                        .pending_changelog
+                        .as_ref()
+                        .filter(|c| is_addon_expanded && c.id == addon.id)
+                        .filter(|_| addon.state == AddonState::Updatable);
+
+                    let is_channel_auto_update = cloned_config
+                        .addons
+                        .auto_update_channels
+                        .contains(&addon.release_channel);
+                    let is_auto_update = cloned_config.addons.is_auto_update(
+                        flavor,
+                        &addon.id,
+                        addon.release_channel,
+                    );
+
                     // A container cell which has all data about the current addon.
                     // If the addon is expanded, then this is also included in this container.
                     let addon_data_cell = element::addon_data_cell(
                         color_palette,
                         addon,
                         is_addon_expanded,
+                        game_interface,
+                        dependencies,
+                        delete_plan,
+                        changelog,
+                        is_auto_update,
+                        is_channel_auto_update,
                         &column_config,
                     );
 
@@ -412,7 +580,7 @@ impl Application for Ajour {
                     let source_picklist = PickList::new(
                         &mut self.catalog_search_state.sources_state,
                         &self.catalog_search_state.sources,
-                        Some(self.catalog_search_state.source),
+                        Some(self.catalog_search_state.source.clone()),
                         Interaction::CatalogSourceSelected,
                     )
                     .text_size(14)
@@ -463,6 +631,60 @@ impl Application for Ajour {
                             .height(Length::Fill)
                             .width(Length::FillPortion(1));
 
+                    let release_channel_picklist = PickList::new(
+                        &mut self.catalog_search_state.release_channels_state,
+                        &self.catalog_search_state.release_channels,
+                        Some(self.catalog_search_state.release_channel),
+                        Interaction::CatalogReleaseChannelSelected,
+                    )
+                    .text_size(14)
+                    .width(Length::Fill)
+                    .style(style::SecondaryPickList(color_palette));
+
+                    let release_channel_picklist: Element<Interaction> =
+                        release_channel_picklist.into();
+                    let release_channel_picklist_container =
+                        Container::new(release_channel_picklist.map(Message::Interaction))
+                            .center_y()
+                            .style(style::NormalForegroundContainer(color_palette))
+                            .height(Length::Fill)
+                            .width(Length::FillPortion(1));
+
+                    let both_flavors_checkbox = Checkbox::new(
+                        self.catalog_search_state.install_both_flavors,
+                        "Both flavors",
+                        Interaction::CatalogInstallBothFlavors,
+                    )
+                    .text_size(14)
+                    .spacing(5)
+                    .style(style::DefaultCheckbox(color_palette));
+
+                    let both_flavors_checkbox: Element<Interaction> = both_flavors_checkbox.into();
+                    let both_flavors_checkbox_container =
+                        Container::new(both_flavors_checkbox.map(Message::Interaction))
+                            .center_y()
+                            .padding(5)
+                            .style(style::NormalForegroundContainer(color_palette))
+                            .height(Length::Fill);
+
+                    let not_installed_checkbox = Checkbox::new(
+                        self.catalog_search_state.not_installed_filter,
+                        "Not installed",
+                        Interaction::CatalogNotInstalledFilter,
+                    )
+                    .text_size(14)
+                    .spacing(5)
+                    .style(style::DefaultCheckbox(color_palette));
+
+                    let not_installed_checkbox: Element<Interaction> =
+                        not_installed_checkbox.into();
+                    let not_installed_checkbox_container =
+                        Container::new(not_installed_checkbox.map(Message::Interaction))
+                            .center_y()
+                            .padding(5)
+                            .style(style::NormalForegroundContainer(color_palette))
+                            .height(Length::Fill);
+
                     let catalog_query_row = Row::new()
                         .push(Space::new(Length::Units(DEFAULT_PADDING), Length::Units(0)))
                         .push(catalog_query.map(Message::Interaction))
@@ -470,6 +692,9 @@ impl Application for Ajour {
                         .push(source_picklist_container)
                         .push(category_picklist_container)
                         .push(result_size_picklist_container)
+                        .push(not_installed_checkbox_container)
+                        .push(release_channel_picklist_container)
+                        .push(both_flavors_checkbox_container)
                         .push(Space::new(
                             Length::Units(DEFAULT_PADDING + 5),
                             Length::Units(0),
@@ -495,66 +720,38 @@ impl Application for Ajour {
                         &mut self.catalog_search_state.scrollable_state,
                     );
 
+                    let mut expanded_catalog_addon = self.expanded_catalog_addon.as_mut();
+
                     for addon in self.catalog_search_state.catalog_rows.iter_mut() {
-                        // TODO: We should make this prettier with new sources coming in.
-                        let retail_installed = if flavor == Flavor::Retail {
-                            addons.iter().any(|a| {
-                                a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string())
-                            })
-                        } else {
-                            other_flavor_addons.iter().any(|a| {
-                                a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string())
-                            })
-                        };
-                        let retail_downloading = if flavor == Flavor::Retail {
-                            addons.iter().any(|a| {
-                                (a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string()))
-                                    && a.state == AddonState::Downloading
-                            })
+                        let (retail_addons, classic_addons) = if flavor == Flavor::Retail {
+                            (addons, other_flavor_addons)
                         } else {
-                            other_flavor_addons.iter().any(|a| {
-                                (a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string()))
-                                    && a.state == AddonState::Downloading
-                            })
+                            (other_flavor_addons, addons)
                         };
+                        let retail_status =
+                            CatalogInstallStatus::new(&addon.addon, Flavor::Retail, retail_addons);
+                        let classic_status = CatalogInstallStatus::new(
+                            &addon.addon,
+                            Flavor::Classic,
+                            classic_addons,
+                        );
 
-                        let classic_installed = if flavor == Flavor::Classic {
-                            addons.iter().any(|a| {
-                                a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string())
-                            })
-                        } else {
-                            other_flavor_addons.iter().any(|a| {
-                                a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string())
-                            })
-                        };
-                        let classic_downloading = if flavor == Flavor::Classic {
-                            addons.iter().any(|a| {
-                                (a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string()))
-                                    && a.state == AddonState::Downloading
-                            })
-                        } else {
-                            other_flavor_addons.iter().any(|a| {
-                                (a.curse_id == Some(addon.addon.id)
-                                    || a.tukui_id == Some(addon.addon.id.to_string()))
-                                    && a.state == AddonState::Downloading
-                            })
+                        let expanded = match expanded_catalog_addon.as_ref() {
+                            Some(e) if e.is_addon(&addon.addon.source, addon.addon.id) => {
+                                expanded_catalog_addon.take()
+                            }
+                            _ => None,
                         };
 
                         let catalog_data_cell = element::catalog_data_cell(
                             color_palette,
                             addon,
+                            expanded,
                             &catalog_column_config,
-                            retail_downloading,
-                            retail_installed,
-                            classic_downloading,
-                            classic_installed,
+                            self.catalog_search_state.release_channel,
+                            self.catalog_search_state.install_both_flavors,
+                            retail_status,
+                            classic_status,
                         );
 
                         catalog_scrollable = catalog_scrollable.push(catalog_data_cell);
@@ -661,6 +858,12 @@ pub fn run(opts: Opts) {
     Ajour::run(settings);
 }
 
+/// Loads the API endpoints before the config is parsed by the application.
+/// Falls back to the environment and command line overrides if the config can't be loaded.
+async fn load_endpoints() -> Endpoints {
+    load_config().await.unwrap_or_default().resolved_endpoints()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum DirectoryType {
     Wow,
@@ -676,6 +879,7 @@ pub enum ColumnKey {
     Channel,
     Author,
     GameVersion,
+    Interface,
 }
 
 impl ColumnKey {
@@ -690,6 +894,7 @@ impl ColumnKey {
             Channel => "Channel",
             Author => "Author",
             GameVersion => "Game Version",
+            Interface => "Interface",
         };
 
         title.to_string()
@@ -706,6 +911,7 @@ impl ColumnKey {
             Channel => "channel",
             Author => "author",
             GameVersion => "game_version",
+            Interface => "interface",
         };
 
         s.to_string()
@@ -722,6 +928,7 @@ impl From<&str> for ColumnKey {
             "channel" => ColumnKey::Channel,
             "author" => ColumnKey::Author,
             "game_version" => ColumnKey::GameVersion,
+            "interface" => ColumnKey::Interface,
             _ => panic!(format!("Unknown ColumnKey for {}", s)),
         }
     }
@@ -814,6 +1021,13 @@ impl Default for HeaderState {
                     hidden: true,
                     order: 6,
                 },
+                ColumnState {
+                    key: ColumnKey::Interface,
+                    btn_state: Default::default(),
+                    width: Length::Units(110),
+                    hidden: true,
+                    order: 7,
+                },
             ],
         }
     }
@@ -896,6 +1110,12 @@ impl Default for ColumnSettings {
                     up_btn_state: Default::default(),
                     down_btn_state: Default::default(),
                 },
+                ColumnSettingState {
+                    key: ColumnKey::Interface,
+                    order: 7,
+                    up_btn_state: Default::default(),
+                    down_btn_state: Default::default(),
+                },
             ],
         }
     }
@@ -927,8 +1147,8 @@ impl CatalogColumnKey {
             Description => "Description",
             Source => "Source",
             NumDownloads => "# Downloads",
-            CatalogColumnKey::InstallRetail => "",
-            CatalogColumnKey::InstallClassic => "",
+            CatalogColumnKey::InstallRetail => "Retail",
+            CatalogColumnKey::InstallClassic => "Classic",
         };
 
         title.to_string()
@@ -1007,12 +1227,12 @@ impl Default for CatalogHeaderState {
                 CatalogColumnState {
                     key: CatalogColumnKey::InstallRetail,
                     btn_state: Default::default(),
-                    width: Length::Units(85),
+                    width: Length::Units(115),
                 },
                 CatalogColumnState {
                     key: CatalogColumnKey::InstallClassic,
                     btn_state: Default::default(),
-                    width: Length::Units(85),
+                    width: Length::Units(115),
                 },
             ],
         }
@@ -1059,6 +1279,14 @@ pub struct CatalogSearchState {
     pub source: CatalogSource,
     pub sources: Vec<CatalogSource>,
     pub sources_state: pick_list::State<CatalogSource>,
+    /// Release channel addons are installed with from the catalog.
+    pub release_channel: ReleaseChannel,
+    pub release_channels: Vec<ReleaseChannel>,
+    pub release_channels_state: pick_list::State<ReleaseChannel>,
+    /// Installs addons for both Retail and Classic, if they support both.
+    pub install_both_flavors: bool,
+    /// Only shows addons which aren't installed for the current flavor.
+    pub not_installed_filter: bool,
 }
 
 impl Default for CatalogSearchState {
@@ -1080,28 +1308,185 @@ impl Default for CatalogSearchState {
             source: CatalogSource::All,
             sources: CatalogSource::all(),
             sources_state: Default::default(),
+            release_channel: ReleaseChannel::Stable,
+            release_channels: ReleaseChannel::ALL.to_vec(),
+            release_channels_state: Default::default(),
+            install_both_flavors: false,
+            not_installed_filter: false,
         }
     }
 }
 
 pub struct CatalogRow {
-    website_state: button::State,
+    details_btn_state: button::State,
     retail_install_state: button::State,
     classic_install_state: button::State,
+    source_state: pick_list::State<catalog::Source>,
+    sources: Vec<catalog::Source>,
     addon: CatalogAddon,
 }
 
 impl From<CatalogAddon> for CatalogRow {
     fn from(addon: CatalogAddon) -> Self {
         Self {
-            website_state: Default::default(),
+            details_btn_state: Default::default(),
             retail_install_state: Default::default(),
             classic_install_state: Default::default(),
+            source_state: Default::default(),
+            sources: addon.sources(),
             addon,
         }
     }
 }
 
+/// Whether a catalog addon is installed for a flavor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CatalogInstallStatus {
+    /// The addon doesn't support the flavor.
+    Unavailable,
+    NotInstalled,
+    Downloading,
+    /// Installed with a newer version available. Holds the id of the installed addon.
+    Updatable(String),
+    Installed,
+}
+
+impl CatalogInstallStatus {
+    fn title(&self) -> &'static str {
+        match self {
+            CatalogInstallStatus::Unavailable => "N/A",
+            CatalogInstallStatus::NotInstalled => "Install",
+            CatalogInstallStatus::Downloading => "Downloading",
+            CatalogInstallStatus::Updatable(_) => "Update available",
+            CatalogInstallStatus::Installed => "Installed",
+        }
+    }
+
+    fn new(catalog_addon: &CatalogAddon, flavor: Flavor, addons: &[Addon]) -> Self {
+        match addons.iter().find(|a| catalog_addon.is_installed_as(a)) {
+            Some(addon) => match addon.state {
+                AddonState::Downloading | AddonState::Unpacking | AddonState::Fingerprint => {
+                    CatalogInstallStatus::Downloading
+                }
+                AddonState::Updatable => CatalogInstallStatus::Updatable(addon.id.clone()),
+                _ => CatalogInstallStatus::Installed,
+            },
+            None if catalog_addon.flavors.contains(&flavor) => CatalogInstallStatus::NotInstalled,
+            None => CatalogInstallStatus::Unavailable,
+        }
+    }
+}
+
+/// Changelog of the version an addon would be updated to.
+pub struct PendingChangelog {
+    id: String,
+    version: String,
+    state: ChangelogState,
+}
+
+pub enum ChangelogState {
+    Loading,
+    Loaded(Option<String>),
+    Failed(String),
+}
+
+/// Addons which were updated without asking, shown once all of them are done.
+#[derive(Debug, Default)]
+pub struct AutoUpdateSummary {
+    /// Addons still being updated, with the label shown for them in the summary.
+    pending: Vec<(Flavor, String, String)>,
+    updated: Vec<String>,
+    failed: Vec<String>,
+    dismiss_btn_state: button::State,
+}
+
+impl AutoUpdateSummary {
+    /// Moves the pending addon `id` to the updated or failed addons.
+    fn complete(&mut self, flavor: Flavor, id: &str, is_updated: bool) {
+        let idx = self
+            .pending
+            .iter()
+            .position(|(f, i, _)| *f == flavor && i == id);
+
+        if let Some(idx) = idx {
+            let (_, _, label) = self.pending.remove(idx);
+
+            if is_updated {
+                self.updated.push(label);
+            } else {
+                self.failed.push(label);
+            }
+        }
+    }
+
+    /// Returns true if no auto update is running and there is something to show.
+    fn is_finished(&self) -> bool {
+        self.pending.is_empty() && !(self.updated.is_empty() && self.failed.is_empty())
+    }
+}
+
+/// The catalog addon shown with its details.
+pub struct ExpandedCatalogAddon {
+    source: catalog::Source,
+    id: u32,
+    details: CatalogDetails,
+    /// Names of the dependencies, looked up in the catalog.
+    required_dependencies: Vec<String>,
+    optional_dependencies: Vec<String>,
+    website_btn_state: button::State,
+    screenshot_btn_states: Vec<button::State>,
+    /// One for each file in the details.
+    file_btn_states: Vec<button::State>,
+}
+
+pub enum CatalogDetails {
+    Loading,
+    Loaded(CatalogAddonDetails),
+    Failed(String),
+}
+
+impl ExpandedCatalogAddon {
+    fn new(source: catalog::Source, id: u32) -> Self {
+        ExpandedCatalogAddon {
+            source,
+            id,
+            details: CatalogDetails::Loading,
+            required_dependencies: vec![],
+            optional_dependencies: vec![],
+            website_btn_state: Default::default(),
+            screenshot_btn_states: vec![],
+            file_btn_states: vec![],
+        }
+    }
+
+    fn is_addon(&self, source: &catalog::Source, id: u32) -> bool {
+        &self.source == source && self.id == id
+    }
+
+    fn set_details(&mut self, details: CatalogAddonDetails, catalog: Option<&Catalog>) {
+        for dependency in &details.dependencies {
+            let name = catalog
+                .and_then(|c| c.find(&dependency.source, dependency.id))
+                .map(|a| a.name.clone())
+                .unwrap_or_else(|| format!("#{}", dependency.id));
+
+            if dependency.required {
+                self.required_dependencies.push(name);
+            } else {
+                self.optional_dependencies.push(name);
+            }
+        }
+
+        self.screenshot_btn_states = details
+            .screenshots
+            .iter()
+            .map(|_| Default::default())
+            .collect();
+        self.file_btn_states = details.files.iter().map(|_| Default::default()).collect();
+        self.details = CatalogDetails::Loaded(details);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum CatalogCategory {
     All,
@@ -1192,7 +1577,7 @@ impl std::fmt::Display for CatalogFlavor {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum CatalogSource {
     All,
     Choice(catalog::Source),
@@ -1203,8 +1588,8 @@ impl CatalogSource {
         vec![
             CatalogSource::All,
             CatalogSource::Choice(catalog::Source::Curse),
-            // FIXME: Uncomment once Tukui catalog is enabled
-            //CatalogSource::Choice(catalog::Source::Tukui),
+            CatalogSource::Choice(catalog::Source::Tukui),
+            CatalogSource::Choice(catalog::Source::WowI),
         ]
     }
 }
@@ -1213,10 +1598,7 @@ impl std::fmt::Display for CatalogSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             CatalogSource::All => "All Sources",
-            CatalogSource::Choice(source) => match source {
-                catalog::Source::Curse => "Curse",
-                catalog::Source::Tukui => "Tukui",
-            },
+            CatalogSource::Choice(source) => return write!(f, "{}", source),
         };
         write!(f, "{}", s)
     }
@@ -1275,3 +1657,65 @@ pub struct BackupState {
     directory_btn_state: button::State,
     backup_now_btn_state: button::State,
 }
+
+/// The network settings as they are typed in settings, until they are applied.
+#[derive(Default)]
+pub struct NetworkState {
+    proxy: String,
+    proxy_username: String,
+    proxy_password: String,
+    no_proxy: String,
+    ca_roots: String,
+    proxy_input_state: text_input::State,
+    proxy_username_input_state: text_input::State,
+    proxy_password_input_state: text_input::State,
+    no_proxy_input_state: text_input::State,
+    ca_roots_input_state: text_input::State,
+    apply_btn_state: button::State,
+}
+
+impl NetworkState {
+    fn load(&mut self, network: &Network) {
+        self.proxy = network.proxy.clone().unwrap_or_default();
+        self.proxy_username = network.proxy_username.clone().unwrap_or_default();
+        self.proxy_password = network.proxy_password.clone().unwrap_or_default();
+        self.no_proxy = network.no_proxy.join(",");
+        self.ca_roots = network
+            .ca_roots
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+    }
+
+    fn set(&mut self, field: NetworkField, value: String) {
+        match field {
+            NetworkField::Proxy => self.proxy = value,
+            NetworkField::ProxyUsername => self.proxy_username = value,
+            NetworkField::ProxyPassword => self.proxy_password = value,
+            NetworkField::NoProxy => self.no_proxy = value,
+            NetworkField::CaRoots => self.ca_roots = value,
+        }
+    }
+
+    fn network(&self) -> Network {
+        let optional = |value: &str| Some(value.trim().to_owned()).filter(|v| !v.is_empty());
+
+        Network {
+            proxy: optional(&self.proxy),
+            proxy_username: optional(&self.proxy_username),
+            // Passwords may start or end with a space.
+            proxy_password: Some(self.proxy_password.clone()).filter(|v| !v.is_empty()),
+            no_proxy: self.no_proxy.split(',').filter_map(optional).collect(),
+            ca_roots: optional(&self.ca_roots).map(PathBuf::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkField {
+    Proxy,
+    ProxyUsername,
+    ProxyPassword,
+    NoProxy,
+    CaRoots,
+}
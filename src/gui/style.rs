@@ -1,5 +1,7 @@
 use ajour_core::theme::ColorPalette;
-use iced::{button, checkbox, container, pick_list, scrollable, text_input, Background, Color};
+use iced::{
+    button, checkbox, container, pick_list, progress_bar, scrollable, text_input, Background, Color,
+};
 
 pub struct BrightForegroundContainer(pub ColorPalette);
 impl container::StyleSheet for BrightForegroundContainer {
@@ -555,6 +557,17 @@ impl container::StyleSheet for ChannelBadge {
     }
 }
 
+pub struct DownloadProgressBar(pub ColorPalette);
+impl progress_bar::StyleSheet for DownloadProgressBar {
+    fn style(&self) -> progress_bar::Style {
+        progress_bar::Style {
+            background: Background::Color(self.0.base.background),
+            bar: Background::Color(self.0.normal.primary),
+            border_radius: 2,
+        }
+    }
+}
+
 pub struct DefaultCheckbox(pub ColorPalette);
 impl checkbox::StyleSheet for DefaultCheckbox {
     fn active(&self, _is_checked: bool) -> checkbox::Style {
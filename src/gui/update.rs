@@ -1,24 +1,37 @@
 use {
     super::{
-        Ajour, AjourMode, AjourState, CatalogCategory, CatalogColumnKey, CatalogFlavor, CatalogRow,
-        CatalogSource, ColumnKey, DirectoryType, Interaction, Message, SortDirection,
+        Ajour, AjourMode, AjourState, CatalogCategory, CatalogColumnKey, CatalogDetails,
+        CatalogFlavor, CatalogRow, CatalogSource, ChangelogState, ColumnKey, DirectoryType,
+        ExpandedCatalogAddon, Interaction, Message, PendingChangelog, SortDirection,
     },
     ajour_core::{
-        addon::{Addon, AddonState},
-        backup::{backup_folders, latest_backup, BackupFolder},
-        catalog,
-        config::{load_config, ColumnConfig, ColumnConfigV2, Flavor},
+        addon::{Addon, AddonState, ReleaseChannel, RemotePackage},
+        backup::{backup_folders, latest_backup, wow_backup_folders},
+        catalog::{
+            self, addon_from_file, fetch_addon_details, load_cached_catalog, Catalog, CatalogAddon,
+            CatalogFile,
+        },
+        changelog::fetch_changelog,
+        config::{
+            load_config, ColumnConfig, ColumnConfigV2, CustomFeed, CustomFeedId, Endpoints, Flavor,
+        },
         curse_api,
+        dependency::{delete_plan, find_catalog_addon, DependencyGraph},
+        error::ClientError,
         fs::{delete_addons, install_addon, PersistentData},
-        network::download_addon,
-        parse::{read_addon_directory, update_addon_fingerprint, FingerprintCollection},
+        network::{configure_client, download_addon, download_addon_package},
+        parse::{
+            clear_fingerprint_collection, read_addon_directory, refresh_remote_packages,
+            update_addon_fingerprint, FingerprintCollection,
+        },
+        search::{search, Query},
         tukui_api,
-        utility::wow_path_resolution,
-        Result,
+        utility::{game_interface, wow_path_resolution},
+        verify::{verify_addons, AddonIntegrity},
+        wowi_api, Result,
     },
     async_std::sync::{Arc, Mutex},
     iced::{Command, Length},
-    isahc::HttpClient,
     native_dialog::*,
     std::collections::{HashMap, HashSet},
     std::path::{Path, PathBuf},
@@ -34,6 +47,7 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             // When we have the config, we parse the addon directory
             // which is provided by the config.
             ajour.config = config;
+            ajour.network_state.load(&ajour.config.network);
 
             // Set column widths from the config
             match &ajour.config.column_config {
@@ -195,6 +209,9 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                     commands.push(Command::perform(
                         perform_read_addon_directory(
                             ajour.fingerprint_collection.clone(),
+                            ajour.config.resolved_endpoints(),
+                            ajour.config.catalog_feeds.custom.clone(),
+                            ajour.config.custom_feed_ids(*flavor),
                             addon_directory.clone(),
                             *flavor,
                         ),
@@ -226,6 +243,39 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
 
             return Ok(Command::perform(load_config(), Message::Parse));
         }
+        Message::Interaction(Interaction::Rehash) => {
+            log::debug!("Interaction::Rehash");
+
+            // Close details if shown.
+            ajour.expanded_addon = None;
+
+            // Prepare state for loading.
+            ajour.state = AjourState::Loading;
+
+            return Ok(Command::perform(
+                clear_fingerprint_collection(ajour.fingerprint_collection.clone()),
+                Message::FingerprintCollectionCleared,
+            ));
+        }
+        Message::FingerprintCollectionCleared(result) => {
+            log::debug!(
+                "Message::FingerprintCollectionCleared(error: {})",
+                result.is_err()
+            );
+
+            result?;
+
+            // Cleans the addons.
+            ajour.addons = HashMap::new();
+
+            // Addons are parsed, and thereby fingerprinted, again from scratch.
+            return Ok(Command::perform(load_config(), Message::Parse));
+        }
+        Message::Interaction(Interaction::OutOfDateFilter(is_checked)) => {
+            log::debug!("Interaction::OutOfDateFilter({})", is_checked);
+
+            ajour.out_of_date_filter = is_checked;
+        }
         Message::Interaction(Interaction::Settings) => {
             log::debug!("Interaction::Settings");
 
@@ -365,6 +415,9 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             // Close settings if shown.
             ajour.is_showing_settings = false;
 
+            // Any pending delete confirmation belongs to the previously expanded addon.
+            ajour.delete_confirmation = None;
+
             // Expand a addon. If it's already expanded, we collapse it again.
             let flavor = ajour.config.wow.flavor;
             let addons = ajour.addons.entry(flavor).or_default();
@@ -379,6 +432,10 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                 }
 
                 ajour.expanded_addon = Some(addon.clone());
+
+                // Fetch what changed if there is an update available.
+                let addon = addon.clone();
+                return Ok(fetch_pending_changelog(ajour, flavor, addon));
             }
         }
         Message::Interaction(Interaction::Delete(id)) => {
@@ -386,28 +443,70 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
 
             // Close settings if shown.
             ajour.is_showing_settings = false;
-            // Close details if shown.
-            ajour.expanded_addon = None;
 
             let flavor = ajour.config.wow.flavor;
             let addons = ajour.addons.entry(flavor).or_default();
 
             if let Some(addon) = addons.iter().find(|a| a.id == id).cloned() {
+                // First press previews which folders are removed, second press deletes.
+                if ajour.delete_confirmation.as_ref() != Some(&addon.id) {
+                    ajour.delete_confirmation = Some(addon.id);
+                    return Ok(Command::none());
+                }
+
+                // Close details if shown.
+                ajour.expanded_addon = None;
+                ajour.delete_confirmation = None;
+
                 let addon_directory = ajour
                     .config
                     .get_addon_directory_for_flavor(&flavor)
                     .expect("has to have addon directory");
 
+                // Folders shipped with other installed addons are kept.
+                let delete_plan = delete_plan(&addon, addons);
+                if !delete_plan.keep.is_empty() {
+                    log::debug!(
+                        "keeping folders shipped with other addons: {}",
+                        delete_plan.keep.join(", ")
+                    );
+                }
+
                 // Remove from local state.
                 addons.retain(|a| a.id != addon.id);
 
-                // Foldernames to the addons which is to be deleted.
-                let mut addons_to_be_deleted = [&addon.dependencies[..], &[addon.id]].concat();
-                addons_to_be_deleted.dedup();
-
                 // Delete addon(s) from disk.
-                let _ = delete_addons(&addon_directory, &addons_to_be_deleted);
+                let _ = delete_addons(&addon_directory, &delete_plan.remove);
+
+                // Forget the custom feed the addon was installed from.
+                if let Some(custom_feed_ids) = ajour.config.addons.custom_feeds.get_mut(&flavor) {
+                    if custom_feed_ids.remove(&addon.id).is_some() {
+                        let _ = ajour.config.save();
+                    }
+                }
+
+                return Ok(refresh_dependency_graph(ajour, flavor));
+            }
+        }
+        Message::Interaction(Interaction::NetworkSettingChanged(field, value)) => {
+            // The value is left out, since it can be a password.
+            log::debug!("Interaction::NetworkSettingChanged({:?})", field);
+
+            ajour.network_state.set(field, value);
+        }
+        Message::Interaction(Interaction::ApplyNetworkSettings) => {
+            log::debug!("Interaction::ApplyNetworkSettings");
+
+            let network = ajour.network_state.network();
+            let previous = std::mem::replace(&mut ajour.config.network, network);
+
+            // Settings which can't be applied are not saved.
+            if let Err(e) = configure_client(&ajour.config.resolved_network()) {
+                ajour.config.network = previous;
+                return Err(e);
             }
+
+            let _ = ajour.config.save();
         }
         Message::Interaction(Interaction::Update(id)) => {
             log::debug!("Interaction::Update({})", &id);
@@ -418,64 +517,158 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             ajour.expanded_addon = None;
 
             let flavor = ajour.config.wow.flavor;
-            let addons = ajour.addons.entry(flavor).or_default();
-            let to_directory = ajour
+            if let Some(command) = update_addon(ajour, flavor, &id) {
+                return Ok(command);
+            }
+        }
+        Message::Interaction(Interaction::CatalogUpdate(flavor, id)) => {
+            log::debug!("Interaction::CatalogUpdate({}, {})", flavor, &id);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            if let Some(command) = update_addon(ajour, flavor, &id) {
+                return Ok(command);
+            }
+        }
+        Message::Interaction(Interaction::UpdateAll) => {
+            log::debug!("Interaction::UpdateAll");
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+            // Close details if shown.
+            ajour.expanded_addon = None;
+
+            // Update all updatable addons, expect ignored.
+            let flavor = ajour.config.wow.flavor;
+            let commands = download_updates(ajour, flavor, |_| true);
+
+            return Ok(Command::batch(commands));
+        }
+        Message::Interaction(Interaction::Repair(id)) => {
+            log::debug!("Interaction::Repair({})", &id);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+            // Close details if shown.
+            ajour.expanded_addon = None;
+
+            let flavor = ajour.config.wow.flavor;
+            let temp_directory = ajour
                 .config
                 .get_temporary_addon_directory(flavor)
                 .expect("Expected a valid path");
-            for addon in addons.iter_mut() {
-                if addon.id == id {
+            let addon_directory = ajour
+                .config
+                .get_addon_directory_for_flavor(&flavor)
+                .expect("Expected a valid path");
+            let addons = ajour.addons.entry(flavor).or_default();
+            if let Some(addon) = addons.iter_mut().find(|a| a.id == id) {
+                if let Some(package) = addon.repair_package.clone() {
                     addon.state = AddonState::Downloading;
                     return Ok(Command::perform(
-                        perform_download_addon(
-                            ajour.shared_client.clone(),
+                        perform_repair_addon(
                             flavor,
                             addon.clone(),
-                            to_directory,
+                            package,
+                            temp_directory,
+                            addon_directory,
                         ),
-                        Message::DownloadedAddon,
+                        Message::RepairedAddon,
                     ));
                 }
             }
         }
-        Message::Interaction(Interaction::UpdateAll) => {
-            log::debug!("Interaction::UpdateAll");
+        Message::Interaction(Interaction::Verify) => {
+            log::debug!("Interaction::Verify");
 
             // Close settings if shown.
             ajour.is_showing_settings = false;
             // Close details if shown.
             ajour.expanded_addon = None;
 
-            // Update all updatable addons, expect ignored.
+            // Verify all addons which can be verified, expect ignored.
             let flavor = ajour.config.wow.flavor;
+            let addon_directory = ajour
+                .config
+                .get_addon_directory_for_flavor(&flavor)
+                .expect("Expected a valid path");
             let ignored_ids = ajour.config.addons.ignored.entry(flavor).or_default();
-            let mut addons: Vec<_> = ajour
+            let addons: Vec<_> = ajour
                 .addons
                 .entry(flavor)
                 .or_default()
                 .iter_mut()
                 .filter(|a| !ignored_ids.iter().any(|i| i == &a.id))
+                .filter(|a| a.curse_id.is_some() && a.file_id.is_some())
+                .map(|a| {
+                    a.state = AddonState::Fingerprint;
+                    a.clone()
+                })
                 .collect();
 
-            let mut commands = vec![];
-            for addon in addons.iter_mut() {
-                if addon.state == AddonState::Updatable {
-                    if let Some(to_directory) = ajour.config.get_temporary_addon_directory(flavor) {
-                        addon.state = AddonState::Downloading;
-                        let addon = addon.clone();
-                        commands.push(Command::perform(
-                            perform_download_addon(
-                                ajour.shared_client.clone(),
-                                flavor,
-                                addon,
-                                to_directory,
-                            ),
-                            Message::DownloadedAddon,
-                        ))
+            return Ok(Command::perform(
+                perform_verify_addons(
+                    ajour.config.resolved_endpoints(),
+                    flavor,
+                    addon_directory,
+                    addons,
+                ),
+                Message::VerifiedAddons,
+            ));
+        }
+        Message::VerifiedAddons((flavor, Ok(results))) => {
+            log::debug!(
+                "Message::VerifiedAddons(({}, {} verified))",
+                flavor,
+                results.len()
+            );
+
+            let addons = ajour.addons.entry(flavor).or_default();
+            for integrity in results {
+                if let Some(addon) = addons.iter_mut().find(|a| a.id == integrity.addon_id) {
+                    addon.extra_files = integrity.extra.clone();
+
+                    if integrity.is_intact() {
+                        addon.modified_modules.clear();
+
+                        addon.state = AddonState::Ajour(Some("Verified".to_owned()));
+                        if let Some(package) = addon.relevant_release_package() {
+                            if addon.is_updatable(package) {
+                                addon.state = AddonState::Updatable;
+                            }
+                        }
+                    } else {
+                        addon.modified_modules = integrity.damaged_modules();
+                        addon.repair_package = Some(integrity.package);
+                        addon.state = AddonState::Modified;
                     }
                 }
             }
-            return Ok(Command::batch(commands));
+
+            // Addons we couldn't verify goes back to idle.
+            for addon in addons.iter_mut() {
+                if addon.state == AddonState::Fingerprint {
+                    addon.state = AddonState::Ajour(None);
+                }
+            }
+        }
+        Message::VerifiedAddons((flavor, Err(error))) => {
+            log::error!("Message::VerifiedAddons(({}, error: {}))", flavor, &error);
+
+            let addons = ajour.addons.entry(flavor).or_default();
+            for addon in addons.iter_mut() {
+                if addon.state == AddonState::Fingerprint {
+                    addon.state = AddonState::Ajour(Some("Error".to_owned()));
+                }
+            }
+
+            ajour.state = AjourState::Error(error);
+        }
+        Message::DependencyGraphRead((flavor, graph)) => {
+            log::debug!("Message::DependencyGraphRead({})", flavor);
+
+            ajour.dependency_graphs.insert(flavor, graph);
         }
         Message::ParsedAddons((flavor, result)) => {
             // if our selected flavor returns (either ok or error) - we change to idle.
@@ -512,6 +705,11 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                             }
                         }
 
+                        // Check if local files differs from the matched remote file.
+                        if a.is_modified() {
+                            a.state = AddonState::Modified;
+                        }
+
                         // Check if addon is updatable based on release channel.
                         if let Some(package) = a.relevant_release_package() {
                             if a.is_updatable(package) {
@@ -537,8 +735,31 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                     ajour.state = AjourState::Idle;
                 }
 
+                // Game interface is used to find out of date addons.
+                match ajour
+                    .config
+                    .wow
+                    .directory
+                    .as_ref()
+                    .and_then(|dir| game_interface(dir, flavor))
+                {
+                    Some(interface) => {
+                        ajour.game_interfaces.insert(flavor, interface);
+                    }
+                    None => {
+                        ajour.game_interfaces.remove(&flavor);
+                    }
+                }
+
                 // Insert the addons into the HashMap.
                 ajour.addons.insert(flavor, addons);
+
+                let graph_command = refresh_dependency_graph(ajour, flavor);
+
+                return Ok(Command::batch(vec![
+                    graph_command,
+                    auto_update_addons(ajour, flavor),
+                ]));
             } else {
                 log::error!(
                     "Message::ParsedAddons({}) - {}",
@@ -579,16 +800,39 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                 result.is_err()
             );
 
+            ajour
+                .auto_update_summary
+                .complete(flavor, &id, result.is_ok());
+
             let addons = ajour.addons.entry(flavor).or_default();
             if let Some(addon) = addons.iter_mut().find(|a| a.id == id) {
                 match result {
-                    Ok(_) => {
+                    Ok(folders) => {
                         addon.state = AddonState::Fingerprint;
 
                         if let Some(package) = addon.relevant_release_package() {
                             addon.version = Some(package.version.clone());
                         }
 
+                        // Addons from a custom feed have no id in their folder, so the
+                        // folder is saved to find them when the addon directory is read.
+                        if let Some(custom_feed_id) = addon.custom_feed_id.clone() {
+                            if let Some((folder, bundled)) = custom_feed_folders(addon, &folders) {
+                                addon.path.set_file_name(&folder);
+                                addon.id = folder.clone();
+                                addon.bundled_modules = bundled;
+
+                                ajour
+                                    .config
+                                    .addons
+                                    .custom_feeds
+                                    .entry(flavor)
+                                    .or_default()
+                                    .insert(folder, custom_feed_id);
+                                let _ = ajour.config.save();
+                            }
+                        }
+
                         let mut commands = vec![];
                         commands.push(Command::perform(
                             perform_hash_addon(
@@ -598,27 +842,85 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                                     .expect("Expected a valid path"),
                                 addon.id.clone(),
                                 ajour.fingerprint_collection.clone(),
+                                ajour.config.resolved_endpoints(),
                                 flavor,
                             ),
                             Message::UpdateFingerprint,
                         ));
 
-                        for dep in &addon.dependencies {
-                            if dep != &addon.id {
-                                commands.push(Command::perform(
+                        for module in &addon.bundled_modules {
+                            commands.push(Command::perform(
+                                perform_hash_addon(
+                                    ajour
+                                        .config
+                                        .get_addon_directory_for_flavor(&flavor)
+                                        .expect("Expected a valid path"),
+                                    module.clone(),
+                                    ajour.fingerprint_collection.clone(),
+                                    ajour.config.resolved_endpoints(),
+                                    flavor,
+                                ),
+                                Message::UpdateFingerprint,
+                            ));
+                        }
+
+                        // New folders might satisfy, or add, dependencies.
+                        commands.push(refresh_dependency_graph(ajour, flavor));
+
+                        return Ok(Command::batch(commands));
+                    }
+                    Err(err) => {
+                        ajour.state = AjourState::Error(err);
+                        addon.state = AddonState::Ajour(Some("Error".to_owned()));
+                    }
+                }
+            }
+        }
+        Message::RepairedAddon((flavor, id, result)) => {
+            log::debug!(
+                "Message::RepairedAddon(({}, {}, error: {}))",
+                flavor,
+                &id,
+                result.is_err()
+            );
+
+            let addon_directory = ajour
+                .config
+                .get_addon_directory_for_flavor(&flavor)
+                .expect("Expected a valid path");
+            let addons = ajour.addons.entry(flavor).or_default();
+            if let Some(addon) = addons.iter_mut().find(|a| a.id == id) {
+                match result {
+                    Ok(_) => {
+                        addon.state = AddonState::Fingerprint;
+                        addon.modified_modules.clear();
+                        // Reinstalling replaces the module folders, extra files included.
+                        addon.extra_files.clear();
+
+                        if let Some(package) = &addon.repair_package {
+                            addon.version = Some(package.version.clone());
+                        }
+
+                        // Rehash the addon and every module we reinstalled.
+                        let folders = addon.folders();
+
+                        let fingerprint_collection = ajour.fingerprint_collection.clone();
+                        let endpoints = ajour.config.resolved_endpoints();
+                        let commands: Vec<_> = folders
+                            .into_iter()
+                            .map(|folder| {
+                                Command::perform(
                                     perform_hash_addon(
-                                        ajour
-                                            .config
-                                            .get_addon_directory_for_flavor(&flavor)
-                                            .expect("Expected a valid path"),
-                                        dep.clone(),
-                                        ajour.fingerprint_collection.clone(),
+                                        addon_directory.clone(),
+                                        folder,
+                                        fingerprint_collection.clone(),
+                                        endpoints.clone(),
                                         flavor,
                                     ),
                                     Message::UpdateFingerprint,
-                                ));
-                            }
-                        }
+                                )
+                            })
+                            .collect();
 
                         return Ok(Command::batch(commands));
                     }
@@ -757,9 +1059,30 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
 
                     // Persist the newly updated config.
                     let _ = &ajour.config.save();
+
+                    // The changelog depends on the release channel.
+                    let addon = addon.clone();
+                    return Ok(fetch_pending_changelog(ajour, flavor, addon));
                 }
             };
         }
+        Message::ChangelogFetched((id, version, result)) => {
+            log::debug!("Message::ChangelogFetched({}, {})", &id, &version);
+
+            // Only keep the changelog if the update is still the same.
+            if let Some(changelog) = ajour.pending_changelog.as_mut() {
+                if changelog.id == id && changelog.version == version {
+                    changelog.state = match result {
+                        Ok(text) => ChangelogState::Loaded(text),
+                        Err(error) => {
+                            log::error!("{}", error);
+
+                            ChangelogState::Failed(error.to_string())
+                        }
+                    };
+                }
+            }
+        }
         Message::ThemeSelected(theme_name) => {
             log::debug!("Message::ThemeSelected({:?})", &theme_name);
 
@@ -881,27 +1204,11 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             // app is processing the backup. We will unflag this on completion.
             ajour.backup_state.backing_up = true;
 
-            let mut src_folders = vec![];
-
-            // Shouldn't panic since button is only clickable if wow directory is chosen
-            let wow_dir = ajour.config.wow.directory.as_ref().unwrap();
-
             // Shouldn't panic since button is only shown if backup directory is chosen
             let dest = ajour.config.backup_directory.as_ref().unwrap();
 
             // Backup WTF & AddOn directories for both flavors if they exist
-            for flavor in Flavor::ALL.iter() {
-                let addon_dir = ajour.config.get_addon_directory_for_flavor(flavor).unwrap();
-                let wtf_dir = ajour.config.get_wtf_directory_for_flavor(flavor).unwrap();
-
-                if addon_dir.exists() {
-                    src_folders.push(BackupFolder::new(&addon_dir, wow_dir));
-                }
-
-                if wtf_dir.exists() {
-                    src_folders.push(BackupFolder::new(&wtf_dir, wow_dir));
-                }
-            }
+            let src_folders = wow_backup_folders(&ajour.config);
 
             return Ok(Command::perform(
                 backup_folders(src_folders, dest.to_owned()),
@@ -947,6 +1254,62 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             // Persist changes to config
             save_column_configs(ajour);
         }
+        Message::Interaction(Interaction::ToggleAutoUpdate(id, is_checked)) => {
+            log::debug!("Interaction::ToggleAutoUpdate({}, {})", &id, is_checked);
+
+            let flavor = ajour.config.wow.flavor;
+
+            // Addons following an auto update channel can't be turned off one by one.
+            let auto_update_channels = &ajour.config.addons.auto_update_channels;
+            let follows_channel = ajour
+                .addons
+                .get(&flavor)
+                .and_then(|addons| addons.iter().find(|a| a.id == id))
+                .map(|addon| auto_update_channels.contains(&addon.release_channel))
+                .unwrap_or_default();
+            if follows_channel {
+                return Ok(Command::none());
+            }
+
+            let auto_update = ajour.config.addons.auto_update.entry(flavor).or_default();
+            auto_update.retain(|i| i != &id);
+            if is_checked {
+                auto_update.push(id);
+            }
+
+            let _ = ajour.config.save();
+
+            return Ok(auto_update_addons(ajour, flavor));
+        }
+        Message::Interaction(Interaction::ToggleAutoUpdateChannel(channel, is_checked)) => {
+            log::debug!(
+                "Interaction::ToggleAutoUpdateChannel({}, {})",
+                channel,
+                is_checked
+            );
+
+            let auto_update_channels = &mut ajour.config.addons.auto_update_channels;
+            auto_update_channels.retain(|c| *c != channel);
+            if is_checked {
+                auto_update_channels.push(channel);
+            }
+
+            let _ = ajour.config.save();
+
+            let flavors: Vec<_> = ajour.addons.keys().copied().collect();
+            let commands: Vec<_> = flavors
+                .into_iter()
+                .map(|flavor| auto_update_addons(ajour, flavor))
+                .collect();
+
+            return Ok(Command::batch(commands));
+        }
+        Message::Interaction(Interaction::DismissAutoUpdateSummary) => {
+            log::debug!("Interaction::DismissAutoUpdateSummary");
+
+            ajour.auto_update_summary.updated.clear();
+            ajour.auto_update_summary.failed.clear();
+        }
         Message::Interaction(Interaction::MoveColumnLeft(key)) => {
             log::debug!("Interaction::MoveColumnLeft({:?})", key);
 
@@ -1003,34 +1366,45 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                 ajour.column_settings.columns.swap(idx, idx + 1);
             }
         }
-        Message::CatalogDownloaded(Ok(catalog)) => {
+        Message::CatalogCacheLoaded(Some(catalog)) => {
             log::debug!(
-                "Message::CatalogDownloaded({} addons in catalog)",
+                "Message::CatalogCacheLoaded({} addons in catalog)",
                 catalog.addons.len()
             );
 
-            let mut categories = HashSet::new();
-            catalog.addons.iter().for_each(|a| {
-                for category in &a.categories {
-                    categories.insert(category.clone());
-                }
-            });
-
-            // Map category strings to Category enum
-            let mut categories: Vec<_> = categories
-                .into_iter()
-                .map(CatalogCategory::Choice)
-                .collect();
-            categories.sort();
-
-            // Unshift the All Categories option into the vec
-            categories.insert(0, CatalogCategory::All);
+            // The refresh might have finished first.
+            if ajour.catalog.is_none() {
+                set_catalog(ajour, catalog);
+            }
+        }
+        Message::CatalogCacheLoaded(None) => {
+            log::debug!("Message::CatalogCacheLoaded(None)");
+        }
+        Message::CatalogDownloaded(Ok(Some(catalog))) => {
+            log::debug!(
+                "Message::CatalogDownloaded({} addons in catalog)",
+                catalog.addons.len()
+            );
 
-            ajour.catalog_search_state.categories = categories;
+            set_catalog(ajour, catalog);
+        }
+        Message::CatalogDownloaded(Ok(None)) => {
+            log::debug!("Message::CatalogDownloaded(not modified)");
 
-            ajour.catalog = Some(catalog);
+            // The cached catalog is current, but it might not have been read yet.
+            if ajour.catalog.is_none() {
+                let endpoints = ajour.config.resolved_endpoints();
+                let feeds = ajour.config.catalog_feeds.clone();
 
-            query_and_sort_catalog(ajour);
+                return Ok(Command::perform(
+                    async move { load_cached_catalog(&endpoints, &feeds).await },
+                    Message::CatalogCacheLoaded,
+                ));
+            }
+        }
+        Message::CatalogDownloaded(Err(error)) if ajour.catalog.is_some() => {
+            // We keep showing the cached catalog.
+            log::error!("{}", error);
         }
         Message::Interaction(Interaction::CatalogQuery(query)) => {
             // Close settings if shown.
@@ -1041,44 +1415,208 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
 
             query_and_sort_catalog(ajour);
         }
-        Message::Interaction(Interaction::CatalogInstall(source, flavor, id)) => {
+        Message::Interaction(Interaction::CatalogAddonSourceSelected(current, id, preferred)) => {
+            log::debug!(
+                "Interaction::CatalogAddonSourceSelected({}, {}, {})",
+                current,
+                id,
+                preferred
+            );
+
+            let is_addon = |a: &CatalogAddon| {
+                (a.source == current && a.id == id)
+                    || a.alternatives
+                        .iter()
+                        .any(|a| a.source == current && a.id == id)
+            };
+
+            // The choice is kept in the catalog, so it survives a new search.
+            if let Some(catalog) = &mut ajour.catalog {
+                if let Some(addon) = catalog.addons.iter_mut().find(|a| is_addon(a)) {
+                    addon.prefer_source(&preferred);
+                }
+            }
+
+            for row in ajour.catalog_search_state.catalog_rows.iter_mut() {
+                if is_addon(&row.addon) {
+                    row.addon.prefer_source(&preferred);
+                }
+            }
+        }
+        Message::Interaction(Interaction::CatalogInstall(source, flavors, id, release_channel)) => {
             log::debug!(
-                "Interaction::CatalogInstall({}, {}, {})",
+                "Interaction::CatalogInstall({}, {:?}, {}, {})",
                 source,
-                flavor,
-                &id
+                flavors,
+                &id,
+                release_channel
+            );
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            let commands: Vec<_> = flavors
+                .into_iter()
+                .filter_map(|flavor| {
+                    install_from_catalog(ajour, source.clone(), flavor, id, release_channel)
+                })
+                .collect();
+
+            return Ok(Command::batch(commands));
+        }
+        Message::Interaction(Interaction::CatalogReleaseChannelSelected(release_channel)) => {
+            log::debug!(
+                "Interaction::CatalogReleaseChannelSelected({})",
+                release_channel
             );
 
             // Close settings if shown.
             ajour.is_showing_settings = false;
 
-            // We create an empty addon we can add to the list of addons.
-            // This will later be updated by a more rich addon.
-            let mut empty_addon = Addon::empty(&id.to_string());
-            empty_addon.state = AddonState::Downloading;
+            ajour.catalog_search_state.release_channel = release_channel;
+        }
+        Message::Interaction(Interaction::CatalogNotInstalledFilter(is_checked)) => {
+            log::debug!("Interaction::CatalogNotInstalledFilter({})", is_checked);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            ajour.catalog_search_state.not_installed_filter = is_checked;
+
+            query_and_sort_catalog(ajour);
+        }
+        Message::Interaction(Interaction::CatalogInstallBothFlavors(is_checked)) => {
+            log::debug!("Interaction::CatalogInstallBothFlavors({})", is_checked);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            ajour.catalog_search_state.install_both_flavors = is_checked;
+        }
+        Message::Interaction(Interaction::ExpandCatalogAddon(source, id)) => {
+            log::debug!("Interaction::ExpandCatalogAddon({}, {})", source, id);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
 
-            match source {
-                catalog::Source::Tukui => empty_addon.tukui_id = Some(id.to_string()),
-                catalog::Source::Curse => empty_addon.curse_id = Some(id),
+            // Collapse the details if they are already shown.
+            if let Some(expanded) = &ajour.expanded_catalog_addon {
+                if expanded.is_addon(&source, id) {
+                    ajour.expanded_catalog_addon = None;
+                    return Ok(Command::none());
+                }
             }
 
-            let addons = ajour.addons.entry(flavor).or_default();
-            addons.push(empty_addon.clone());
-
-            if let Some(addon_path) = ajour.config.get_addon_directory_for_flavor(&flavor) {
-                let command = match source {
-                    catalog::Source::Curse => Command::perform(
-                        curse_api::latest_stable_addon_from_id(id, empty_addon, addon_path, flavor),
-                        Message::CatalogInstallAddonFetched,
-                    ),
-                    catalog::Source::Tukui => Command::perform(
-                        tukui_api::latest_stable_addon_from_id(id, empty_addon, addon_path, flavor),
-                        Message::CatalogInstallAddonFetched,
-                    ),
+            let catalog_addon = ajour
+                .catalog
+                .as_ref()
+                .and_then(|catalog| catalog.find(&source, id))
+                .cloned();
+
+            if let Some(catalog_addon) = catalog_addon {
+                ajour.expanded_catalog_addon = Some(ExpandedCatalogAddon::new(source.clone(), id));
+
+                let endpoints = ajour.config.resolved_endpoints();
+                return Ok(Command::perform(
+                    async move {
+                        let details = fetch_addon_details(&endpoints, &catalog_addon).await;
+                        (source, id, details)
+                    },
+                    Message::CatalogAddonDetailsFetched,
+                ));
+            }
+        }
+        Message::CatalogAddonDetailsFetched((source, id, result)) => {
+            log::debug!("Message::CatalogAddonDetailsFetched({}, {})", source, id);
+
+            // The details are dropped if another addon was expanded meanwhile.
+            if let Some(expanded) = ajour.expanded_catalog_addon.as_mut() {
+                if expanded.is_addon(&source, id) {
+                    match result {
+                        Ok(details) => expanded.set_details(details, ajour.catalog.as_ref()),
+                        Err(error) => {
+                            log::error!("{}", error);
+
+                            expanded.details = CatalogDetails::Failed(error.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        Message::Interaction(Interaction::CatalogInstallFile(idx)) => {
+            log::debug!("Interaction::CatalogInstallFile({})", idx);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            let selected = ajour.expanded_catalog_addon.as_ref().and_then(|expanded| {
+                let file = match &expanded.details {
+                    CatalogDetails::Loaded(details) => details.files.get(idx)?.clone(),
+                    _ => return None,
                 };
+                let catalog_addon = ajour
+                    .catalog
+                    .as_ref()?
+                    .find(&expanded.source, expanded.id)?
+                    .clone();
 
-                return Ok(command);
+                Some((catalog_addon, file))
+            });
+
+            if let Some((catalog_addon, file)) = selected {
+                if let Some(command) = install_file_from_catalog(ajour, catalog_addon, file) {
+                    return Ok(command);
+                }
+            }
+        }
+        Message::Interaction(Interaction::InstallDependencies(id)) => {
+            log::debug!("Interaction::InstallDependencies({})", &id);
+
+            // Close settings if shown.
+            ajour.is_showing_settings = false;
+
+            let flavor = ajour.config.wow.flavor;
+            let addons = ajour.addons.entry(flavor).or_default();
+            let missing = match (
+                addons.iter().find(|a| a.id == id),
+                ajour.dependency_graphs.get(&flavor),
+            ) {
+                (Some(addon), Some(graph)) => graph.resolve(addon).missing,
+                _ => vec![],
+            };
+
+            let mut commands = vec![];
+            let mut not_found = vec![];
+            for folder in missing {
+                // Look up which catalog addon provides the missing folder.
+                let catalog_addon = ajour
+                    .catalog
+                    .as_ref()
+                    .and_then(|catalog| find_catalog_addon(catalog, &folder, flavor))
+                    .map(|a| (a.source.clone(), a.id));
+
+                match catalog_addon {
+                    Some((source, catalog_id)) => {
+                        commands.extend(install_from_catalog(
+                            ajour,
+                            source,
+                            flavor,
+                            catalog_id,
+                            ReleaseChannel::Stable,
+                        ));
+                    }
+                    None => not_found.push(folder),
+                }
             }
+
+            if !not_found.is_empty() {
+                ajour.state = AjourState::Error(ClientError::Custom(format!(
+                    "Couldn't find {} in the catalog",
+                    not_found.join(", ")
+                )));
+            }
+
+            return Ok(Command::batch(commands));
         }
         Message::Interaction(Interaction::CatalogCategorySelected(category)) => {
             log::debug!("Interaction::CatalogCategorySelected({})", &category);
@@ -1130,6 +1668,22 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                 &addon.curse_id
             );
 
+            // Remember the release channel if a file from another channel was chosen.
+            let release_channels = ajour
+                .config
+                .addons
+                .release_channels
+                .entry(flavor)
+                .or_default();
+            if addon.release_channel == ReleaseChannel::Stable {
+                if release_channels.remove(&addon.id).is_some() {
+                    let _ = ajour.config.save();
+                }
+            } else {
+                release_channels.insert(addon.id.clone(), addon.release_channel);
+                let _ = ajour.config.save();
+            }
+
             if let Some(addons) = ajour.addons.get_mut(&flavor) {
                 // Remove the empty addon and add in our rich addon
                 if addons.iter_mut().any(|a| a.id == id.to_string()) {
@@ -1144,12 +1698,7 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
                     .expect("Expected a valid path");
 
                 return Ok(Command::perform(
-                    perform_download_addon(
-                        ajour.shared_client.clone(),
-                        flavor,
-                        addon,
-                        to_directory,
-                    ),
+                    perform_download_addon(flavor, addon, to_directory),
                     Message::DownloadedAddon,
                 ));
             }
@@ -1158,12 +1707,23 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
         | Message::Parse(Err(error))
         | Message::NeedsUpdate(Err(error))
         | Message::CatalogDownloaded(Err(error))
-        | Message::CatalogInstallAddonFetched(Err(error))
-        | Message::DownloadedAddon((_, _, Err(error))) => {
+        | Message::CatalogInstallAddonFetched(Err(error)) => {
             log::error!("{}", error);
 
             ajour.state = AjourState::Error(error);
         }
+        Message::DownloadedAddon((flavor, id, Err(error))) => {
+            log::error!("{}", error);
+
+            ajour.auto_update_summary.complete(flavor, &id, false);
+
+            let addons = ajour.addons.entry(flavor).or_default();
+            if let Some(addon) = addons.iter_mut().find(|a| a.id == id) {
+                addon.state = AddonState::Ajour(Some("Error".to_owned()));
+            }
+
+            ajour.state = AjourState::Error(error);
+        }
         Message::RuntimeEvent(iced_native::Event::Window(
             iced_native::window::Event::Resized { width, height },
         )) => {
@@ -1174,12 +1734,387 @@ pub fn handle_message(ajour: &mut Ajour, message: Message) -> Result<Command<Mes
             let _ = ajour.config.save();
         }
         Message::RuntimeEvent(_) => {}
+        // Nothing to update, the progress is read from the addon when drawing.
+        Message::DownloadProgress(_) => {}
+        Message::CheckForUpdates(_) => {
+            log::debug!("Message::CheckForUpdates");
+
+            let endpoints = ajour.config.resolved_endpoints();
+            let custom_feeds = ajour.config.catalog_feeds.custom.clone();
+            let commands: Vec<_> = ajour
+                .addons
+                .iter()
+                .filter(|(_, addons)| !addons.is_empty())
+                .map(|(flavor, addons)| {
+                    let (flavor, addons, endpoints) = (*flavor, addons.clone(), endpoints.clone());
+                    let custom_feeds = custom_feeds.clone();
+
+                    Command::perform(
+                        async move {
+                            let result =
+                                refresh_remote_packages(&endpoints, &custom_feeds, flavor, addons)
+                                    .await;
+                            (flavor, result)
+                        },
+                        Message::RemotePackagesRefreshed,
+                    )
+                })
+                .collect();
+
+            return Ok(Command::batch(commands));
+        }
+        Message::RemotePackagesRefreshed((flavor, Ok(refreshed))) => {
+            log::debug!(
+                "Message::RemotePackagesRefreshed({}, {} addons)",
+                flavor,
+                refreshed.len()
+            );
+
+            let num_updates = ajour.num_updates();
+
+            let addons = ajour.addons.entry(flavor).or_default();
+            for refreshed in refreshed {
+                if let Some(addon) = addons.iter_mut().find(|a| a.id == refreshed.id) {
+                    addon.remote_packages = refreshed.remote_packages;
+
+                    // Addons being downloaded, modified or ignored keep their state.
+                    if !matches!(addon.state, AddonState::Ajour(_) | AddonState::Updatable) {
+                        continue;
+                    }
+
+                    let is_updatable = addon
+                        .relevant_release_package()
+                        .map(|package| addon.is_updatable(package))
+                        .unwrap_or_default();
+
+                    if is_updatable {
+                        addon.state = AddonState::Updatable;
+                    } else if addon.state == AddonState::Updatable {
+                        addon.state = AddonState::Ajour(None);
+                    }
+                }
+            }
+
+            let new_updates = ajour.num_updates().saturating_sub(num_updates);
+            if new_updates > 0 {
+                log::info!("{} - {} new addon updates available", flavor, new_updates);
+            }
+
+            return Ok(auto_update_addons(ajour, flavor));
+        }
+        Message::RemotePackagesRefreshed((flavor, Err(error))) => {
+            // A failed background check isn't shown, the next check will retry.
+            log::error!("{} - couldn't check for addon updates: {}", flavor, error);
+        }
         Message::None(_) => {}
     }
 
     Ok(Command::none())
 }
 
+/// Replaces the catalog and the categories which can be filtered on.
+fn set_catalog(ajour: &mut Ajour, catalog: Catalog) {
+    let mut categories = HashSet::new();
+    catalog.addons.iter().for_each(|a| {
+        for category in &a.categories {
+            categories.insert(category.clone());
+        }
+    });
+
+    // Map category strings to Category enum
+    let mut categories: Vec<_> = categories
+        .into_iter()
+        .map(CatalogCategory::Choice)
+        .collect();
+    categories.sort();
+
+    // Unshift the All Categories option into the vec
+    categories.insert(0, CatalogCategory::All);
+
+    ajour.catalog_search_state.categories = categories;
+
+    // Custom feeds are listed after the built-in sources.
+    let mut sources = CatalogSource::all();
+    let mut custom_sources: Vec<_> = catalog
+        .addons
+        .iter()
+        .flat_map(|a| a.sources())
+        .filter(|s| matches!(s, catalog::Source::Custom(_)))
+        .map(CatalogSource::Choice)
+        .collect();
+    custom_sources.sort();
+    custom_sources.dedup();
+    sources.extend(custom_sources);
+
+    if !sources.contains(&ajour.catalog_search_state.source) {
+        ajour.catalog_search_state.source = CatalogSource::All;
+    }
+    ajour.catalog_search_state.sources = sources;
+
+    ajour.catalog = Some(catalog);
+
+    query_and_sort_catalog(ajour);
+}
+
+/// Rebuilds the dependency graph for `flavor` from the addon directory.
+/// The current graph is kept until the new one is read.
+fn refresh_dependency_graph(ajour: &Ajour, flavor: Flavor) -> Command<Message> {
+    let addon_directory = match ajour.config.get_addon_directory_for_flavor(&flavor) {
+        Some(addon_directory) => addon_directory,
+        None => return Command::none(),
+    };
+    let addons = ajour.addons.get(&flavor).cloned().unwrap_or_default();
+
+    Command::perform(
+        perform_read_dependency_graph(flavor, addon_directory, addons),
+        Message::DependencyGraphRead,
+    )
+}
+
+/// Fetches the changelog of the update available for `addon`, if any.
+fn fetch_pending_changelog(ajour: &mut Ajour, flavor: Flavor, addon: Addon) -> Command<Message> {
+    ajour.pending_changelog = None;
+
+    if addon.state != AddonState::Updatable {
+        return Command::none();
+    }
+
+    let version = match addon.relevant_release_package() {
+        Some(package) => package.version.clone(),
+        None => return Command::none(),
+    };
+
+    ajour.pending_changelog = Some(PendingChangelog {
+        id: addon.id.clone(),
+        version: version.clone(),
+        state: ChangelogState::Loading,
+    });
+
+    let endpoints = ajour.config.resolved_endpoints();
+    Command::perform(
+        async move {
+            let changelog = fetch_changelog(&endpoints, &addon, flavor).await;
+            (addon.id, version, changelog)
+        },
+        Message::ChangelogFetched,
+    )
+}
+
+/// Downloads the newest file of the installed addon `id`.
+fn update_addon(ajour: &mut Ajour, flavor: Flavor, id: &str) -> Option<Command<Message>> {
+    let to_directory = ajour
+        .config
+        .get_temporary_addon_directory(flavor)
+        .expect("Expected a valid path");
+    let addons = ajour.addons.entry(flavor).or_default();
+    let addon = addons.iter_mut().find(|a| a.id == id)?;
+    addon.state = AddonState::Downloading;
+
+    Some(Command::perform(
+        perform_download_addon(flavor, addon.clone(), to_directory),
+        Message::DownloadedAddon,
+    ))
+}
+
+/// Downloads every updatable addon of `flavor` matching `filter`, except ignored ones.
+fn download_updates<F>(ajour: &mut Ajour, flavor: Flavor, filter: F) -> Vec<Command<Message>>
+where
+    F: Fn(&Addon) -> bool,
+{
+    let to_directory = match ajour.config.get_temporary_addon_directory(flavor) {
+        Some(to_directory) => to_directory,
+        None => return vec![],
+    };
+    let ignored_ids = ajour.config.addons.ignored.entry(flavor).or_default();
+
+    let mut commands = vec![];
+    for addon in ajour.addons.entry(flavor).or_default().iter_mut() {
+        if addon.state != AddonState::Updatable
+            || ignored_ids.iter().any(|i| i == &addon.id)
+            || !filter(addon)
+        {
+            continue;
+        }
+
+        addon.state = AddonState::Downloading;
+        commands.push(Command::perform(
+            perform_download_addon(flavor, addon.clone(), to_directory.clone()),
+            Message::DownloadedAddon,
+        ));
+    }
+
+    commands
+}
+
+/// Downloads the updates of addons marked as auto update, either by themselves
+/// or through their release channel, and adds them to the summary.
+fn auto_update_addons(ajour: &mut Ajour, flavor: Flavor) -> Command<Message> {
+    let addons_config = ajour.config.addons.clone();
+    let is_auto_update =
+        |addon: &Addon| addons_config.is_auto_update(flavor, &addon.id, addon.release_channel);
+
+    // The labels are taken before downloading, while the old version is still known.
+    for addon in ajour.addons.entry(flavor).or_default().iter() {
+        if addon.state != AddonState::Updatable || !is_auto_update(addon) {
+            continue;
+        }
+
+        let new_version = addon
+            .relevant_release_package()
+            .map(|package| package.version.clone())
+            .unwrap_or_default();
+        let label = format!(
+            "{} ({} → {})",
+            addon.title,
+            addon.version.as_deref().unwrap_or("-"),
+            new_version
+        );
+
+        ajour
+            .auto_update_summary
+            .pending
+            .push((flavor, addon.id.clone(), label));
+    }
+
+    let commands = download_updates(ajour, flavor, is_auto_update);
+    if !commands.is_empty() {
+        log::info!(
+            "{} - automatically updating {} addons",
+            flavor,
+            commands.len()
+        );
+    }
+
+    Command::batch(commands)
+}
+
+/// Adds a placeholder addon for the catalog addon `id`, and fetches the rich addon
+/// which is then downloaded and installed.
+///
+/// Only Curse has release channels, so addons from other sources are always
+/// installed from stable.
+fn install_from_catalog(
+    ajour: &mut Ajour,
+    source: catalog::Source,
+    flavor: Flavor,
+    id: u32,
+    release_channel: ReleaseChannel,
+) -> Option<Command<Message>> {
+    // Addons from a custom feed have no API, so they are installed from the
+    // file listed in the feed.
+    let feed_addon = match source {
+        catalog::Source::Custom(_) => Some(
+            ajour
+                .catalog
+                .as_ref()
+                .and_then(|catalog| catalog.find(&source, id))?
+                .clone(),
+        ),
+        _ => None,
+    };
+
+    // We create an empty addon we can add to the list of addons.
+    // This will later be updated by a more rich addon.
+    let mut empty_addon = Addon::empty(&id.to_string());
+    empty_addon.state = AddonState::Downloading;
+
+    match &source {
+        catalog::Source::Tukui => empty_addon.tukui_id = Some(id.to_string()),
+        catalog::Source::Curse => empty_addon.curse_id = Some(id),
+        catalog::Source::WowI => empty_addon.wowi_id = Some(id.to_string()),
+        catalog::Source::Custom(feed) => {
+            empty_addon.custom_feed_id = Some(CustomFeedId {
+                feed: feed.clone(),
+                id,
+            })
+        }
+    }
+
+    let addon_path = ajour.config.get_addon_directory_for_flavor(&flavor)?;
+
+    let addons = ajour.addons.entry(flavor).or_default();
+    addons.push(empty_addon.clone());
+
+    let endpoints = ajour.config.resolved_endpoints();
+    let command = match source {
+        catalog::Source::Curse => Command::perform(
+            async move {
+                curse_api::latest_addon_from_id(
+                    &endpoints,
+                    id,
+                    empty_addon,
+                    addon_path,
+                    flavor,
+                    release_channel,
+                )
+                .await
+            },
+            Message::CatalogInstallAddonFetched,
+        ),
+        catalog::Source::Tukui => Command::perform(
+            async move {
+                tukui_api::latest_stable_addon_from_id(
+                    &endpoints,
+                    id,
+                    empty_addon,
+                    addon_path,
+                    flavor,
+                )
+                .await
+            },
+            Message::CatalogInstallAddonFetched,
+        ),
+        catalog::Source::WowI => Command::perform(
+            async move {
+                wowi_api::latest_stable_addon_from_id(
+                    &endpoints,
+                    id,
+                    empty_addon,
+                    addon_path,
+                    flavor,
+                )
+                .await
+            },
+            Message::CatalogInstallAddonFetched,
+        ),
+        catalog::Source::Custom(_) => Command::perform(
+            catalog::latest_addon_from_feed(feed_addon?, empty_addon, addon_path, flavor),
+            Message::CatalogInstallAddonFetched,
+        ),
+    };
+
+    Some(command)
+}
+
+/// Installs a specific file of a catalog addon, chosen in its details.
+fn install_file_from_catalog(
+    ajour: &mut Ajour,
+    catalog_addon: CatalogAddon,
+    file: CatalogFile,
+) -> Option<Command<Message>> {
+    let id = catalog_addon.id;
+    let flavor = file.flavor;
+    let addon_path = ajour.config.get_addon_directory_for_flavor(&flavor)?;
+
+    let mut empty_addon = Addon::empty(&id.to_string());
+    empty_addon.state = AddonState::Downloading;
+
+    // The file is known already, so the rich addon can be built right away.
+    let addon = addon_from_file(&catalog_addon, &file, empty_addon, addon_path);
+
+    // The placeholder carries the source ids so the catalog shows it as downloading.
+    let mut placeholder = addon.clone();
+    placeholder.id = id.to_string();
+
+    let addons = ajour.addons.entry(flavor).or_default();
+    addons.push(placeholder);
+
+    Some(Command::perform(
+        async move { Ok((id, flavor, addon)) },
+        Message::CatalogInstallAddonFetched,
+    ))
+}
+
 async fn open_directory() -> Option<PathBuf> {
     let dialog = OpenSingleDir { dir: None };
     if let Ok(show) = dialog.show() {
@@ -1191,19 +2126,41 @@ async fn open_directory() -> Option<PathBuf> {
 
 async fn perform_read_addon_directory(
     fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    endpoints: Endpoints,
+    custom_feeds: Vec<CustomFeed>,
+    custom_feed_ids: HashMap<String, CustomFeedId>,
     root_dir: PathBuf,
     flavor: Flavor,
 ) -> (Flavor, Result<Vec<Addon>>) {
     (
         flavor,
-        read_addon_directory(fingerprint_collection, root_dir, flavor).await,
+        read_addon_directory(
+            fingerprint_collection,
+            &endpoints,
+            &custom_feeds,
+            &custom_feed_ids,
+            root_dir,
+            flavor,
+        )
+        .await,
+    )
+}
+
+/// Reads the `.toc` of every folder in `addon_directory` into a `DependencyGraph`.
+async fn perform_read_dependency_graph(
+    flavor: Flavor,
+    addon_directory: PathBuf,
+    addons: Vec<Addon>,
+) -> (Flavor, DependencyGraph) {
+    (
+        flavor,
+        DependencyGraph::from_directory(addon_directory, &addons).unwrap_or_default(),
     )
 }
 
 /// Downloads the newest version of the addon.
 /// This is for now only downloading from warcraftinterface.
 async fn perform_download_addon(
-    shared_client: Arc<HttpClient>,
     flavor: Flavor,
     addon: Addon,
     to_directory: PathBuf,
@@ -1211,7 +2168,39 @@ async fn perform_download_addon(
     (
         flavor,
         addon.id.clone(),
-        download_addon(&shared_client, &addon, &to_directory).await,
+        download_addon(&addon, &to_directory).await,
+    )
+}
+
+/// Downloads the remote file the addon was matched against and reinstalls it.
+/// This restores a pristine copy of every module in the addon.
+async fn perform_repair_addon(
+    flavor: Flavor,
+    addon: Addon,
+    package: RemotePackage,
+    temp_directory: PathBuf,
+    addon_directory: PathBuf,
+) -> (Flavor, String, Result<()>) {
+    let result = match download_addon_package(&addon, &package, &temp_directory).await {
+        Ok(_) => install_addon(&addon, &temp_directory, &addon_directory)
+            .await
+            .map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    (flavor, addon.id.clone(), result)
+}
+
+/// Verifies the local files of each `Addon` against the remote file.
+async fn perform_verify_addons(
+    endpoints: Endpoints,
+    flavor: Flavor,
+    addon_directory: PathBuf,
+    addons: Vec<Addon>,
+) -> (Flavor, Result<Vec<AddonIntegrity>>) {
+    (
+        flavor,
+        verify_addons(&endpoints, &addon_directory, &addons).await,
     )
 }
 
@@ -1220,12 +2209,20 @@ async fn perform_hash_addon(
     addon_dir: impl AsRef<Path>,
     addon_id: String,
     fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    endpoints: Endpoints,
     flavor: Flavor,
 ) -> (Flavor, String, Result<()>) {
     (
         flavor,
         addon_id.clone(),
-        update_addon_fingerprint(fingerprint_collection, flavor, addon_dir, addon_id).await,
+        update_addon_fingerprint(
+            fingerprint_collection,
+            &endpoints,
+            flavor,
+            addon_dir,
+            addon_id,
+        )
+        .await,
     )
 }
 
@@ -1235,7 +2232,7 @@ async fn perform_unpack_addon(
     addon: Addon,
     from_directory: PathBuf,
     to_directory: PathBuf,
-) -> (Flavor, String, Result<()>) {
+) -> (Flavor, String, Result<Vec<String>>) {
     (
         flavor,
         addon.id.clone(),
@@ -1243,6 +2240,20 @@ async fn perform_unpack_addon(
     )
 }
 
+/// Returns the folder of an addon installed from a custom feed, and its bundled
+/// modules. The folder named like the addon is preferred, else the shortest
+/// name is taken as the addon, like for WoWInterface.
+fn custom_feed_folders(addon: &Addon, folders: &[String]) -> Option<(String, Vec<String>)> {
+    let folder = folders
+        .iter()
+        .find(|f| **f == addon.id)
+        .or_else(|| folders.iter().min_by_key(|f| f.len()))?
+        .clone();
+    let bundled = folders.iter().filter(|f| **f != folder).cloned().collect();
+
+    Some((folder, bundled))
+}
+
 fn sort_addons(addons: &mut [Addon], sort_direction: SortDirection, column_key: ColumnKey) {
     match (column_key, sort_direction) {
         (ColumnKey::Title, SortDirection::Asc) => {
@@ -1313,6 +2324,12 @@ fn sort_addons(addons: &mut [Addon], sort_direction: SortDirection, column_key:
         (ColumnKey::GameVersion, SortDirection::Desc) => {
             addons.sort_by(|a, b| a.game_version.cmp(&b.game_version).reverse())
         }
+        (ColumnKey::Interface, SortDirection::Asc) => {
+            addons.sort_by(|a, b| a.toc.interface.cmp(&b.toc.interface))
+        }
+        (ColumnKey::Interface, SortDirection::Desc) => {
+            addons.sort_by(|a, b| a.toc.interface.cmp(&b.toc.interface).reverse())
+        }
     }
 }
 
@@ -1364,61 +2381,70 @@ fn sort_catalog_addons(
 
 fn query_and_sort_catalog(ajour: &mut Ajour) {
     if let Some(catalog) = &ajour.catalog {
-        let query = ajour
-            .catalog_search_state
-            .query
-            .as_ref()
-            .map(|s| s.to_lowercase());
+        let query = Query::parse(
+            ajour
+                .catalog_search_state
+                .query
+                .as_deref()
+                .unwrap_or_default(),
+        );
         let flavor = &ajour.catalog_search_state.flavor;
         let source = &ajour.catalog_search_state.source;
         let category = &ajour.catalog_search_state.category;
         let result_size = ajour.catalog_search_state.result_size.as_usize();
+        let not_installed_filter = ajour.catalog_search_state.not_installed_filter;
+        let installed = ajour
+            .addons
+            .get(&ajour.config.wow.flavor)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
 
-        let mut catalog_rows: Vec<_> = catalog
+        let addons = catalog
             .addons
             .iter()
-            .filter(|a| {
-                let cleaned_text =
-                    format!("{} {}", a.name.to_lowercase(), a.summary.to_lowercase());
-
-                if let Some(query) = &query {
-                    cleaned_text.contains(query)
-                } else {
-                    true
-                }
+            .filter(|a| match source {
+                CatalogSource::All => true,
+                CatalogSource::Choice(source) => a.sources().contains(source),
             })
             .filter(|a| match flavor {
                 CatalogFlavor::All => true,
                 CatalogFlavor::Choice(flavor) => a.flavors.iter().any(|f| f == flavor),
             })
-            .filter(|a| match source {
-                CatalogSource::All => true,
-                CatalogSource::Choice(source) => a.source == *source,
-            })
             .filter(|a| match category {
                 CatalogCategory::All => true,
                 CatalogCategory::Choice(name) => a.categories.iter().any(|c| c == name),
             })
+            .filter(|a| !not_installed_filter || !installed.iter().any(|i| a.is_installed_as(i)));
+
+        // Results are ranked by relevance, unless sorted by a column.
+        let mut catalog_rows: Vec<_> = search(addons, &query)
+            .into_iter()
             .cloned()
-            .map(CatalogRow::from)
+            .map(|mut a| {
+                // When filtering on a source, the addon is shown from that
+                // source even if it is an alternative.
+                let preferred = match source {
+                    CatalogSource::Choice(source) => Some(source.clone()),
+                    CatalogSource::All => query.preferred_source(&a),
+                };
+                if let Some(preferred) = preferred {
+                    a.prefer_source(&preferred);
+                }
+
+                CatalogRow::from(a)
+            })
             .collect();
 
-        let sort_direction = ajour
-            .catalog_header_state
-            .previous_sort_direction
-            .unwrap_or(SortDirection::Desc);
-        let column_key = ajour
-            .catalog_header_state
-            .previous_column_key
-            .unwrap_or(CatalogColumnKey::NumDownloads);
+        if let Some(column_key) = ajour.catalog_header_state.previous_column_key {
+            let sort_direction = ajour
+                .catalog_header_state
+                .previous_sort_direction
+                .unwrap_or(SortDirection::Desc);
 
-        sort_catalog_addons(&mut catalog_rows, sort_direction, column_key);
+            sort_catalog_addons(&mut catalog_rows, sort_direction, column_key);
+        }
 
-        catalog_rows = catalog_rows
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, row)| if idx < result_size { Some(row) } else { None })
-            .collect();
+        catalog_rows.truncate(result_size);
 
         ajour.catalog_search_state.catalog_rows = catalog_rows;
     }
@@ -0,0 +1,59 @@
+use crate::update::read_addons;
+
+use ajour_core::config::load_config;
+use ajour_core::error::ClientError;
+use ajour_core::utility::game_interface;
+use ajour_core::Result;
+
+use async_std::sync::{Arc, Mutex};
+use async_std::task;
+
+/// Lists all installed addons for each flavor, flagging the ones the game
+/// considers out of date.
+pub fn list_addons() -> Result<()> {
+    task::block_on(async {
+        let config = load_config().await?;
+
+        let wow_directory = config.wow.directory.clone().ok_or_else(|| ClientError::Custom("No WoW directory set. Launch Ajour and make sure a WoW directory is set before using the command line.".to_string()))?;
+
+        // Fingerprint cache will be fetched during `read_addon_directory`
+        let fingerprint_collection: Arc<Mutex<_>> = Default::default();
+
+        for (flavor, mut addons) in read_addons(&config, fingerprint_collection).await? {
+            addons.sort();
+
+            let game_interface = game_interface(&wow_directory, flavor);
+            let num_out_of_date = addons
+                .iter()
+                .filter(|a| a.is_out_of_date(game_interface))
+                .count();
+
+            log::info!(
+                "{} - {} addons, {} out of date (game interface: {})",
+                flavor,
+                addons.len(),
+                num_out_of_date,
+                game_interface.map_or_else(|| "unknown".to_string(), |i| i.to_string())
+            );
+
+            for addon in addons.iter() {
+                log::info!(
+                    "\t{} - {} - interface: {}{}",
+                    &addon.title,
+                    addon.version.as_deref().unwrap_or("-"),
+                    addon
+                        .toc
+                        .interface
+                        .map_or_else(|| "-".to_string(), |i| i.to_string()),
+                    if addon.is_out_of_date(game_interface) {
+                        " (out of date)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+
+        Result::Ok(())
+    })
+}
@@ -3,12 +3,20 @@
 // https://msdn.microsoft.com/en-us/library/4cc7ya5b.aspx for more information.
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod api;
 mod cli;
+mod daemon;
 mod gui;
+mod list;
+mod rehash;
+mod search;
 mod update;
+mod verify;
 
+use ajour_core::config::{Config, Endpoints, Network, ENDPOINT_OVERRIDES, NETWORK_OVERRIDES};
 use ajour_core::error::ClientError;
-use ajour_core::fs::CONFIG_DIR;
+use ajour_core::fs::{PersistentData, CONFIG_DIR};
+use ajour_core::network::configure_client;
 use ajour_core::Result;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -45,6 +53,57 @@ pub fn main() {
         *config_dir = data_dir.clone();
     }
 
+    // Endpoints passed on the command line takes precedence over the config.
+    {
+        let mut endpoint_overrides = ENDPOINT_OVERRIDES.lock().unwrap();
+
+        *endpoint_overrides = Endpoints {
+            curse: opts.curse_endpoint.clone(),
+            tukui: opts.tukui_endpoint.clone(),
+            catalog: opts.catalog_endpoint.clone(),
+            github: opts.github_endpoint.clone(),
+            wowi: opts.wowi_endpoint.clone(),
+        };
+    }
+
+    // Network settings passed on the command line takes precedence over the config.
+    {
+        let mut network_overrides = NETWORK_OVERRIDES.lock().unwrap();
+
+        let (proxy_username, proxy_password) = match &opts.proxy_user {
+            Some(user) => match user.find(':') {
+                Some(idx) => (
+                    Some(user[..idx].to_owned()),
+                    Some(user[idx + 1..].to_owned()),
+                ),
+                None => (Some(user.clone()), None),
+            },
+            None => (None, None),
+        };
+
+        *network_overrides = Network {
+            proxy: opts.proxy.clone(),
+            proxy_username,
+            proxy_password,
+            no_proxy: opts.no_proxy.clone(),
+            ca_roots: opts.ca_roots.clone(),
+        };
+    }
+
+    // Every request goes through the shared client, so it is configured once
+    // here, and again when the network settings are applied in the GUI. An
+    // invalid setting shouldn't keep Ajour from starting, so requests then go
+    // out directly, without a proxy or custom certificate authorities.
+    {
+        let network = Config::load_or_default()
+            .unwrap_or_default()
+            .resolved_network();
+
+        if let Err(e) = configure_client(&network) {
+            log_error(&e);
+        }
+    }
+
     log_panics::init();
 
     log::info!("Ajour {} has started.", VERSION);
@@ -53,7 +112,20 @@ pub fn main() {
         Some(command) => {
             // Process the command and exit
             if let Err(e) = match command {
-                cli::Command::Update => update::update_all_addons(),
+                cli::Command::List => list::list_addons(),
+                cli::Command::Update { dry_run } => update::update_all_addons(dry_run),
+                cli::Command::Verify { repair } => verify::verify_all_addons(repair),
+                cli::Command::Rehash => rehash::rehash_all_addons(),
+                cli::Command::Search { query, limit } => {
+                    search::search_catalog(&query.join(" "), limit)
+                }
+                cli::Command::Daemon {
+                    interval,
+                    status_file,
+                    all,
+                    api_port,
+                    api_token,
+                } => daemon::run_daemon(interval, status_file, all, api_port, api_token),
             } {
                 log_error(&e);
             }
@@ -0,0 +1,27 @@
+use crate::update::read_addons;
+
+use ajour_core::config::load_config;
+use ajour_core::parse::clear_fingerprint_collection;
+use ajour_core::Result;
+
+use async_std::sync::{Arc, Mutex};
+use async_std::task;
+
+/// Clears all cached fingerprints and fingerprints every addon again.
+pub fn rehash_all_addons() -> Result<()> {
+    log::info!("Rehashing all addons...");
+
+    task::block_on(async {
+        let config = load_config().await?;
+
+        let fingerprint_collection: Arc<Mutex<_>> = Default::default();
+        clear_fingerprint_collection(fingerprint_collection.clone()).await?;
+
+        // Flavors which can't be read are logged by `read_addons`.
+        for (flavor, addons) in read_addons(&config, fingerprint_collection).await? {
+            log::info!("{} - rehashed, {} addons found", flavor, addons.len());
+        }
+
+        Result::Ok(())
+    })
+}
@@ -0,0 +1,46 @@
+use ajour_core::catalog::get_catalog;
+use ajour_core::config::load_config;
+use ajour_core::search::{search, Query};
+use ajour_core::Result;
+
+use async_std::task;
+
+/// Searches the catalog and lists the best matches.
+pub fn search_catalog(query: &str, limit: usize) -> Result<()> {
+    task::block_on(async {
+        let config = load_config().await?;
+        let catalog = get_catalog(&config.resolved_endpoints(), &config.catalog_feeds).await?;
+
+        let query = Query::parse(query);
+        let results = search(&catalog.addons, &query);
+
+        log::info!(
+            "{} addons found, showing {}",
+            results.len(),
+            results.len().min(limit)
+        );
+
+        for addon in results.into_iter().take(limit) {
+            let mut addon = addon.clone();
+            if let Some(source) = query.preferred_source(&addon) {
+                addon.prefer_source(&source);
+            }
+
+            let flavors: Vec<_> = addon.flavors.iter().map(ToString::to_string).collect();
+
+            log::info!(
+                "\t{} - {} (id: {}) - {} downloads - {}",
+                &addon.name,
+                &addon.source,
+                addon.id,
+                addon.number_of_downloads,
+                flavors.join(", ")
+            );
+            if !addon.summary.is_empty() {
+                log::info!("\t\t{}", &addon.summary);
+            }
+        }
+
+        Result::Ok(())
+    })
+}
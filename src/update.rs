@@ -3,7 +3,8 @@
 use crate::log_error;
 
 use ajour_core::addon::Addon;
-use ajour_core::config::{load_config, Flavor};
+use ajour_core::changelog::fetch_changelog;
+use ajour_core::config::{load_config, Config, Endpoints, Flavor};
 use ajour_core::error::ClientError;
 use ajour_core::fs::install_addon;
 use ajour_core::network::download_addon;
@@ -15,117 +16,80 @@ use async_std::task;
 
 use futures::future::join_all;
 
-use isahc::config::RedirectPolicy;
-use isahc::prelude::*;
-
 use std::path::PathBuf;
 
-pub fn update_all_addons() -> Result<()> {
+/// An addon with an update available, with the fingerprint collection, flavor,
+/// temporary directory and addon directory needed to install it.
+pub type PendingUpdate = (
+    Arc<Mutex<Option<FingerprintCollection>>>,
+    Flavor,
+    Addon,
+    PathBuf,
+    PathBuf,
+);
+
+pub fn update_all_addons(dry_run: bool) -> Result<()> {
     log::info!("Checking for addon updates...");
 
     task::block_on(async {
         let config = load_config().await?;
+        let endpoints = config.resolved_endpoints();
 
         // Fingerprint cache will be fetched during `read_addon_directory`
         let fingerprint_collection: Arc<Mutex<_>> = Default::default();
 
-        let mut addons_to_update = vec![];
-
-        // API request will get limited to 6 per host
-        let shared_client = Arc::new(
-            HttpClient::builder()
-                .redirect_policy(RedirectPolicy::Follow)
-                .max_connections_per_host(6)
-                .build()
-                .unwrap(),
-        );
-
-        // Update addons for both flavors
-        for flavor in Flavor::ALL.iter() {
-            // Only returns None if the path isn't set in the config
-            let addon_directory = config.get_addon_directory_for_flavor(flavor).ok_or_else(|| ClientError::Custom("No WoW directory set. Launch Ajour and make sure a WoW directory is set before using the command line.".to_string()))?;
-
-            if let Ok(addons) =
-                read_addon_directory(fingerprint_collection.clone(), &addon_directory, *flavor)
-                    .await
-            {
-                // Get any saved release channel preferences from config
-                let release_channels = config
-                    .addons
-                    .release_channels
-                    .get(flavor)
-                    .cloned()
-                    .unwrap_or_default();
-
-                // Get any ingnored addons from the config
-                let ignored_ids = config
-                    .addons
-                    .ignored
-                    .get(flavor)
-                    .cloned()
-                    .unwrap_or_default();
-
-                // Filter out any ignored addons
-                for mut addon in addons
-                    .into_iter()
-                    .filter(|a| !ignored_ids.iter().any(|i| i == &a.id))
-                {
-                    // Apply release channel preference
-                    if let Some(channel) = release_channels.get(&addon.id) {
-                        addon.release_channel = *channel;
-                    }
-
-                    if let Some(package) = addon.relevant_release_package() {
-                        // Directory to temporarily save downloaded addon
-                        let temp_directory = config
-                            .get_temporary_addon_directory(*flavor)
-                            .expect("Expected a valid path");
-
-                        // Only add addons that have an update available
-                        if addon.is_updatable(package) {
-                            addons_to_update.push((
-                                shared_client.clone(),
-                                fingerprint_collection.clone(),
-                                *flavor,
-                                addon,
-                                temp_directory,
-                                addon_directory.clone(),
-                            ));
-                        }
-                    }
-                }
-            }
-        }
+        let addons_to_update = find_updates(&config, fingerprint_collection).await?;
 
         let num_updates = addons_to_update.len();
         let mut num_errors = 0;
 
         log::info!("{} addons have an update available", num_updates);
 
-        addons_to_update
-            .iter()
-            .for_each(|(_, _, flavor, addon, ..)| {
-                let current_version = addon.version.as_deref().unwrap_or_default();
-                let new_version = addon
+        addons_to_update.iter().for_each(|(_, flavor, addon, ..)| {
+            let current_version = addon.version.as_deref().unwrap_or_default();
+            let new_version = addon
+                .relevant_release_package()
+                .map(|p| p.version.clone())
+                .unwrap_or_default();
+
+            log::info!(
+                "\t{} - {}, {} -> {}",
+                &addon.id,
+                flavor,
+                current_version,
+                new_version
+            );
+        });
+
+        // Show what changed instead of updating.
+        if dry_run {
+            for (_, flavor, addon, ..) in addons_to_update.iter() {
+                let version = addon
                     .relevant_release_package()
                     .map(|p| p.version.clone())
                     .unwrap_or_default();
 
-                log::info!(
-                    "\t{} - {}, {} -> {}",
-                    &addon.id,
-                    flavor,
-                    current_version,
-                    new_version
-                );
-            });
+                match fetch_changelog(&endpoints, addon, *flavor).await {
+                    Ok(Some(changelog)) => {
+                        log::info!("Changelog for {} {}:", &addon.id, version);
+
+                        for line in changelog.lines() {
+                            log::info!("\t{}", line);
+                        }
+                    }
+                    Ok(None) => log::info!("No changelog for {} {}", &addon.id, version),
+                    Err(e) => log_error(&e),
+                }
+            }
+
+            return Ok(());
+        }
 
         if num_updates > 0 {
             log::info!("Updating... this may take a minute");
         }
 
-        // Call `update_addon` on each addon concurrently
-        for result in join_all(addons_to_update.into_iter().map(update_addon)).await {
+        for (_, _, result) in apply_updates(&endpoints, addons_to_update).await {
             // Log any errors updating an addon
             if let Err(e) = result {
                 log_error(&e);
@@ -146,24 +110,171 @@ pub fn update_all_addons() -> Result<()> {
     })
 }
 
+/// Parses the addons of both flavors and returns the ones with an update
+/// available, except ignored addons.
+pub async fn find_updates(
+    config: &Config,
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+) -> Result<Vec<PendingUpdate>> {
+    let addons = read_addons(config, fingerprint_collection.clone()).await?;
+
+    Ok(pending_updates(config, fingerprint_collection, addons))
+}
+
+/// Parses the addons of both flavors, applying any saved release channel.
+/// A flavor which can't be read is logged and left out.
+pub async fn read_addons(
+    config: &Config,
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+) -> Result<Vec<(Flavor, Vec<Addon>)>> {
+    let endpoints = config.resolved_endpoints();
+
+    let mut addons_by_flavor = vec![];
+
+    for flavor in Flavor::ALL.iter() {
+        // Only returns None if the path isn't set in the config
+        let addon_directory = config.get_addon_directory_for_flavor(flavor).ok_or_else(|| ClientError::Custom("No WoW directory set. Launch Ajour and make sure a WoW directory is set before using the command line.".to_string()))?;
+
+        let result = read_addon_directory(
+            fingerprint_collection.clone(),
+            &endpoints,
+            &config.catalog_feeds.custom,
+            &config.custom_feed_ids(*flavor),
+            &addon_directory,
+            *flavor,
+        )
+        .await;
+
+        match result {
+            Ok(mut addons) => {
+                // Get any saved release channel preferences from config
+                let release_channels = config
+                    .addons
+                    .release_channels
+                    .get(flavor)
+                    .cloned()
+                    .unwrap_or_default();
+
+                // Apply release channel preference
+                for addon in addons.iter_mut() {
+                    if let Some(channel) = release_channels.get(&addon.id) {
+                        addon.release_channel = *channel;
+                    }
+                }
+
+                addons_by_flavor.push((*flavor, addons));
+            }
+            Err(e) => log::error!("{} - {}", flavor, e),
+        }
+    }
+
+    Ok(addons_by_flavor)
+}
+
+/// Returns the addons with an update available, except ignored addons.
+pub fn pending_updates(
+    config: &Config,
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    addons_by_flavor: Vec<(Flavor, Vec<Addon>)>,
+) -> Vec<PendingUpdate> {
+    let mut addons_to_update = vec![];
+
+    for (flavor, addons) in addons_by_flavor {
+        let addon_directory = config
+            .get_addon_directory_for_flavor(&flavor)
+            .expect("Expected a valid path");
+
+        // Directory to temporarily save downloaded addon
+        let temp_directory = config
+            .get_temporary_addon_directory(flavor)
+            .expect("Expected a valid path");
+
+        // Get any ingnored addons from the config
+        let ignored_ids = config
+            .addons
+            .ignored
+            .get(&flavor)
+            .cloned()
+            .unwrap_or_default();
+
+        // Filter out any ignored addons
+        for addon in addons
+            .into_iter()
+            .filter(|a| !ignored_ids.iter().any(|i| i == &a.id))
+        {
+            // Only add addons that have an update available
+            let is_updatable = addon
+                .relevant_release_package()
+                .map(|package| addon.is_updatable(package))
+                .unwrap_or_default();
+
+            if is_updatable {
+                addons_to_update.push((
+                    fingerprint_collection.clone(),
+                    flavor,
+                    addon,
+                    temp_directory.clone(),
+                    addon_directory.clone(),
+                ));
+            }
+        }
+    }
+
+    addons_to_update
+}
+
+/// Updates the addons concurrently, returning the result for each of them.
+pub async fn apply_updates(
+    endpoints: &Endpoints,
+    addons_to_update: Vec<PendingUpdate>,
+) -> Vec<(Flavor, Addon, Result<()>)> {
+    // Call `update_addon` on each addon concurrently
+    let results = join_all(
+        addons_to_update
+            .iter()
+            .map(|args| update_addon(endpoints, args)),
+    )
+    .await;
+
+    addons_to_update
+        .into_iter()
+        .zip(results)
+        .map(|((_, flavor, addon, ..), result)| (flavor, addon, result))
+        .collect()
+}
+
 /// Updates an addon
 ///
 /// Downloads the latest file, extracts it and refingerprints the addon, saving it to the cache.
 async fn update_addon(
-    (shared_client, fingerprint_collection, flavor, addon, temp_directory, addon_directory): (
-        Arc<HttpClient>,
-        Arc<Mutex<Option<FingerprintCollection>>>,
-        Flavor,
-        Addon,
-        PathBuf,
-        PathBuf,
-    ),
+    endpoints: &Endpoints,
+    (fingerprint_collection, flavor, addon, temp_directory, addon_directory): &PendingUpdate,
 ) -> Result<()> {
     // Download the update to the temp directory
-    download_addon(&shared_client, &addon, &temp_directory).await?;
+    download_addon(addon, temp_directory).await?;
 
+    install_and_fingerprint_addon(
+        fingerprint_collection.clone(),
+        endpoints,
+        *flavor,
+        addon,
+        temp_directory,
+        addon_directory,
+    )
+    .await
+}
+
+/// Extracts a downloaded addon and refingerprints it, saving it to the cache.
+pub async fn install_and_fingerprint_addon(
+    fingerprint_collection: Arc<Mutex<Option<FingerprintCollection>>>,
+    endpoints: &Endpoints,
+    flavor: Flavor,
+    addon: &Addon,
+    temp_directory: &PathBuf,
+    addon_directory: &PathBuf,
+) -> Result<()> {
     // Extracts addon from the downloaded archive to the addon directory and removes the archive
-    install_addon(&addon, &temp_directory, &addon_directory).await?;
+    install_addon(addon, temp_directory, addon_directory).await?;
 
     // Stores each folder name we need to fingerprint
     let mut folders_to_fingerprint = vec![];
@@ -172,16 +283,16 @@ async fn update_addon(
     folders_to_fingerprint.push((
         fingerprint_collection.clone(),
         flavor,
-        &addon_directory,
+        addon_directory,
         addon.id.clone(),
     ));
 
-    // Store all dependencies
-    folders_to_fingerprint.extend(addon.dependencies.iter().map(|id| {
+    // Store all bundled modules
+    folders_to_fingerprint.extend(addon.bundled_modules.iter().map(|id| {
         (
             fingerprint_collection.clone(),
             flavor,
-            &addon_directory,
+            addon_directory,
             id.clone(),
         )
     }));
@@ -189,7 +300,13 @@ async fn update_addon(
     // Call `update_addon_fingerprint` on each folder concurrently
     for result in join_all(folders_to_fingerprint.into_iter().map(
         |(fingerprint_collection, flavor, addon_dir, addon_id)| {
-            update_addon_fingerprint(fingerprint_collection, flavor, addon_dir, addon_id)
+            update_addon_fingerprint(
+                fingerprint_collection,
+                endpoints,
+                flavor,
+                addon_dir,
+                addon_id,
+            )
         },
     ))
     .await
@@ -0,0 +1,140 @@
+use crate::log_error;
+use crate::update::{install_and_fingerprint_addon, read_addons};
+
+use ajour_core::config::load_config;
+use ajour_core::error::ClientError;
+use ajour_core::network::download_addon_package;
+use ajour_core::verify::verify_addons;
+use ajour_core::Result;
+
+use async_std::sync::{Arc, Mutex};
+use async_std::task;
+
+/// Verifies the files of all installed addons against the remote file they were
+/// installed from. If `repair` is set, damaged addons are reinstalled.
+pub fn verify_all_addons(repair: bool) -> Result<()> {
+    log::info!("Verifying addon files...");
+
+    task::block_on(async {
+        let config = load_config().await?;
+        let endpoints = config.resolved_endpoints();
+
+        // Fingerprint cache will be fetched during `read_addon_directory`
+        let fingerprint_collection: Arc<Mutex<_>> = Default::default();
+
+        let mut num_damaged = 0;
+        let mut num_with_extra_files = 0;
+        let mut num_errors = 0;
+
+        let addons_by_flavor = read_addons(&config, fingerprint_collection.clone()).await?;
+
+        for (flavor, addons) in addons_by_flavor {
+            // Only returns None if the path isn't set in the config
+            let addon_directory = config.get_addon_directory_for_flavor(&flavor).ok_or_else(|| ClientError::Custom("No WoW directory set. Launch Ajour and make sure a WoW directory is set before using the command line.".to_string()))?;
+
+            // Get any ignored addons from the config
+            let ignored_ids = config
+                .addons
+                .ignored
+                .get(&flavor)
+                .cloned()
+                .unwrap_or_default();
+
+            // Filter out any ignored addons
+            let addons: Vec<_> = addons
+                .into_iter()
+                .filter(|a| !ignored_ids.iter().any(|i| i == &a.id))
+                .collect();
+
+            let results = verify_addons(&endpoints, &addon_directory, &addons).await?;
+
+            for integrity in results
+                .iter()
+                .filter(|r| !r.is_intact() || !r.extra.is_empty())
+            {
+                log::info!(
+                    "\t{} - {}, modified: [{}], missing: [{}], extra: [{}]",
+                    &integrity.addon_id,
+                    flavor,
+                    integrity.modified.join(", "),
+                    integrity.missing.join(", "),
+                    integrity.extra.join(", ")
+                );
+
+                if !integrity.extra.is_empty() {
+                    num_with_extra_files += 1;
+                }
+
+                // Extra files might have been added on purpose, so they aren't repaired.
+                if integrity.is_intact() {
+                    continue;
+                }
+
+                num_damaged += 1;
+
+                if !repair {
+                    continue;
+                }
+
+                let addon = match addons.iter().find(|a| a.id == integrity.addon_id) {
+                    Some(addon) => addon,
+                    None => continue,
+                };
+
+                // Directory to temporarily save downloaded addon
+                let temp_directory = config
+                    .get_temporary_addon_directory(flavor)
+                    .expect("Expected a valid path");
+
+                let result = match download_addon_package(
+                    addon,
+                    &integrity.package,
+                    &temp_directory,
+                )
+                .await
+                {
+                    Ok(_) => {
+                        install_and_fingerprint_addon(
+                            fingerprint_collection.clone(),
+                            &endpoints,
+                            flavor,
+                            addon,
+                            &temp_directory,
+                            &addon_directory,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = result {
+                    log_error(&e);
+
+                    num_errors += 1;
+                }
+            }
+        }
+
+        if num_with_extra_files > 0 {
+            log::info!(
+                "{} addons have extra files which no .toc or .xml file references",
+                num_with_extra_files
+            );
+        }
+
+        if num_damaged == 0 {
+            log::info!("All addon files are intact!");
+        } else if !repair {
+            log::info!(
+                "{} addons have modified or missing files. Use `ajour verify --repair` to reinstall them",
+                num_damaged
+            );
+        } else if num_errors > 0 {
+            log::error!("{} addons failed to be repaired", num_errors);
+        } else {
+            log::info!("{} addons repaired successfully!", num_damaged);
+        }
+
+        Result::Ok(())
+    })
+}